@@ -193,6 +193,27 @@ where
                     signal=enum_slice!(|write| Condition::ProcessMixShareFeature(feat, comp, [value])) />
             }.into_view(),
 
+            Condition::OutputMixShareFeature(output, feat, comp, value) => view! {
+                <div class="input-help">"Compare against the share (percentage) of an output's mix from processes with a particular feature."</div>
+                <EnumInput
+                    label="Output"
+                    help="Which output's mix to compare against."
+                    signal=enum_slice!(|write| Condition::OutputMixShareFeature([output], feat, comp, value)) />
+                <EnumInput
+                    label="Process"
+                    help="Which process feature to compare against."
+                    signal=enum_slice!(|write| Condition::OutputMixShareFeature(output, [feat], comp, value)) />
+                <EnumInput
+                    label="Comparator"
+                    help="The comparison operation."
+                    signal=enum_slice!(|write| Condition::OutputMixShareFeature(output, feat, [comp], value)) />
+                <PercentInput
+                    inline=true
+                    label="Mix Share"
+                    help="The mix share to compare against."
+                    signal=enum_slice!(|write| Condition::OutputMixShareFeature(output, feat, comp, [value])) />
+            }.into_view(),
+
             Condition::ResourcePressure(resource, comp, value) => view! {
                 <div class="input-help">"Compare against the pressure on a particular resource. Pressure is represented as a percentage, where 0% means there is no pressure on the resource (demand for it is 0) and 100% means the demand for the resource equals its total supply."</div>
                 <EnumInput
@@ -6,6 +6,7 @@ use hes_engine::{
     Industry,
     Process,
     Project,
+    Region,
     WorldVariable,
     NPC,
 };
@@ -30,6 +31,8 @@ where
     let industries =
         expect_context::<Signal<Collection<Ref<Industry>>>>();
     let npcs = expect_context::<Signal<Collection<Ref<NPC>>>>();
+    let regions =
+        expect_context::<Signal<Collection<Ref<Region>>>>();
 
     let input = move || {
         match read.get() {
@@ -133,6 +136,20 @@ where
                     signal=enum_slice!(|write| Effect::RegionHabitability(lat, [value])) />
             }.into_view(),
 
+            Effect::RegionHabitabilityById(id, value) => view! {
+                <div class="input-help">"Modify the habitability of a single region. Has no effect if the region has seceded."</div>
+                <EntityPicker
+                    label="Region"
+                    opts=regions
+                    help="Which region is affected."
+                    signal=enum_slice!(|write| Effect::RegionHabitabilityById([id], value)) />
+                <NumericInput
+                    inline=true
+                    label="Value"
+                    help="The amount to change the habitability by."
+                    signal=enum_slice!(|write| Effect::RegionHabitabilityById(id, [value])) />
+            }.into_view(),
+
             Effect::Resource(resource, value) => view! {
                 <div class="input-help">"Modify the availability of the specified resource by an absolute amount. Note that this won't do anything for fuel and electricity as those are dynamically calculated."</div>
                 <EnumInput
@@ -252,6 +269,20 @@ where
                     signal=enum_slice!(|write| Effect::ProcessLimit(id, [value])) />
             }.into_view(),
 
+            Effect::ModifyProcessLimitPercent(id, value) => view! {
+                <div class="input-help">"Modify the maximum output of a process by a percentage. If the process has no limit, one is first set based on its current mix share."</div>
+                <EntityPicker
+                    label="Process"
+                    opts=processes
+                    help="Which process is affected."
+                    signal=enum_slice!(|write| Effect::ModifyProcessLimitPercent([id], value)) />
+                <PercentInput
+                    inline=true
+                    label="Percent Change"
+                    help="The percent to modify the limit by."
+                    signal=enum_slice!(|write| Effect::ModifyProcessLimitPercent(id, [value])) />
+            }.into_view(),
+
             Effect::Feedstock(feedstock, value) => view! {
                 <div class="input-help">"Modify the specified feedstock's reserves by a percentage."</div>
                 <EnumInput
@@ -384,6 +415,14 @@ where
                     signal=enum_slice!(|write| Effect::AddFlag([flag])) />
             }.into_view(),
 
+            Effect::RemoveFlag(flag) => view! {
+                <div class="input-help">"Unset a flag."</div>
+                <EnumInput
+                    label="Flag"
+                    help="Which flag to remove."
+                    signal=enum_slice!(|write| Effect::RemoveFlag([flag])) />
+            }.into_view(),
+
             Effect::NPCRelationship(id, change) => view! {
                 <div class="input-help">"Change the relationship with an NPC."</div>
                 <EntityPicker
@@ -398,6 +437,20 @@ where
                     signal=enum_slice!(|write| Effect::NPCRelationship(id, [change])) />
             }.into_view(),
 
+            Effect::NPCSeats(id, change) => view! {
+                <div class="input-help">"Change the share of parliamentary seats an NPC holds. Seats can't go below zero."</div>
+                <EntityPicker
+                    label="NPC"
+                    opts=npcs
+                    help="Which NPC's seats are affected."
+                    signal=enum_slice!(|write| Effect::NPCSeats([id], change)) />
+                <NumericInput
+                    inline=true
+                    label="Value"
+                    help="The amount to change the seats by, as a fraction of all seats."
+                    signal=enum_slice!(|write| Effect::NPCSeats(id, [change])) />
+            }.into_view(),
+
             Effect::ModifyProcessByproducts(id, byproduct, value) => view! {
                 <div class="input-help">"Modify the amount of a single byproduct for a single process by a percentage."</div>
                 <EntityPicker
@@ -559,6 +612,58 @@ where
             Effect::GameOver => view! {
                 <div class="input-help">"Trigger an immediate game over."</div>
             }.into_view(),
+
+            Effect::All(..) => {
+                let inner = (
+                    Signal::derive(move || match read.get() {
+                        Effect::All(effects) => effects,
+                        _ => vec![],
+                    }),
+                    SignalSetter::map(move |effects: Vec<Effect>| {
+                        write.set(Effect::All(effects));
+                    }),
+                );
+                view! {
+                    <div class="input-help">"A bundle of effects which are applied and scaled together."</div>
+                    <Effects effects=inner />
+                }.into_view()
+            }
+
+            Effect::Temporary(..) => {
+                let years = (
+                    Signal::derive(move || match read.get() {
+                        Effect::Temporary(_, years) => years,
+                        _ => 0,
+                    }),
+                    SignalSetter::map(move |years| {
+                        if let Effect::Temporary(effect, _) = read.get_untracked() {
+                            write.set(Effect::Temporary(effect, years));
+                        }
+                    }),
+                );
+                let inner = (
+                    Signal::derive(move || match read.get() {
+                        Effect::Temporary(effect, _) => vec![*effect],
+                        _ => vec![],
+                    }),
+                    SignalSetter::map(move |effects: Vec<Effect>| {
+                        if let Effect::Temporary(_, years) = read.get_untracked() {
+                            if let Some(effect) = effects.into_iter().next() {
+                                write.set(Effect::Temporary(Box::new(effect), years));
+                            }
+                        }
+                    }),
+                );
+                view! {
+                    <div class="input-help">"Applies an effect which is reversed after some number of years. Only the first effect below is used."</div>
+                    <NumericInput
+                        inline=true
+                        label="Years"
+                        help="How many years until the effect is reversed."
+                        signal=years />
+                    <Effects effects=inner />
+                }.into_view()
+            }
         }
     };
     let label = move || {
@@ -595,6 +700,8 @@ pub fn Effects(
     let industries =
         expect_context::<Signal<Collection<Ref<Industry>>>>();
     let npcs = expect_context::<Signal<Collection<Ref<NPC>>>>();
+    let regions =
+        expect_context::<Signal<Collection<Ref<Region>>>>();
 
     let default_process =
         move || with!(|processes| processes.first().id);
@@ -605,6 +712,8 @@ pub fn Effects(
     let default_event =
         move || with!(|events| events.first().id);
     let default_npc = move || with!(|npcs| npcs.first().id);
+    let default_region =
+        move || with!(|regions| regions.first().id);
 
     view! {
         <div class="effects mutable-list" class:mutable-list-double-col={double_col}>
@@ -623,6 +732,7 @@ pub fn Effects(
                             default_industry(),
                             default_event(),
                             default_npc(),
+                            default_region(),
                             );
                         let mut effects = read.get();
                         effects.insert(0, effect);
@@ -85,6 +85,13 @@ pub fn App() -> impl IntoView {
             .map(|item| item.as_ref())
             .collect::<Collection<Ref<_>>>())
     }));
+    provide_context(Signal::derive(move || {
+        with!(|world| world
+            .regions
+            .iter()
+            .map(|item| item.as_ref())
+            .collect::<Collection<Ref<_>>>())
+    }));
     provide_context(Signal::derive(move || {
         with!(|world| world
             .industries
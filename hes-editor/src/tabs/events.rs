@@ -29,10 +29,31 @@ fn Event(
                         <TextInput signal=subsignal!(event.flavor.arc) />
                         <div class="input-help">Optional story arc name.</div>
                     </div>
+                    <TextInput
+                        label="Arc ID"
+                        help="Optional arc this event is a step of. Leave empty if it isn't part of a chain."
+                        signal=(
+                            Signal::derive(move || {
+                                event.0.with(|event| event.arc.clone().unwrap_or_default())
+                            }),
+                            SignalSetter::map(move |arc: String| {
+                                let mut ev = event.0.get();
+                                ev.arc = (!arc.is_empty()).then_some(arc);
+                                event.1.set(ev);
+                            }),
+                        ) />
+                    <NumericInput
+                        label="Arc Step"
+                        help="The event's position in its arc. It can only occur after the previous step has."
+                        signal=subsignal!(event.arc_step) />
                     <EnumInput
                         label="Phase"
                         help="What phase/screen the event can occur on."
                         signal=subsignal!(event.phase) />
+                    <ToggleInput
+                        label="Variable Severity"
+                        help="If the event's severity is rolled when it occurs, scaling its effects."
+                        signal=subsignal!(event.variable_severity) />
                 </div>
             </div>
 
@@ -9,6 +9,8 @@ fn Project(
     let project = signal;
 
     let npcs = expect_context::<Signal<Collection<Ref<NPC>>>>();
+    let projects =
+        expect_context::<Signal<Collection<Ref<Project>>>>();
 
     view! {
         <div class="project" id={move || signal.0.with(|signal| signal.id.to_string())}>
@@ -67,6 +69,12 @@ fn Project(
                     signal=subsignal!(project.opposers)
                     opts=npcs
                     />
+                <MultiEntitySelect
+                    label="Requires"
+                    help="Projects that must be completed before this one is unlocked."
+                    signal=subsignal!(project.requires)
+                    opts=projects
+                    />
             </div>
 
             <div class="item-form effects-form">
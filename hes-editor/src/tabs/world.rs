@@ -243,6 +243,22 @@ fn Regions(world: RwSignal<World>) -> impl IntoView {
                              }).collect::<Vec<_>>()
                          }}
                      </div>
+                     <div class="input-column">
+                         <label>"Coastal Exposure"</label>
+                        {move || {
+                             (0..n_regions).map(|i| {
+                                 view! {
+                                  <NumericInput
+                                      label="Coastal Exposure"
+                                      help="How exposed the region is to sea level rise, from 0.0 (entirely inland) to 1.0 (entirely coastal)."
+                                      signal=create_slice(world,
+                                          move |world| world.regions.by_idx(i).coastal_exposure,
+                                          move |world, val| world.regions.by_idx_mut(i).coastal_exposure = val
+                                      ) />
+                                 }
+                             }).collect::<Vec<_>>()
+                         }}
+                     </div>
                 </div>
             </div>
         </div>
@@ -0,0 +1,189 @@
+use crate::{
+    events::{Comparator, Condition, WorldVariable},
+    state::State,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Identifies an achievement, e.g. "low-emissions".
+pub type AchievementId = String;
+
+/// An achievement, unlocked the first time
+/// all of its conditions are met.
+#[derive(
+    Debug, Clone, Default, Serialize, Deserialize, PartialEq,
+)]
+pub struct Achievement {
+    pub id: AchievementId,
+    pub name: String,
+    pub conditions: Vec<Condition>,
+}
+
+impl Achievement {
+    fn is_met(&self, state: &State) -> bool {
+        self.conditions
+            .iter()
+            .all(|cond| cond.eval(state, None))
+    }
+
+    /// The achievements for worlds which don't define their own.
+    pub fn defaults() -> Vec<Achievement> {
+        let achievement =
+            |id: &str, name: &str, condition| Achievement {
+                id: id.into(),
+                name: name.into(),
+                conditions: vec![condition],
+            };
+        vec![
+            // Emissions are in g CO2eq.
+            achievement(
+                "low-emissions",
+                "Low Emissions",
+                Condition::WorldVariable(
+                    WorldVariable::Emissions,
+                    Comparator::Less,
+                    5e15,
+                ),
+            ),
+            achievement(
+                "net-zero",
+                "Net Zero",
+                Condition::WorldVariable(
+                    WorldVariable::Emissions,
+                    Comparator::LessEqual,
+                    0.,
+                ),
+            ),
+            achievement(
+                "cooling",
+                "Cooling Down",
+                Condition::WorldVariable(
+                    WorldVariable::Temperature,
+                    Comparator::Less,
+                    1.,
+                ),
+            ),
+            achievement(
+                "half-earth",
+                "Half-Earth",
+                Condition::ProtectLand(
+                    Comparator::GreaterEqual,
+                    0.5,
+                ),
+            ),
+        ]
+    }
+}
+
+/// Progress towards the world's achievements
+/// (see `World::achievements`).
+#[derive(
+    Debug, Clone, Default, Serialize, Deserialize, PartialEq,
+)]
+pub struct Achievements {
+    /// Unlocked achievements and the year they were unlocked in.
+    pub unlocked: BTreeMap<AchievementId, usize>,
+
+    /// Achievements unlocked by the most recent update.
+    pub newly_unlocked: Vec<AchievementId>,
+}
+
+impl Achievements {
+    /// Check any achievements which haven't been unlocked yet,
+    /// recording those which are now met.
+    pub fn update(
+        &mut self,
+        achievements: &[Achievement],
+        state: &State,
+    ) -> &[AchievementId] {
+        self.newly_unlocked.clear();
+        for achievement in achievements {
+            if !self.unlocked.contains_key(&achievement.id)
+                && achievement.is_met(state)
+            {
+                self.unlocked.insert(
+                    achievement.id.clone(),
+                    state.world.year,
+                );
+                self.newly_unlocked
+                    .push(achievement.id.clone());
+            }
+        }
+        &self.newly_unlocked
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains_key(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::PlayerVariable,
+        state::Emissions,
+        World,
+    };
+
+    #[test]
+    fn test_unlocks_once() {
+        let world = World {
+            achievements: vec![Achievement {
+                id: "rich".into(),
+                name: "Rich".into(),
+                conditions: vec![Condition::PlayerVariable(
+                    PlayerVariable::PoliticalCapital,
+                    Comparator::GreaterEqual,
+                    200.,
+                )],
+            }],
+            ..Default::default()
+        };
+        let mut state = State {
+            political_capital: 100,
+            ..State::new(world)
+        };
+
+        state.update_achievements();
+        assert!(state.newly_unlocked_achievements().is_empty());
+        assert!(!state.achievements.is_unlocked("rich"));
+
+        state.political_capital = 250;
+        state.update_achievements();
+        assert_eq!(
+            state.newly_unlocked_achievements(),
+            vec!["rich"]
+        );
+        assert_eq!(
+            state.achievements.unlocked.get("rich"),
+            Some(&state.world.year)
+        );
+
+        // Still met, but already unlocked.
+        state.update_achievements();
+        assert!(state.newly_unlocked_achievements().is_empty());
+
+        // Met again after no longer being met.
+        state.political_capital = 100;
+        state.update_achievements();
+        state.political_capital = 250;
+        state.update_achievements();
+        assert!(state.newly_unlocked_achievements().is_empty());
+        assert!(state.achievements.is_unlocked("rich"));
+    }
+
+    #[test]
+    fn test_default_achievements() {
+        let mut state = State::default();
+        state.update_achievements();
+        assert!(state.newly_unlocked_achievements().is_empty());
+
+        state.emissions = Emissions::default();
+        state.update_achievements();
+        assert_eq!(
+            state.newly_unlocked_achievements(),
+            vec!["low-emissions", "net-zero"]
+        );
+    }
+}
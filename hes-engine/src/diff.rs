@@ -220,7 +220,7 @@ impl Diff for World {
 
 impl Diff for Project {
     fn diff(&self, other: &Self) -> Vec<Change> {
-        diffs!(self, other, { status, level, active_outcome })
+        diffs!(self, other, { status, level, active_outcomes })
     }
 }
 impl DiffLabel for Project {
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoStaticStr};
+
+/// Difficulty presets, which scale project costs,
+/// event probabilities, and starting political capital.
+/// See `State::apply_difficulty`.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+    Display,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Multiplier on all project costs.
+    pub fn cost_modifier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.8,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.25,
+        }
+    }
+
+    /// Multiplier on all event probabilities.
+    pub fn event_modifier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Change to the starting political capital.
+    pub fn political_capital(&self) -> isize {
+        match self {
+            Difficulty::Easy => 50,
+            Difficulty::Normal => 0,
+            Difficulty::Hard => -30,
+        }
+    }
+}
@@ -52,6 +52,16 @@ pub enum Condition {
     ProcessOutput(Id, Comparator, f32),
     ProcessMixShare(Id, Comparator, f32),
     ProcessMixShareFeature(ProcessFeature, Comparator, f32),
+
+    /// The fraction (0 to 1) of an output's mix
+    /// made up of processes with the feature,
+    /// e.g. how much electricity is nuclear.
+    OutputMixShareFeature(
+        Output,
+        ProcessFeature,
+        Comparator,
+        f32,
+    ),
     ResourcePressure(Resource, Comparator, f32),
     ResourceDemandGap(Resource, Comparator, f32),
     OutputDemandGap(Output, Comparator, f32),
@@ -60,11 +70,17 @@ pub enum Condition {
     ActiveProjectUpgrades(Id, Comparator, usize),
     RunsPlayed(Comparator, usize),
     RegionFlag(RegionFlag),
+
+    /// Whether the NPC is currently in this relation,
+    /// see `NPC::relation`.
     NPCRelationship(Id, NPCRelation),
     FeedstockYears(Feedstock, Comparator, f32),
     HasFlag(Flag),
     WithoutFlag(Flag),
     HeavyProjects(Comparator, usize),
+
+    /// The fraction (0 to 1) of land under protection,
+    /// i.e. `State::protected_land`.
     ProtectLand(Comparator, f32),
     WaterStress(Comparator, f32),
 }
@@ -112,6 +128,14 @@ impl Condition {
                     0.,
                 )
             }
+            ConditionKind::OutputMixShareFeature => {
+                Self::OutputMixShareFeature(
+                    Output::Electricity,
+                    ProcessFeature::IsCCS,
+                    comp,
+                    0.,
+                )
+            }
             ConditionKind::ResourcePressure => {
                 Self::ResourcePressure(Resource::Land, comp, 0.)
             }
@@ -303,6 +327,36 @@ impl Condition {
                     .sum();
                 comp.eval(val, *other_val)
             }
+            Condition::OutputMixShareFeature(
+                output,
+                feat,
+                comp,
+                other_val,
+            ) => {
+                let (feat_share, total_share) = state
+                    .world
+                    .processes
+                    .iter()
+                    .filter(|p| p.output == *output)
+                    .fold((0, 0), |(feat_share, total), p| {
+                        let share = if p.features.contains(feat)
+                        {
+                            p.mix_share
+                        } else {
+                            0
+                        };
+                        (
+                            feat_share + share,
+                            total + p.mix_share,
+                        )
+                    });
+                let val = if total_share == 0 {
+                    0.
+                } else {
+                    feat_share as f32 / total_share as f32
+                };
+                comp.eval(val, *other_val)
+            }
             Condition::ResourcePressure(
                 resource,
                 comp,
@@ -380,13 +434,14 @@ impl Condition {
                 state.world.projects[id].level as f32,
                 *upgrades as f32,
             ),
-            Condition::NPCRelationship(id, relation) => {
-                state.npcs[id].relation() == *relation
-            }
+            Condition::NPCRelationship(id, relation) => state
+                .npcs
+                .try_get(id)
+                .is_some_and(|npc| npc.relation() == *relation),
             Condition::RegionFlag(flag) => {
                 if let Some(id) = &region_id {
                     let region = &state.world.regions[id];
-                    region.flags.contains(flag)
+                    region.has_flag(*flag)
                 } else {
                     false
                 }
@@ -413,10 +468,7 @@ impl Condition {
                 comp.eval(state.protected_land, *n)
             }
             Condition::WaterStress(comp, n) => {
-                let water_stress =
-                    state.resource_demand.of(Resource::Water)
-                        / state.resources.available.water;
-                comp.eval(water_stress, *n)
+                comp.eval(state.water_stress(), *n)
             }
         }
     }
@@ -501,4 +553,133 @@ mod tests {
         state.produced.amount.plant_calories = 50.;
         assert_eq!(cond.eval(&state, None), true);
     }
+
+    #[test]
+    fn test_npc_relationship() {
+        use crate::npcs::{
+            ALLY_THRESHOLD,
+            FRIENDLY_THRESHOLD,
+            NEMESIS_THRESHOLD,
+        };
+        use strum::IntoEnumIterator;
+
+        let mut state = State::default();
+        let id = state.npcs.by_idx(0).id;
+        let bands = [
+            (0., NPCRelation::Nemesis),
+            (NEMESIS_THRESHOLD, NPCRelation::Nemesis),
+            (NEMESIS_THRESHOLD + 0.5, NPCRelation::Neutral),
+            (FRIENDLY_THRESHOLD, NPCRelation::Neutral),
+            (ALLY_THRESHOLD - 0.5, NPCRelation::Neutral),
+            (ALLY_THRESHOLD, NPCRelation::Ally),
+            (ALLY_THRESHOLD + 1., NPCRelation::Ally),
+        ];
+        for (relationship, expected) in bands {
+            state.npcs[&id].relationship = relationship;
+            for relation in NPCRelation::iter() {
+                let cond =
+                    Condition::NPCRelationship(id, relation);
+                assert_eq!(
+                    cond.eval(&state, None),
+                    relation == expected,
+                    "{relationship} {relation:?}"
+                );
+            }
+        }
+
+        // Unknown NPCs are in no relation.
+        let cond = Condition::NPCRelationship(
+            Id::new_v4(),
+            NPCRelation::Neutral,
+        );
+        assert!(!cond.eval(&state, None));
+    }
+
+    #[test]
+    fn test_protect_land() {
+        use crate::events::{Effect, Likelihood, Probability};
+
+        let mut state = State {
+            protected_land: 0.1,
+            ..Default::default()
+        };
+        let prob = Probability {
+            likelihood: Likelihood::Guaranteed,
+            conditions: vec![Condition::ProtectLand(
+                Comparator::GreaterEqual,
+                0.3,
+            )],
+        };
+        assert!(prob.eval(&state, None).is_none());
+
+        state.protected_land = 0.25;
+        assert!(prob.eval(&state, None).is_none());
+
+        // Crossing it through the effect.
+        let effect = Effect::ProtectLand(0.1);
+        effect.apply(&mut state, None);
+        assert!(prob.eval(&state, None).is_some());
+
+        // And back again.
+        effect.unapply(&mut state, None);
+        assert!(prob.eval(&state, None).is_none());
+    }
+
+    #[test]
+    fn test_output_mix_share_feature() {
+        let mut state = State::default();
+        let mut nuclear = false;
+        let mut other = false;
+        for process in state.world.processes.iter_mut() {
+            process.mix_share = 0;
+            if process.output != Output::Electricity {
+                continue;
+            }
+            let is_nuclear = process
+                .features
+                .contains(&ProcessFeature::CanMeltdown);
+            if is_nuclear && !nuclear {
+                process.mix_share = 10;
+                nuclear = true;
+            } else if !is_nuclear && !other {
+                process.mix_share = 10;
+                other = true;
+            }
+        }
+        assert!(nuclear && other);
+
+        // Nuclear is exactly half of electricity.
+        let cases = [
+            (Comparator::Less, 0.5, false),
+            (Comparator::LessEqual, 0.5, true),
+            (Comparator::Equal, 0.5, true),
+            (Comparator::NotEqual, 0.5, false),
+            (Comparator::GreaterEqual, 0.5, true),
+            (Comparator::Greater, 0.5, false),
+            (Comparator::Greater, 0.49, true),
+            (Comparator::Less, 0.51, true),
+        ];
+        for (comp, val, expected) in cases {
+            let cond = Condition::OutputMixShareFeature(
+                Output::Electricity,
+                ProcessFeature::CanMeltdown,
+                comp,
+                val,
+            );
+            assert_eq!(
+                cond.eval(&state, None),
+                expected,
+                "{comp:?} {val}"
+            );
+        }
+
+        // No fuel is nuclear, and there's no fuel mix at all.
+        let cond = Condition::OutputMixShareFeature(
+            Output::Fuel,
+            ProcessFeature::CanMeltdown,
+            Comparator::Greater,
+            0.,
+        );
+        assert!(!cond.eval(&state, None));
+    }
 }
@@ -1,9 +1,18 @@
 use super::{PlayerVariable, WorldVariable};
 use crate::{
-    kinds::{Byproduct, Feedstock, Output, Resource},
-    production::ProcessFeature,
+    kinds::{
+        Byproduct,
+        Feedstock,
+        Output,
+        OutputDemand,
+        Resource,
+    },
+    npcs::NPCKind,
+    production::{FeatureModifier, ProcessFeature},
     regions::{Latitude, Region},
     state::State,
+    Collection,
+    HasId,
     Id,
 };
 use serde::{Deserialize, Serialize};
@@ -19,6 +28,73 @@ use strum::{
 const MIGRATION_WAVE_PERCENT_POP: f32 = 0.1;
 const CLOSED_BORDERS_MULTILPIER: f32 = 0.5;
 
+/// A record of a single migration wave, kept
+/// so that the wave can be reversed exactly.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MigrationWave {
+    /// How many people left the origin region.
+    pub leave_pop: f32,
+
+    /// Which regions received migrants and how many.
+    pub arrivals: Vec<(Id, f32)>,
+}
+
+/// A change made to the game state by applying an effect.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EffectChange {
+    /// The variable that was changed,
+    /// identified by the effect's fingerprint.
+    pub variable: String,
+
+    /// How much the variable changed by.
+    /// Effects without a magnitude (e.g. unlocks or flags)
+    /// have a delta of zero.
+    pub delta: f32,
+
+    /// The region that was affected, if any.
+    pub region_id: Option<Id>,
+}
+
+/// An effect which references something
+/// that doesn't exist.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EffectError {
+    MissingProcess(Id),
+    MissingProject(Id),
+    MissingIndustry(Id),
+    MissingEvent(Id),
+    MissingNPC(Id),
+    MissingRegion(Id),
+}
+impl std::fmt::Display for EffectError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::MissingProcess(id) => {
+                write!(f, "No process with id {id}")
+            }
+            Self::MissingProject(id) => {
+                write!(f, "No project with id {id}")
+            }
+            Self::MissingIndustry(id) => {
+                write!(f, "No industry with id {id}")
+            }
+            Self::MissingEvent(id) => {
+                write!(f, "No event with id {id}")
+            }
+            Self::MissingNPC(id) => {
+                write!(f, "No NPC with id {id}")
+            }
+            Self::MissingRegion(id) => {
+                write!(f, "No region with id {id}")
+            }
+        }
+    }
+}
+impl std::error::Error for EffectError {}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
 pub enum Request {
     Project,
@@ -128,6 +204,7 @@ pub enum Effect {
     WorldVariable(WorldVariable, f32),
     PlayerVariable(PlayerVariable, f32),
     RegionHabitability(Latitude, f32),
+    RegionHabitabilityById(Id, f32),
 
     Resource(Resource, f32),
     Demand(Output, f32),
@@ -138,6 +215,11 @@ pub enum Effect {
     CO2ForFeature(ProcessFeature, f32),
     BiodiversityPressureForFeature(ProcessFeature, f32),
     ProcessLimit(Id, f32),
+
+    /// Scales a process's limit by a percentage.
+    /// If the process has no limit, one is first set
+    /// based on its current mix share.
+    ModifyProcessLimitPercent(Id, f32),
     Feedstock(Feedstock, f32),
 
     AddEvent(Id),
@@ -156,7 +238,9 @@ pub enum Effect {
     AddRegionFlag(RegionFlag),
 
     AddFlag(Flag),
+    RemoveFlag(Flag),
     NPCRelationship(Id, f32),
+    NPCSeats(Id, f32),
 
     ModifyProcessByproducts(Id, Byproduct, f32),
     ModifyIndustryByproducts(Id, Byproduct, f32),
@@ -170,8 +254,19 @@ pub enum Effect {
 
     ProtectLand(f32),
 
+    /// Clears any political capital debt
+    /// (i.e. negative political capital)
+    /// and then grants the given amount.
     BailOut(usize),
     GameOver,
+
+    /// Applies the inner effect, which is then
+    /// reversed after the given number of years.
+    Temporary(Box<Effect>, usize),
+
+    /// A bundle of effects which are
+    /// applied and scaled together.
+    All(Vec<Effect>),
 }
 impl AsRef<Effect> for Effect {
     fn as_ref(&self) -> &Effect {
@@ -179,14 +274,51 @@ impl AsRef<Effect> for Effect {
     }
 }
 
-fn check_game_over(state: &mut State) {
-    if !state.npcs.is_ally("The Authoritarian")
+fn maybe_game_over(state: &mut State) {
+    if !state.npcs.is_ally(NPCKind::Authoritarian)
         && state.outlook() < 0.
     {
         state.game_over = true;
     }
 }
 
+/// The total temperature change from the
+/// solar radiation management project's active effects,
+/// if the project exists.
+fn srm_temperature_change(state: &State) -> Option<f32> {
+    let project =
+        state.world.projects.iter().find(|p| p.is_srm())?;
+    let temp = project
+        .active_effects()
+        .iter()
+        .filter_map(|eff| match eff {
+            Effect::WorldVariable(
+                WorldVariable::Temperature,
+                val,
+            ) => Some(*val),
+            _ => None,
+        })
+        .sum();
+    Some(temp)
+}
+
+/// The display name of the item with the given id,
+/// or a placeholder if it doesn't exist.
+fn name_of<T: HasId + std::fmt::Display>(
+    items: &Collection<T>,
+    id: &Id,
+) -> String {
+    items.try_get(id).map_or_else(
+        || "Unknown".into(),
+        |item| item.to_string(),
+    )
+}
+
+/// Format a fractional change as a signed percent.
+fn signed_percent(value: f32) -> String {
+    format!("{:+}%", (value * 100.).round())
+}
+
 impl Default for Effect {
     fn default() -> Self {
         Effect::PlayerVariable(
@@ -204,6 +336,7 @@ impl Effect {
         default_industry: Id,
         default_event: Id,
         default_npc: Id,
+        default_region: Id,
     ) -> Self {
         match kind {
             EffectKind::WorldVariable => Effect::WorldVariable(
@@ -219,6 +352,12 @@ impl Effect {
             EffectKind::RegionHabitability => {
                 Effect::RegionHabitability(Latitude::Tropic, 0.)
             }
+            EffectKind::RegionHabitabilityById => {
+                Effect::RegionHabitabilityById(
+                    default_region,
+                    0.,
+                )
+            }
             EffectKind::Resource => {
                 Effect::Resource(Resource::Land, 0.)
             }
@@ -252,6 +391,12 @@ impl Effect {
             EffectKind::ProcessLimit => {
                 Effect::ProcessLimit(default_process, 0.)
             }
+            EffectKind::ModifyProcessLimitPercent => {
+                Effect::ModifyProcessLimitPercent(
+                    default_process,
+                    0.,
+                )
+            }
             EffectKind::Feedstock => {
                 Effect::Feedstock(Feedstock::Coal, 0.)
             }
@@ -296,9 +441,15 @@ impl Effect {
                 Effect::AddRegionFlag(RegionFlag::Protests)
             }
             EffectKind::AddFlag => Effect::AddFlag(Flag::Vegan),
+            EffectKind::RemoveFlag => {
+                Effect::RemoveFlag(Flag::ClosedBorders)
+            }
             EffectKind::NPCRelationship => {
                 Effect::NPCRelationship(default_npc, 0.)
             }
+            EffectKind::NPCSeats => {
+                Effect::NPCSeats(default_npc, 0.)
+            }
             EffectKind::ModifyProcessByproducts => {
                 Effect::ModifyProcessByproducts(
                     default_process,
@@ -351,6 +502,11 @@ impl Effect {
             EffectKind::ProtectLand => Effect::ProtectLand(0.1),
             EffectKind::BailOut => Effect::BailOut(20),
             EffectKind::GameOver => Effect::GameOver,
+            EffectKind::Temporary => Effect::Temporary(
+                Box::new(Effect::default()),
+                3,
+            ),
+            EffectKind::All => Effect::All(vec![]),
         }
     }
 
@@ -358,6 +514,7 @@ impl Effect {
         match self {
             Effect::OutputForProcess(id, _)
             | Effect::ProcessLimit(id, _)
+            | Effect::ModifyProcessLimitPercent(id, _)
             | Effect::UnlocksProcess(id)
             | Effect::ProcessRequest(id, ..)
             | Effect::ModifyProcessByproducts(id, ..) => {
@@ -387,6 +544,93 @@ impl Effect {
         }
     }
 
+    pub fn region_id(&self) -> Option<Id> {
+        match self {
+            Effect::RegionHabitabilityById(id, _) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn npc_id(&self) -> Option<Id> {
+        match self {
+            Effect::UnlocksNPC(id)
+            | Effect::NPCRelationship(id, _)
+            | Effect::NPCSeats(id, _) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Check that everything this effect
+    /// references exists in the given state.
+    pub fn validate(
+        &self,
+        state: &State,
+    ) -> Result<(), EffectError> {
+        match self {
+            Effect::Temporary(effect, _) => {
+                return effect.validate(state);
+            }
+            Effect::All(effects) => {
+                return effects.iter().try_for_each(|effect| {
+                    effect.validate(state)
+                });
+            }
+            _ => (),
+        }
+        if let Some(id) = self.process_id() {
+            if state.world.processes.try_get(&id).is_none() {
+                return Err(EffectError::MissingProcess(id));
+            }
+        }
+        if let Some(id) = self.project_id() {
+            if state.world.projects.try_get(&id).is_none() {
+                return Err(EffectError::MissingProject(id));
+            }
+        }
+        if let Some(id) = self.industry_id() {
+            if state.world.industries.try_get(&id).is_none() {
+                return Err(EffectError::MissingIndustry(id));
+            }
+        }
+        if let Some(id) = self.event_id() {
+            if state.event_pool.events.try_get(&id).is_none() {
+                return Err(EffectError::MissingEvent(id));
+            }
+        }
+        if let Some(id) = self.npc_id() {
+            if state.npcs.try_get(&id).is_none() {
+                return Err(EffectError::MissingNPC(id));
+            }
+        }
+        if let Some(id) = self.region_id() {
+            if state.world.regions.try_get(&id).is_none() {
+                return Err(EffectError::MissingRegion(id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether this effect can influence the global
+    /// outlook or the conditions for losing because of it.
+    /// These are:
+    /// - `WorldVariable::Outlook`, `DemandOutlookChange`, and
+    ///   `IncomeOutlookChange`, which change outlook directly.
+    /// - `RegionLeave` and `Migration`, which change the
+    ///   makeup of the regions that outlook is computed from.
+    /// - `NPCRelationship`, which can change whether
+    ///   "The Authoritarian" is an ally.
+    pub fn affects_outlook(&self) -> bool {
+        matches!(
+            self,
+            Effect::WorldVariable(WorldVariable::Outlook, _)
+                | Effect::DemandOutlookChange(..)
+                | Effect::IncomeOutlookChange(..)
+                | Effect::RegionLeave
+                | Effect::Migration
+                | Effect::NPCRelationship(..)
+        )
+    }
+
     pub fn event_id(&self) -> Option<Id> {
         match self {
             Effect::AddEvent(id)
@@ -397,6 +641,19 @@ impl Effect {
             _ => None,
         }
     }
+
+    /// How many migration waves applying this effect makes
+    /// (see `State::migrations`).
+    pub fn migrations(&self) -> usize {
+        match self {
+            Effect::Migration => 1,
+            Effect::Temporary(effect, _) => effect.migrations(),
+            Effect::All(effects) => {
+                effects.iter().map(Effect::migrations).sum()
+            }
+            _ => 0,
+        }
+    }
 }
 
 impl Effect {
@@ -405,6 +662,13 @@ impl Effect {
     pub fn fingerprint(&self) -> String {
         let discrim: EffectKind = self.into();
         let discrim: &'static str = discrim.into();
+        if let Self::All(effects) = self {
+            let inner = effects
+                .iter()
+                .map(|effect| effect.fingerprint())
+                .collect::<Vec<_>>();
+            return format!("{discrim}:[{}]", inner.join(","));
+        }
         let subkind: &'static str = match self {
             Self::WorldVariable(var, _) => var.into(),
             Self::PlayerVariable(var, _) => var.into(),
@@ -432,25 +696,407 @@ impl Effect {
                 res.into()
             }
             Self::DemandOutlookChange(out, _) => out.into(),
+            Self::RemoveFlag(flag) => flag.into(),
             _ => "",
         };
         format!("{discrim}:{subkind}")
     }
 
+    /// The outlook change for a region
+    /// for `DemandOutlookChange` and `IncomeOutlookChange`.
+    fn regional_outlook_change(
+        &self,
+        region: &Region,
+        per_capita_demand: &[OutputDemand; 4],
+    ) -> f32 {
+        match self {
+            Effect::DemandOutlookChange(output, mult) => (mult
+                * region.demand_level(output, per_capita_demand)
+                    as f32)
+                .round(),
+            Effect::IncomeOutlookChange(mult) => {
+                (mult * region.income.level() as f32).round()
+            }
+            _ => 0.,
+        }
+    }
+
+    /// The magnitude of this effect, if it has one.
+    fn amount(&self) -> Option<f32> {
+        match self {
+            Effect::WorldVariable(_, val)
+            | Effect::PlayerVariable(_, val)
+            | Effect::RegionHabitability(_, val)
+            | Effect::RegionHabitabilityById(_, val)
+            | Effect::Resource(_, val)
+            | Effect::Demand(_, val)
+            | Effect::Output(_, val)
+            | Effect::DemandAmount(_, val)
+            | Effect::OutputForFeature(_, val)
+            | Effect::OutputForProcess(_, val)
+            | Effect::CO2ForFeature(_, val)
+            | Effect::BiodiversityPressureForFeature(_, val)
+            | Effect::ProcessLimit(_, val)
+            | Effect::ModifyProcessLimitPercent(_, val)
+            | Effect::Feedstock(_, val)
+            | Effect::NPCRelationship(_, val)
+            | Effect::NPCSeats(_, val)
+            | Effect::ModifyProcessByproducts(_, _, val)
+            | Effect::ModifyIndustryByproducts(_, _, val)
+            | Effect::ModifyIndustryResources(_, _, val)
+            | Effect::ModifyIndustryResourcesAmount(
+                _,
+                _,
+                val,
+            )
+            | Effect::ModifyEventProbability(_, val)
+            | Effect::ModifyIndustryDemand(_, val)
+            | Effect::DemandOutlookChange(_, val)
+            | Effect::IncomeOutlookChange(val)
+            | Effect::ProjectCostModifier(_, val)
+            | Effect::ProtectLand(val) => Some(*val),
+            Effect::BailOut(amount) => Some(*amount as f32),
+            _ => None,
+        }
+    }
+
+    /// The current value of the single variable this effect
+    /// changes, if it changes just one, so that the change
+    /// actually made can be logged (e.g. after clamping).
+    fn observed_value(&self, state: &State) -> Option<f32> {
+        let world = &state.world;
+        match self {
+            Effect::WorldVariable(var, _) => match var {
+                WorldVariable::Year => Some(world.year as f32),
+                WorldVariable::Population => {
+                    Some(world.population())
+                }
+                WorldVariable::PopulationGrowth => {
+                    Some(world.population_growth_modifier)
+                }
+                WorldVariable::Emissions => {
+                    Some(state.emissions.co2 * 1e-15)
+                }
+                WorldVariable::ExtinctionRate => Some(
+                    -state.byproducts.modifier.biodiversity,
+                ),
+                WorldVariable::Outlook => {
+                    Some(world.base_outlook)
+                }
+                WorldVariable::Temperature => {
+                    Some(world.temperature_modifier)
+                }
+                WorldVariable::SeaLevelRise => {
+                    Some(world.sea_level_rise)
+                }
+                WorldVariable::SeaLevelRiseRate => {
+                    Some(world.sea_level_rise_modifier)
+                }
+                WorldVariable::Precipitation => {
+                    Some(world.precipitation)
+                }
+            },
+            Effect::PlayerVariable(var, _) => match var {
+                PlayerVariable::PoliticalCapital => {
+                    Some(state.political_capital as f32)
+                }
+                PlayerVariable::ResearchPoints => {
+                    Some(state.research_points as f32)
+                }
+                _ => None,
+            },
+            Effect::RegionHabitabilityById(id, _) => world
+                .regions
+                .try_get(id)
+                .map(|region| region.base_habitability),
+            Effect::Resource(resource, _) => {
+                Some(state.resources.available[*resource])
+            }
+            Effect::DemandAmount(output, _) => {
+                Some(state.output_demand.modifier[*output])
+            }
+            Effect::OutputForProcess(id, _) => world
+                .processes
+                .try_get(id)
+                .map(|process| process.output_modifier),
+            Effect::ProcessLimit(id, _)
+            | Effect::ModifyProcessLimitPercent(id, _) => world
+                .processes
+                .try_get(id)
+                .and_then(|process| process.limit),
+            Effect::Feedstock(feedstock, _) => {
+                Some(state.feedstocks.available[*feedstock])
+            }
+            Effect::NPCRelationship(id, _) => state
+                .npcs
+                .try_get(id)
+                .map(|npc| npc.relationship),
+            Effect::NPCSeats(id, _) => {
+                state.npcs.try_get(id).map(|npc| npc.seats)
+            }
+            Effect::ModifyEventProbability(id, _) => state
+                .event_pool
+                .events
+                .try_get(id)
+                .map(|ev| ev.prob_modifier),
+            Effect::ProjectCostModifier(id, _) => world
+                .projects
+                .try_get(id)
+                .map(|project| project.cost_modifier),
+            Effect::ProtectLand(_) => {
+                Some(state.protected_land)
+            }
+            _ => None,
+        }
+    }
+
+    /// A short human-readable summary of this effect,
+    /// e.g. "+5% output for Solar PV".
+    /// Unlike `fingerprint` this includes the effect's
+    /// values and resolves names from the state.
+    /// The returned strings are in English and ready
+    /// to be used as translation keys.
+    pub fn describe(&self, state: &State) -> String {
+        let world = &state.world;
+        match self {
+            Effect::WorldVariable(var, val) => {
+                format!("{val:+} {var}")
+            }
+            Effect::PlayerVariable(var, val) => {
+                format!("{val:+} {var}")
+            }
+            Effect::RegionHabitability(lat, val) => {
+                format!(
+                    "{val:+} habitability for {lat} regions"
+                )
+            }
+            Effect::RegionHabitabilityById(id, val) => format!(
+                "{val:+} habitability for {}",
+                name_of(&world.regions, id)
+            ),
+            Effect::Resource(res, val) => {
+                format!("{val:+} {res} available")
+            }
+            Effect::Demand(out, val) => {
+                format!("{} {out} demand", signed_percent(*val))
+            }
+            Effect::Output(out, val) => {
+                format!("{} {out} output", signed_percent(*val))
+            }
+            Effect::DemandAmount(out, val) => {
+                format!("{val:+} {out} demand")
+            }
+            Effect::OutputForFeature(feat, val) => format!(
+                "{} output for {feat} processes",
+                signed_percent(*val)
+            ),
+            Effect::OutputForProcess(id, val) => format!(
+                "{} output for {}",
+                signed_percent(*val),
+                name_of(&world.processes, id)
+            ),
+            Effect::CO2ForFeature(feat, val) => format!(
+                "{} CO2 for {feat} processes",
+                signed_percent(*val)
+            ),
+            Effect::BiodiversityPressureForFeature(
+                feat,
+                val,
+            ) => {
+                format!(
+                    "{} biodiversity pressure for {feat} processes",
+                    signed_percent(*val)
+                )
+            }
+            Effect::ProcessLimit(id, val) => format!(
+                "{val:+} output limit for {}",
+                name_of(&world.processes, id)
+            ),
+            Effect::ModifyProcessLimitPercent(id, val) => {
+                format!(
+                    "{} output limit for {}",
+                    signed_percent(*val),
+                    name_of(&world.processes, id)
+                )
+            }
+            Effect::Feedstock(fs, val) => format!(
+                "{} {fs} reserves",
+                signed_percent(*val)
+            ),
+            Effect::AddEvent(id) => format!(
+                "Enables {}",
+                name_of(&state.event_pool.events, id)
+            ),
+            Effect::TriggerEvent(id, years) => format!(
+                "Triggers {} in {years} years",
+                name_of(&state.event_pool.events, id)
+            ),
+            Effect::LocksProject(id) => format!(
+                "Locks {}",
+                name_of(&world.projects, id)
+            ),
+            Effect::UnlocksProject(id) => format!(
+                "Unlocks {}",
+                name_of(&world.projects, id)
+            ),
+            Effect::UnlocksProcess(id) => format!(
+                "Unlocks {}",
+                name_of(&world.processes, id)
+            ),
+            Effect::UnlocksNPC(id) => {
+                format!("Unlocks {}", name_of(&state.npcs, id))
+            }
+            Effect::ProjectRequest(id, active, bounty) => {
+                format!(
+                    "Requests {} be {} (+{bounty} political capital)",
+                    name_of(&world.projects, id),
+                    if *active { "implemented" } else { "stopped" }
+                )
+            }
+            Effect::ProcessRequest(id, active, bounty) => {
+                format!(
+                    "Requests {} be {} (+{bounty} political capital)",
+                    name_of(&world.processes, id),
+                    if *active { "promoted" } else { "banned" }
+                )
+            }
+            Effect::Migration => {
+                "Triggers a migration wave".into()
+            }
+            Effect::RegionLeave => {
+                "Region leaves the world government".into()
+            }
+            Effect::TerminationShock => {
+                "Termination shock when stopped".into()
+            }
+            Effect::AddRegionFlag(flag) => {
+                format!("Adds {flag} to the region")
+            }
+            Effect::AddFlag(flag) => flag.to_string(),
+            Effect::RemoveFlag(flag) => {
+                format!("No longer in effect: {flag}")
+            }
+            Effect::NPCRelationship(id, val) => format!(
+                "{val:+} relationship with {}",
+                name_of(&state.npcs, id)
+            ),
+            Effect::NPCSeats(id, val) => format!(
+                "{} seats for {}",
+                signed_percent(*val),
+                name_of(&state.npcs, id)
+            ),
+            Effect::ModifyProcessByproducts(id, byp, val) => {
+                format!(
+                    "{} {byp} for {}",
+                    signed_percent(*val),
+                    name_of(&world.processes, id)
+                )
+            }
+            Effect::ModifyIndustryByproducts(id, byp, val) => {
+                format!(
+                    "{} {byp} for {}",
+                    signed_percent(*val),
+                    name_of(&world.industries, id)
+                )
+            }
+            Effect::ModifyIndustryResources(id, res, val) => {
+                format!(
+                    "{} {res} use for {}",
+                    signed_percent(*val),
+                    name_of(&world.industries, id)
+                )
+            }
+            Effect::ModifyIndustryResourcesAmount(
+                id,
+                res,
+                val,
+            ) => {
+                format!(
+                    "{val:+} {res} use for {}",
+                    name_of(&world.industries, id)
+                )
+            }
+            Effect::ModifyEventProbability(id, val) => format!(
+                "{} chance of {}",
+                signed_percent(*val),
+                name_of(&state.event_pool.events, id)
+            ),
+            Effect::ModifyIndustryDemand(id, val) => format!(
+                "{} demand for {}",
+                signed_percent(*val),
+                name_of(&world.industries, id)
+            ),
+            Effect::DemandOutlookChange(out, val) => {
+                format!(
+                    "{val:+} outlook per level of {out} demand"
+                )
+            }
+            Effect::IncomeOutlookChange(val) => {
+                format!("{val:+} outlook per income level")
+            }
+            Effect::ProjectCostModifier(id, val) => format!(
+                "{} cost for {}",
+                signed_percent(*val),
+                name_of(&world.projects, id)
+            ),
+            Effect::ProtectLand(val) => {
+                format!(
+                    "{} protected land",
+                    signed_percent(*val)
+                )
+            }
+            Effect::BailOut(amount) => {
+                format!(
+                    "Bail out with {amount} political capital"
+                )
+            }
+            Effect::GameOver => "Game over".into(),
+            Effect::Temporary(effect, years) => format!(
+                "{} for {years} years",
+                effect.describe(state)
+            ),
+            Effect::All(effects) => effects
+                .iter()
+                .map(|effect| effect.describe(state))
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+
     pub fn apply(
         &self,
         state: &mut State,
         region_id: Option<Id>,
     ) {
+        self.apply_logged(state, region_id, &mut vec![]);
+    }
+
+    /// Apply the effect, recording what changed
+    /// into the provided log.
+    pub fn apply_logged(
+        &self,
+        state: &mut State,
+        region_id: Option<Id>,
+        log: &mut Vec<EffectChange>,
+    ) {
+        let n_changes = log.len();
+        let before = self.observed_value(state);
         match self {
             Effect::GameOver => {
                 state.game_over = true;
             }
             Effect::BailOut(amount) => {
-                if state.political_capital < 0 {
-                    state.political_capital = 0;
-                }
+                let before = state.political_capital;
+                let debt = state.political_capital.min(0);
+                state.political_capital -= debt;
                 state.political_capital += *amount as isize;
+                state.bailout_debts.push(debt);
+                log.push(EffectChange {
+                    variable: self.fingerprint(),
+                    delta: (state.political_capital - before)
+                        as f32,
+                    region_id,
+                });
             }
             Effect::WorldVariable(var, change) => {
                 match var {
@@ -480,7 +1126,6 @@ impl Effect {
                     }
                     WorldVariable::Outlook => {
                         state.world.base_outlook += *change;
-                        check_game_over(state);
                     }
                     WorldVariable::Temperature => {
                         state.world.temperature_modifier +=
@@ -517,6 +1162,16 @@ impl Effect {
                     region.base_habitability += change;
                 }
             }
+            Effect::RegionHabitabilityById(id, change) => {
+                if let Some(region) = state
+                    .world
+                    .regions
+                    .try_get_mut(id)
+                    .filter(|r| !r.seceded)
+                {
+                    region.base_habitability += change;
+                }
+            }
             Effect::Resource(resource, amount) => {
                 state.resources.available[*resource] += amount;
             }
@@ -550,8 +1205,11 @@ impl Effect {
                 }
             }
             Effect::OutputForProcess(id, pct_change) => {
-                let process = &mut state.world.processes[id];
-                process.output_modifier += pct_change;
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    process.output_modifier += pct_change;
+                }
             }
             Effect::CO2ForFeature(feat, pct_change) => {
                 for process in state
@@ -579,9 +1237,29 @@ impl Effect {
                 }
             }
             Effect::ProcessLimit(id, change) => {
-                let process = &mut state.world.processes[id];
-                if let Some(limit) = process.limit {
-                    process.limit = Some(limit + change);
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    if let Some(limit) = process.limit {
+                        process.limit = Some(limit + change);
+                    }
+                }
+            }
+            Effect::ModifyProcessLimitPercent(
+                id,
+                pct_change,
+            ) => {
+                let demand = state.output_demand.total();
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    let limit =
+                        process.limit.unwrap_or_else(|| {
+                            demand[process.output]
+                                * process.mix_percent()
+                        });
+                    process.limit =
+                        Some(limit * (1. + pct_change));
                 }
             }
             Effect::Feedstock(feedstock, pct_change) => {
@@ -603,7 +1281,11 @@ impl Effect {
                 state.world.projects[id].locked = false;
             }
             Effect::UnlocksProcess(id) => {
-                state.world.processes[id].locked = false;
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    process.locked = false;
+                }
             }
             Effect::UnlocksNPC(id) => {
                 state.npcs[id].locked = false;
@@ -640,6 +1322,11 @@ impl Effect {
                         * modifier;
                     state.world.regions[id].population -=
                         leave_pop;
+                    log.push(EffectChange {
+                        variable: self.fingerprint(),
+                        delta: -leave_pop,
+                        region_id: Some(*id),
+                    });
 
                     // Find the most habitable regions
                     let mean_habitability: f32 =
@@ -651,15 +1338,31 @@ impl Effect {
                             .iter_mut()
                             .filter(|r| {
                                 &r.id != id
+                                    && !r.seceded
                                     && r.habitability()
                                         > mean_habitability
                             })
                             .collect();
                     let per_region =
                         leave_pop / target_regions.len() as f32;
+                    let mut arrivals = vec![];
                     for region in target_regions {
                         region.population += per_region;
+                        arrivals.push((region.id, per_region));
+                        log.push(EffectChange {
+                            variable: self.fingerprint(),
+                            delta: per_region,
+                            region_id: Some(region.id),
+                        });
                     }
+                    state
+                        .migrations
+                        .entry(*id)
+                        .or_default()
+                        .push(MigrationWave {
+                            leave_pop,
+                            arrivals,
+                        });
                 }
             }
             Effect::RegionLeave => {
@@ -675,17 +1378,40 @@ impl Effect {
             Effect::AddFlag(flag) => {
                 state.flags.push(*flag);
             }
+            Effect::RemoveFlag(flag) => {
+                if let Some(idx) =
+                    state.flags.iter().position(|x| x == flag)
+                {
+                    state.flags.remove(idx);
+                }
+            }
             Effect::NPCRelationship(id, change) => {
                 state.npcs[id].relationship += change;
             }
+            Effect::NPCSeats(id, change) => {
+                // Seats can't go negative, so record
+                // the change that was actually made.
+                let npc = &mut state.npcs[id];
+                let before = npc.seats;
+                npc.seats = (npc.seats + change).max(0.);
+                state
+                    .seat_changes
+                    .entry(*id)
+                    .or_default()
+                    .push(npc.seats - before);
+            }
 
             Effect::ModifyProcessByproducts(
                 id,
                 byproduct,
                 change,
             ) => {
-                state.world.processes[id]
-                    .byproduct_modifiers[*byproduct] += change;
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    process.byproduct_modifiers[*byproduct] +=
+                        change;
+                }
             }
             Effect::ModifyIndustryByproducts(
                 id,
@@ -719,74 +1445,228 @@ impl Effect {
                 state.world.industries[id].demand_modifier +=
                     change;
             }
-            Effect::DemandOutlookChange(output, mult) => {
-                for region in state.world.regions.iter_mut() {
-                    region.outlook += (mult
-                        * region.demand_level(
-                            output,
-                            &state.world.per_capita_demand,
-                        ) as f32)
-                        .round();
-                }
-                check_game_over(state);
-            }
-            Effect::IncomeOutlookChange(mult) => {
+            Effect::DemandOutlookChange(..)
+            | Effect::IncomeOutlookChange(..) => {
+                let mut changes = vec![];
                 for region in state.world.regions.iter_mut() {
-                    region.outlook += (mult
-                        * region.income.level() as f32)
-                        .round();
+                    let change = self.regional_outlook_change(
+                        region,
+                        &state.world.per_capita_demand,
+                    );
+                    region.outlook += change;
+                    changes.push((region.id, change));
+                    log.push(EffectChange {
+                        variable: self.fingerprint(),
+                        delta: change,
+                        region_id: Some(region.id),
+                    });
                 }
-                check_game_over(state);
+                state
+                    .outlook_changes
+                    .push((self.clone(), changes));
             }
             Effect::ProjectCostModifier(id, change) => {
                 state.world.projects[id].cost_modifier +=
                     change;
             }
             Effect::TerminationShock => {
-                let p = state
-                    .world
-                    .projects
-                    .iter()
-                    .find(|p| {
-                        // HACK: Not great to be matching on the
-                        // project's name; ideally would introduce
-                        // a flag effect that mirrors `TerminationShock`
-                        // and match on any project that contains that flag,
-                        // but that may be a complicated change to make at this point.
-                        p.name.contains(
-                            "Solar Radiation Management",
-                        )
-                    })
-                    .unwrap();
-                let effects = p.active_effects();
-                let mut temp = 0.;
-                for eff in effects {
-                    match eff {
-                        Effect::WorldVariable(typ, val) => {
-                            match typ {
-                                WorldVariable::Temperature => {
-                                    temp += val
-                                }
-                                _ => (),
-                            }
-                        }
-                        _ => (),
-                    };
+                if let Some(temp) =
+                    srm_temperature_change(state)
+                {
+                    state.world.temperature_modifier -= temp;
                 }
-                state.world.temperature_modifier -= temp;
             }
             Effect::ProtectLand(percent) => {
-                state.protected_land += percent;
+                // Can't protect more than all
+                // or less than none of the land.
+                let before = state.protected_land;
+                state.protected_land =
+                    (before + percent).clamp(0., 1.);
+                state.protected_land_changes.push((
+                    *percent,
+                    state.protected_land - before,
+                ));
+            }
+            Effect::All(effects) => {
+                for effect in effects {
+                    effect.apply_logged(state, region_id, log);
+                }
             }
+            Effect::Temporary(effect, years) => {
+                effect.apply_logged(state, region_id, log);
+                state.temporary_effects.push((
+                    *effect.clone(),
+                    region_id,
+                    state.world.year + years,
+                ));
+            }
+        }
+
+        // Effects which didn't record their own changes are
+        // logged as a single change. Where the effect changes
+        // a single variable this is the change actually made,
+        // otherwise it's the change made to each target,
+        // e.g. each process with a feature.
+        if log.len() == n_changes {
+            let after = self.observed_value(state);
+            let delta = match (before, after) {
+                (Some(before), Some(after)) => after - before,
+                _ => self.amount().unwrap_or(0.),
+            };
+            log.push(EffectChange {
+                variable: self.fingerprint(),
+                delta,
+                region_id,
+            });
+        }
+
+        if self.affects_outlook() {
+            maybe_game_over(state);
         }
     }
 
-    pub fn unapply(
+    /// For feature effects, which process modifier
+    /// they contribute to and by how much.
+    pub(crate) fn feature_modifier(
         &self,
-        state: &mut State,
-        _region_id: Option<Id>,
+    ) -> Option<(FeatureModifier, ProcessFeature, f32)> {
+        match self {
+            Effect::OutputForFeature(feat, pct_change) => Some(
+                (FeatureModifier::Output, *feat, *pct_change),
+            ),
+            Effect::CO2ForFeature(feat, pct_change) => {
+                Some((FeatureModifier::CO2, *feat, *pct_change))
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply the effect on behalf of a `source`,
+    /// e.g. the id of the project or event it's from.
+    ///
+    /// Feature effects (`OutputForFeature` and `CO2ForFeature`)
+    /// add to each matching process's modifiers, so applying
+    /// them twice double-stacks. Here each process only
+    /// takes a given source's contribution for an effect
+    /// once, which is safe to use when effects may be
+    /// re-evaluated. Distinct effects from the same source
+    /// each contribute. Other effects are applied as usual.
+    pub fn apply_sourced(
+        &self,
+        state: &mut State,
+        region_id: Option<Id>,
+        source: Id,
+    ) {
+        self.apply_sourced_logged(
+            state,
+            region_id,
+            source,
+            &mut vec![],
+        );
+    }
+
+    /// Apply the effect on behalf of a `source`
+    /// (see `Effect::apply_sourced`), recording
+    /// what changed into the provided log.
+    pub fn apply_sourced_logged(
+        &self,
+        state: &mut State,
+        region_id: Option<Id>,
+        source: Id,
+        log: &mut Vec<EffectChange>,
+    ) {
+        match self.feature_modifier() {
+            Some((modifier, feat, amount)) => {
+                for process in state
+                    .world
+                    .processes
+                    .iter_mut()
+                    .filter(|p| p.features.contains(&feat))
+                {
+                    let key = (source, modifier, feat, amount);
+                    if !process.sourced_modifiers.contains(&key)
+                    {
+                        process.sourced_modifiers.push(key);
+                        process.add_feature_modifier(
+                            modifier, amount,
+                        );
+                        log.push(EffectChange {
+                            variable: self.fingerprint(),
+                            delta: amount,
+                            region_id,
+                        });
+                    }
+                }
+            }
+            None => self.apply_logged(state, region_id, log),
+        }
+    }
+
+    /// Reverse an effect applied with `apply_sourced`.
+    /// Feature effects only remove the contribution
+    /// from processes which have it.
+    pub fn unapply_sourced(
+        &self,
+        state: &mut State,
+        region_id: Option<Id>,
+        source: Id,
+    ) {
+        match self.feature_modifier() {
+            Some((modifier, feat, amount)) => {
+                for process in state.world.processes.iter_mut()
+                {
+                    let key = (source, modifier, feat, amount);
+                    if let Some(idx) = process
+                        .sourced_modifiers
+                        .iter()
+                        .position(|k| *k == key)
+                    {
+                        process.sourced_modifiers.remove(idx);
+                        process.add_feature_modifier(
+                            modifier, -amount,
+                        );
+                    }
+                }
+            }
+            None => self.unapply(state, region_id),
+        }
+    }
+
+    pub fn unapply(
+        &self,
+        state: &mut State,
+        region_id: Option<Id>,
     ) {
         match self {
+            Effect::Migration => {
+                // Reverse the most recent wave for this region
+                // using the recorded amounts, so that changes
+                // to e.g. `ClosedBorders` or habitability since
+                // the wave don't affect the reversal.
+                let wave = region_id.and_then(|id| {
+                    state
+                        .migrations
+                        .get_mut(&id)
+                        .and_then(|waves| waves.pop())
+                        .map(|wave| (id, wave))
+                });
+                if let Some((id, wave)) = wave {
+                    if let Some(region) =
+                        state.world.regions.try_get_mut(&id)
+                    {
+                        region.population += wave.leave_pop;
+                    }
+                    for (target_id, amount) in wave.arrivals {
+                        if let Some(region) = state
+                            .world
+                            .regions
+                            .try_get_mut(&target_id)
+                        {
+                            region.population -= amount;
+                        }
+                    }
+                }
+            }
             Effect::WorldVariable(var, change) => {
                 match var {
                     WorldVariable::Year => {
@@ -851,6 +1731,16 @@ impl Effect {
                     region.base_habitability -= change;
                 }
             }
+            Effect::RegionHabitabilityById(id, change) => {
+                if let Some(region) = state
+                    .world
+                    .regions
+                    .try_get_mut(id)
+                    .filter(|r| !r.seceded)
+                {
+                    region.base_habitability -= change;
+                }
+            }
             Effect::Resource(resource, amount) => {
                 state.resources.available[*resource] -= amount;
             }
@@ -884,8 +1774,11 @@ impl Effect {
                 }
             }
             Effect::OutputForProcess(id, pct_change) => {
-                let process = &mut state.world.processes[id];
-                process.output_modifier -= pct_change;
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    process.output_modifier -= pct_change;
+                }
             }
             Effect::CO2ForFeature(feat, pct_change) => {
                 for process in state
@@ -913,9 +1806,31 @@ impl Effect {
                 }
             }
             Effect::ProcessLimit(id, change) => {
-                let process = &mut state.world.processes[id];
-                if let Some(limit) = process.limit {
-                    process.limit = Some(limit - change);
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    if let Some(limit) = process.limit {
+                        process.limit = Some(limit - change);
+                    }
+                }
+            }
+            Effect::ModifyProcessLimitPercent(
+                id,
+                pct_change,
+            ) => {
+                // Note that if the limit was initialized
+                // when applied it remains set here.
+                // A change of -100% can't be reversed.
+                let factor = 1. + pct_change;
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    if let Some(limit) = process.limit {
+                        if factor != 0. {
+                            process.limit =
+                                Some(limit / factor);
+                        }
+                    }
                 }
             }
             Effect::Feedstock(feedstock, pct_change) => {
@@ -925,13 +1840,26 @@ impl Effect {
             Effect::NPCRelationship(id, change) => {
                 state.npcs[id].relationship -= change;
             }
+            Effect::NPCSeats(id, change) => {
+                let change = state
+                    .seat_changes
+                    .get_mut(id)
+                    .and_then(|changes| changes.pop())
+                    .unwrap_or(*change);
+                let npc = &mut state.npcs[id];
+                npc.seats = (npc.seats - change).max(0.);
+            }
             Effect::ModifyProcessByproducts(
                 id,
                 byproduct,
                 change,
             ) => {
-                state.world.processes[id]
-                    .byproduct_modifiers[*byproduct] -= change;
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    process.byproduct_modifiers[*byproduct] -=
+                        change;
+                }
             }
             Effect::ModifyIndustryByproducts(
                 id,
@@ -965,21 +1893,33 @@ impl Effect {
                 state.world.industries[id].demand_modifier -=
                     change;
             }
-            Effect::DemandOutlookChange(output, mult) => {
-                for region in state.world.regions.iter_mut() {
-                    region.outlook -= (mult
-                        * region.demand_level(
-                            output,
-                            &state.world.per_capita_demand,
-                        ) as f32)
-                        .floor();
-                }
-            }
-            Effect::IncomeOutlookChange(mult) => {
-                for region in state.world.regions.iter_mut() {
-                    region.outlook -= (mult
-                        * region.income.level() as f32)
-                        .floor();
+            Effect::DemandOutlookChange(..)
+            | Effect::IncomeOutlookChange(..) => {
+                // Reverse the changes as they were applied, as
+                // demand or income levels may have since changed.
+                let idx = state
+                    .outlook_changes
+                    .iter()
+                    .rposition(|(effect, _)| effect == self);
+                if let Some(idx) = idx {
+                    let (_, changes) =
+                        state.outlook_changes.remove(idx);
+                    for (id, change) in changes {
+                        if let Some(region) =
+                            state.world.regions.try_get_mut(&id)
+                        {
+                            region.outlook -= change;
+                        }
+                    }
+                } else {
+                    for region in state.world.regions.iter_mut()
+                    {
+                        region.outlook -= self
+                            .regional_outlook_change(
+                                region,
+                                &state.world.per_capita_demand,
+                            );
+                    }
                 }
             }
             Effect::ProjectCostModifier(id, change) => {
@@ -987,40 +1927,42 @@ impl Effect {
                     change;
             }
             Effect::TerminationShock => {
-                let p = state
-                    .world
-                    .projects
+                if let Some(temp) =
+                    srm_temperature_change(state)
+                {
+                    state.world.temperature_modifier += temp;
+                }
+            }
+            Effect::ProtectLand(percent) => {
+                let changes = &mut state.protected_land_changes;
+                let change = changes
                     .iter()
-                    .find(|p| {
-                        // HACK: Not great to be matching on the
-                        // project's name; ideally would introduce
-                        // a flag effect that mirrors `TerminationShock`
-                        // and match on any project that contains that flag,
-                        // but that may be a complicated change to make at this point.
-                        p.name.contains(
-                            "Solar Radiation Management",
-                        )
+                    .rposition(|(requested, _)| {
+                        requested == percent
                     })
-                    .unwrap();
-                let effects = p.active_effects();
-                let mut temp = 0.;
-                for eff in effects {
-                    match eff {
-                        Effect::WorldVariable(typ, val) => {
-                            match typ {
-                                WorldVariable::Temperature => {
-                                    temp += val
-                                }
-                                _ => (),
-                            }
-                        }
-                        _ => (),
-                    };
+                    .map(|idx| changes.remove(idx).1)
+                    .unwrap_or(*percent);
+                state.protected_land = (state.protected_land
+                    - change)
+                    .clamp(0., 1.);
+            }
+            Effect::All(effects) => {
+                for effect in effects.iter().rev() {
+                    effect.unapply(state, region_id);
                 }
-                state.world.temperature_modifier += temp;
             }
-            Effect::ProtectLand(percent) => {
-                state.protected_land -= percent;
+            Effect::Temporary(effect, _) => {
+                // Only reverse the inner effect if it
+                // hasn't already expired.
+                if let Some(idx) =
+                    state.temporary_effects.iter().position(
+                        |(eff, ..)| eff == effect.as_ref(),
+                    )
+                {
+                    let (effect, region_id, _) =
+                        state.temporary_effects.remove(idx);
+                    effect.unapply(state, region_id);
+                }
             }
             Effect::AddFlag(flag) => {
                 if let Some(idx) =
@@ -1029,6 +1971,21 @@ impl Effect {
                     state.flags.remove(idx);
                 }
             }
+            Effect::AddRegionFlag(flag) => {
+                if let Some(id) = &region_id {
+                    state.world.regions[id].remove_flag(*flag);
+                }
+            }
+            Effect::RemoveFlag(flag) => {
+                state.flags.push(*flag);
+            }
+            Effect::BailOut(amount) => {
+                // Restore any debt that was cleared.
+                let debt =
+                    state.bailout_debts.pop().unwrap_or(0);
+                state.political_capital -= *amount as isize;
+                state.political_capital += debt;
+            }
             Effect::LocksProject(id) => {
                 state.world.projects[id].locked = false;
             }
@@ -1036,7 +1993,11 @@ impl Effect {
                 state.world.projects[id].locked = true;
             }
             Effect::UnlocksProcess(id) => {
-                state.world.processes[id].locked = true;
+                if let Some(process) =
+                    state.world.process_mut(id)
+                {
+                    process.locked = true;
+                }
             }
             Effect::UnlocksNPC(id) => {
                 state.npcs[id].locked = true;
@@ -1060,6 +2021,9 @@ impl Mul<f32> for Effect {
             Effect::PlayerVariable(var, val) => {
                 Effect::PlayerVariable(var, val * rhs)
             }
+            Effect::RegionHabitabilityById(id, val) => {
+                Effect::RegionHabitabilityById(id, val * rhs)
+            }
             Effect::Resource(resource, val) => {
                 Effect::Resource(resource, val * rhs)
             }
@@ -1078,6 +2042,9 @@ impl Mul<f32> for Effect {
             Effect::OutputForProcess(id, val) => {
                 Effect::OutputForProcess(id, val * rhs)
             }
+            Effect::ModifyProcessLimitPercent(id, val) => {
+                Effect::ModifyProcessLimitPercent(id, val * rhs)
+            }
             Effect::Feedstock(feedstock, val) => {
                 Effect::Feedstock(feedstock, val * rhs)
             }
@@ -1103,7 +2070,7 @@ impl Mul<f32> for Effect {
                 id,
                 resource,
                 val,
-            ) => Effect::ModifyIndustryResources(
+            ) => Effect::ModifyIndustryResourcesAmount(
                 id,
                 resource,
                 val * rhs,
@@ -1126,53 +2093,375 @@ impl Mul<f32> for Effect {
             Effect::ProtectLand(val) => {
                 Effect::ProtectLand(val * rhs)
             }
+            Effect::NPCSeats(id, val) => {
+                Effect::NPCSeats(id, val * rhs)
+            }
+            Effect::Temporary(effect, years) => {
+                Effect::Temporary(
+                    Box::new(*effect * rhs),
+                    years,
+                )
+            }
+            Effect::All(effects) => Effect::All(
+                effects
+                    .into_iter()
+                    .map(|effect| effect * rhs)
+                    .collect(),
+            ),
             _ => self,
         }
     }
 }
 
-pub fn mean_income_outlook_change(
-    mult: f32,
-    state: &State,
-) -> f32 {
+/// The mean outlook change across regions for an effect,
+/// rounded per region the same as when it's applied.
+fn mean_outlook_change(effect: &Effect, state: &State) -> f32 {
     state
         .world
         .regions
         .iter()
         .map(|region| {
-            (mult * region.income.level() as f32).floor()
+            effect.regional_outlook_change(
+                region,
+                &state.world.per_capita_demand,
+            )
         })
         .sum::<f32>()
         / state.world.regions.len() as f32
 }
 
+pub fn mean_income_outlook_change(
+    mult: f32,
+    state: &State,
+) -> f32 {
+    mean_outlook_change(
+        &Effect::IncomeOutlookChange(mult),
+        state,
+    )
+}
+
 pub fn mean_demand_outlook_change(
     mult: f32,
     output: &Output,
     state: &State,
 ) -> f32 {
-    state
-        .world
-        .regions
-        .iter()
-        .map(|region| {
-            (mult
-                * region.demand_level(
-                    output,
-                    &state.world.per_capita_demand,
-                ) as f32)
-                .floor()
-        })
-        .sum::<f32>()
-        / state.world.regions.len() as f32
+    mean_outlook_change(
+        &Effect::DemandOutlookChange(*output, mult),
+        state,
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Status;
+    use crate::{Status, World};
+    use strum::IntoEnumIterator;
 
     use super::*;
 
+    fn pick<T: IntoEnumIterator>(rng: &mut fastrand::Rng) -> T {
+        let n = T::iter().count();
+        T::iter().nth(rng.usize(..n)).unwrap()
+    }
+
+    /// Any finite float, including extremes and subnormals.
+    fn arbitrary_f32(rng: &mut fastrand::Rng) -> f32 {
+        loop {
+            let val = f32::from_bits(rng.u32(..));
+            if val.is_finite() {
+                return val;
+            }
+        }
+    }
+
+    fn arbitrary_effect(
+        kind: EffectKind,
+        rng: &mut fastrand::Rng,
+        depth: usize,
+    ) -> Effect {
+        let id = Id::from_u128(rng.u128(..));
+        let val = arbitrary_f32(rng);
+        let n = rng.usize(..);
+        let b = rng.bool();
+        match kind {
+            EffectKind::WorldVariable => {
+                Effect::WorldVariable(pick(rng), val)
+            }
+            EffectKind::PlayerVariable => {
+                Effect::PlayerVariable(pick(rng), val)
+            }
+            EffectKind::RegionHabitability => {
+                Effect::RegionHabitability(pick(rng), val)
+            }
+            EffectKind::RegionHabitabilityById => {
+                Effect::RegionHabitabilityById(id, val)
+            }
+            EffectKind::Resource => {
+                Effect::Resource(pick(rng), val)
+            }
+            EffectKind::Demand => {
+                Effect::Demand(pick(rng), val)
+            }
+            EffectKind::Output => {
+                Effect::Output(pick(rng), val)
+            }
+            EffectKind::DemandAmount => {
+                Effect::DemandAmount(pick(rng), val)
+            }
+            EffectKind::OutputForFeature => {
+                Effect::OutputForFeature(pick(rng), val)
+            }
+            EffectKind::OutputForProcess => {
+                Effect::OutputForProcess(id, val)
+            }
+            EffectKind::CO2ForFeature => {
+                Effect::CO2ForFeature(pick(rng), val)
+            }
+            EffectKind::BiodiversityPressureForFeature => {
+                Effect::BiodiversityPressureForFeature(
+                    pick(rng),
+                    val,
+                )
+            }
+            EffectKind::ProcessLimit => {
+                Effect::ProcessLimit(id, val)
+            }
+            EffectKind::ModifyProcessLimitPercent => {
+                Effect::ModifyProcessLimitPercent(id, val)
+            }
+            EffectKind::Feedstock => {
+                Effect::Feedstock(pick(rng), val)
+            }
+            EffectKind::AddEvent => Effect::AddEvent(id),
+            EffectKind::TriggerEvent => {
+                Effect::TriggerEvent(id, n)
+            }
+            EffectKind::LocksProject => {
+                Effect::LocksProject(id)
+            }
+            EffectKind::UnlocksProject => {
+                Effect::UnlocksProject(id)
+            }
+            EffectKind::UnlocksProcess => {
+                Effect::UnlocksProcess(id)
+            }
+            EffectKind::UnlocksNPC => Effect::UnlocksNPC(id),
+            EffectKind::ProjectRequest => {
+                Effect::ProjectRequest(id, b, n)
+            }
+            EffectKind::ProcessRequest => {
+                Effect::ProcessRequest(id, b, n)
+            }
+            EffectKind::Migration => Effect::Migration,
+            EffectKind::RegionLeave => Effect::RegionLeave,
+            EffectKind::TerminationShock => {
+                Effect::TerminationShock
+            }
+            EffectKind::AddRegionFlag => {
+                Effect::AddRegionFlag(pick(rng))
+            }
+            EffectKind::AddFlag => Effect::AddFlag(pick(rng)),
+            EffectKind::RemoveFlag => {
+                Effect::RemoveFlag(pick(rng))
+            }
+            EffectKind::NPCRelationship => {
+                Effect::NPCRelationship(id, val)
+            }
+            EffectKind::NPCSeats => Effect::NPCSeats(id, val),
+            EffectKind::ModifyProcessByproducts => {
+                Effect::ModifyProcessByproducts(
+                    id,
+                    pick(rng),
+                    val,
+                )
+            }
+            EffectKind::ModifyIndustryByproducts => {
+                Effect::ModifyIndustryByproducts(
+                    id,
+                    pick(rng),
+                    val,
+                )
+            }
+            EffectKind::ModifyIndustryResources => {
+                Effect::ModifyIndustryResources(
+                    id,
+                    pick(rng),
+                    val,
+                )
+            }
+            EffectKind::ModifyIndustryResourcesAmount => {
+                Effect::ModifyIndustryResourcesAmount(
+                    id,
+                    pick(rng),
+                    val,
+                )
+            }
+            EffectKind::ModifyEventProbability => {
+                Effect::ModifyEventProbability(id, val)
+            }
+            EffectKind::ModifyIndustryDemand => {
+                Effect::ModifyIndustryDemand(id, val)
+            }
+            EffectKind::DemandOutlookChange => {
+                Effect::DemandOutlookChange(pick(rng), val)
+            }
+            EffectKind::IncomeOutlookChange => {
+                Effect::IncomeOutlookChange(val)
+            }
+            EffectKind::ProjectCostModifier => {
+                Effect::ProjectCostModifier(id, val)
+            }
+            EffectKind::ProtectLand => Effect::ProtectLand(val),
+            EffectKind::BailOut => Effect::BailOut(n),
+            EffectKind::GameOver => Effect::GameOver,
+            EffectKind::Temporary => {
+                let inner = if depth > 0 {
+                    arbitrary_effect(pick(rng), rng, depth - 1)
+                } else {
+                    Effect::default()
+                };
+                Effect::Temporary(Box::new(inner), n)
+            }
+            EffectKind::All => {
+                let effects = if depth > 0 {
+                    (0..rng.usize(..4))
+                        .map(|_| {
+                            arbitrary_effect(
+                                pick(rng),
+                                rng,
+                                depth - 1,
+                            )
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                };
+                Effect::All(effects)
+            }
+        }
+    }
+
+    #[test]
+    fn test_missing_process() {
+        let mut state = State::default();
+        let before = state.clone();
+        let id = Id::new_v4();
+        let effects = [
+            Effect::OutputForProcess(id, 0.1),
+            Effect::ProcessLimit(id, 10.),
+            Effect::ModifyProcessLimitPercent(id, 0.1),
+            Effect::UnlocksProcess(id),
+            Effect::ModifyProcessByproducts(
+                id,
+                Byproduct::Co2,
+                0.1,
+            ),
+        ];
+        for effect in effects {
+            effect.apply(&mut state, None);
+            effect.unapply(&mut state, None);
+        }
+        assert!(
+            state.world.processes == before.world.processes
+        );
+
+        state.change_process_mix_share(&id, 1);
+        assert!(
+            state.world.processes == before.world.processes
+        );
+    }
+
+    #[test]
+    fn test_effect_serde_round_trip() {
+        let mut rng = fastrand::Rng::with_seed(42);
+        for kind in EffectKind::iter() {
+            for _ in 0..100 {
+                let effect =
+                    arbitrary_effect(kind, &mut rng, 2);
+                let json =
+                    serde_json::to_string(&effect).unwrap();
+                let parsed: Effect = serde_json::from_str(
+                    &json,
+                )
+                .unwrap_or_else(|err| {
+                    panic!("{json} failed to parse: {err}")
+                });
+                assert_eq!(effect, parsed, "{json}");
+
+                // The editor saves worlds as msgpack.
+                let bytes =
+                    rmp_serde::encode::to_vec_named(&effect)
+                        .unwrap();
+                let parsed: Effect =
+                    rmp_serde::decode::from_slice(&bytes)
+                        .unwrap();
+                assert_eq!(effect, parsed, "{json}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_sourced() {
+        let mut state = State::default();
+        let feat = ProcessFeature::IsSolar;
+        let modifiers = |state: &State| -> Vec<(f32, f32)> {
+            state
+                .world
+                .processes
+                .iter()
+                .filter(|p| p.features.contains(&feat))
+                .map(|p| {
+                    (
+                        p.output_modifier,
+                        p.byproduct_modifiers.co2,
+                    )
+                })
+                .collect()
+        };
+        let before = modifiers(&state);
+        assert!(!before.is_empty());
+
+        let source = Id::new_v4();
+        let output = Effect::OutputForFeature(feat, 0.5);
+        let co2 = Effect::CO2ForFeature(feat, -0.25);
+        for _ in 0..2 {
+            output.apply_sourced(&mut state, None, source);
+            co2.apply_sourced(&mut state, None, source);
+        }
+        let expected: Vec<_> = before
+            .iter()
+            .map(|(o, c)| (o + 0.5, c - 0.25))
+            .collect();
+        assert_eq!(modifiers(&state), expected);
+
+        // Other sources still stack.
+        let other = Id::new_v4();
+        output.apply_sourced(&mut state, None, other);
+        output.unapply_sourced(&mut state, None, other);
+        assert_eq!(modifiers(&state), expected);
+
+        // As do distinct effects from the same source.
+        let extra = Effect::OutputForFeature(feat, 0.25);
+        extra.apply_sourced(&mut state, None, source);
+        let stacked: Vec<_> = expected
+            .iter()
+            .map(|(o, c)| (o + 0.25, *c))
+            .collect();
+        assert_eq!(modifiers(&state), stacked);
+        extra.unapply_sourced(&mut state, None, source);
+        assert_eq!(modifiers(&state), expected);
+
+        for _ in 0..2 {
+            output.unapply_sourced(&mut state, None, source);
+            co2.unapply_sourced(&mut state, None, source);
+        }
+        assert_eq!(modifiers(&state), before);
+        assert!(state
+            .world
+            .processes
+            .iter()
+            .all(|p| p.sourced_modifiers.is_empty()));
+    }
+
     #[test]
     fn test_termination_shock() {
         let mut state = State::default();
@@ -1182,10 +2471,7 @@ mod tests {
                 .world
                 .projects
                 .iter_mut()
-                .find(|p| {
-                    p.name
-                        .contains("Solar Radiation Management")
-                })
+                .find(|p| p.is_srm())
                 .unwrap();
 
             project.points = 100;
@@ -1215,6 +2501,521 @@ mod tests {
         assert_eq!(state.world.temperature, temp_next);
     }
 
+    #[test]
+    fn test_termination_shock_without_srm() {
+        let mut world = World::default();
+        let srm_ids: Vec<Id> = world
+            .projects
+            .iter()
+            .filter(|p| p.is_srm())
+            .map(|p| p.id)
+            .collect();
+        for id in &srm_ids {
+            world.projects.remove(id);
+        }
+        let mut state = State::new(world);
+        let temp_mod = state.world.temperature_modifier;
+
+        let effect = Effect::TerminationShock;
+        effect.apply(&mut state, None);
+        effect.unapply(&mut state, None);
+        assert_eq!(state.world.temperature_modifier, temp_mod);
+    }
+
+    #[test]
+    fn test_region_leave_game_over() {
+        let mut state = State::default();
+        let region_id = state.world.regions.by_idx(0).id;
+        state.world.base_outlook = -state.outlook() - 1.;
+        assert!(state.outlook() < 0.);
+        assert!(!state.game_over);
+
+        Effect::RegionLeave.apply(&mut state, Some(region_id));
+        assert!(state.game_over);
+    }
+
+    #[test]
+    fn test_game_over_with_renamed_authoritarian() {
+        let mut state = State::default();
+        let region_id = state.world.regions.by_idx(0).id;
+        for npc in state.npcs.iter_mut() {
+            npc.name = format!("Localized {}", npc.name);
+        }
+        state.world.base_outlook = -state.outlook() - 1.;
+
+        // The Authoritarian being an ally prevents game over,
+        // even under a different name.
+        let authoritarian = NPCKind::Authoritarian.id();
+        state.npcs[&authoritarian].relationship = 6.;
+        let mut allied = state.clone();
+        Effect::RegionLeave.apply(&mut allied, Some(region_id));
+        assert!(!allied.game_over);
+
+        state.npcs[&authoritarian].relationship = 3.;
+        Effect::RegionLeave.apply(&mut state, Some(region_id));
+        assert!(state.game_over);
+    }
+
+    #[test]
+    fn test_apply_logged() {
+        let mut state = State::default();
+        let mut log = vec![];
+
+        let effect =
+            Effect::WorldVariable(WorldVariable::Outlook, 2.);
+        effect.apply_logged(&mut state, None, &mut log);
+        assert_eq!(
+            log,
+            vec![EffectChange {
+                variable: "WorldVariable:Outlook".into(),
+                delta: 2.,
+                region_id: None,
+            }]
+        );
+
+        log.clear();
+        state.political_capital = -10;
+        Effect::BailOut(20)
+            .apply_logged(&mut state, None, &mut log);
+        assert_eq!(log[0].delta, 30.);
+
+        log.clear();
+        let region_id = state.world.regions.by_idx(0).id;
+        Effect::Migration.apply_logged(
+            &mut state,
+            Some(region_id),
+            &mut log,
+        );
+        assert_eq!(log[0].region_id, Some(region_id));
+        assert!(log[0].delta < 0.);
+        let arrived: f32 =
+            log[1..].iter().map(|change| change.delta).sum();
+        assert!(
+            (arrived + log[0].delta).abs() / arrived < 1e-5
+        );
+
+        // Clamped effects log the change actually made.
+        log.clear();
+        state.protected_land = 0.9;
+        Effect::ProtectLand(0.3)
+            .apply_logged(&mut state, None, &mut log);
+        assert!((log[0].delta - 0.1).abs() < 1e-5);
+
+        log.clear();
+        let npc_id = state.npcs.by_idx(0).id;
+        state.npcs[&npc_id].seats = 0.1;
+        Effect::NPCSeats(npc_id, -0.5)
+            .apply_logged(&mut state, None, &mut log);
+        assert_eq!(log[0].delta, -0.1);
+    }
+
+    #[test]
+    fn test_temporary_effect() {
+        let mut state = State::default();
+        let water = state.resources.available.water;
+        let effect = Effect::Temporary(
+            Box::new(Effect::Resource(Resource::Water, -1e12)),
+            3,
+        );
+        effect.apply(&mut state, None);
+        assert_eq!(state.temporary_effects.len(), 1);
+
+        let start_year = state.world.year;
+        let mut years = 0;
+        while state.resources.available.water != water {
+            state.world.year += 1;
+            state.tick_temporary_effects();
+            years += 1;
+            assert!(years <= 3);
+        }
+        assert_eq!(state.world.year - start_year, 3);
+        assert!(state.temporary_effects.is_empty());
+
+        // Pending temporary effects should
+        // survive serialization.
+        effect.apply(&mut state, None);
+        let ser = serde_json::to_string(&state).unwrap();
+        let de: State = serde_json::from_str(&ser).unwrap();
+        assert_eq!(
+            de.temporary_effects,
+            state.temporary_effects
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        use strum::IntoEnumIterator;
+
+        let state = State::default();
+        let process = state.world.processes.first().id;
+        let project = state.world.projects.first().id;
+        let industry = state.world.industries.first().id;
+        let event = state.event_pool.events.first().id;
+        let npc = state.npcs.first().id;
+        let region = state.world.regions.first().id;
+        let missing = Id::new_v4();
+
+        for kind in EffectKind::iter() {
+            let effect = Effect::from_kind(
+                kind, process, project, industry, event, npc,
+                region,
+            );
+            assert!(effect.validate(&state).is_ok());
+
+            let effect = Effect::from_kind(
+                kind, missing, missing, missing, missing,
+                missing, missing,
+            );
+            let has_id = effect.process_id().is_some()
+                || effect.project_id().is_some()
+                || effect.industry_id().is_some()
+                || effect.event_id().is_some()
+                || effect.npc_id().is_some()
+                || effect.region_id().is_some();
+            assert_eq!(
+                effect.validate(&state).is_err(),
+                has_id
+            );
+        }
+
+        let effect = Effect::Temporary(
+            Box::new(Effect::UnlocksNPC(missing)),
+            3,
+        );
+        assert_eq!(
+            effect.validate(&state),
+            Err(EffectError::MissingNPC(missing))
+        );
+        assert!(state.validate_effects().is_ok());
+    }
+
+    #[test]
+    fn test_region_habitability_by_id() {
+        let mut state = State::default();
+        let region_id = state.world.regions.by_idx(0).id;
+        let other_id = state.world.regions.by_idx(1).id;
+        let base =
+            state.world.regions[&region_id].base_habitability;
+        let other_base =
+            state.world.regions[&other_id].base_habitability;
+
+        let effect =
+            Effect::RegionHabitabilityById(region_id, 2.);
+        effect.apply(&mut state, None);
+        assert_eq!(
+            state.world.regions[&region_id].base_habitability,
+            base + 2.
+        );
+        assert_eq!(
+            state.world.regions[&other_id].base_habitability,
+            other_base
+        );
+        effect.unapply(&mut state, None);
+        assert_eq!(
+            state.world.regions[&region_id].base_habitability,
+            base
+        );
+
+        let scaled = effect.clone() * 0.5;
+        assert_eq!(
+            scaled,
+            Effect::RegionHabitabilityById(region_id, 1.)
+        );
+
+        // Seceded regions are unaffected.
+        state.world.regions[&region_id].seceded = true;
+        effect.apply(&mut state, None);
+        assert_eq!(
+            state.world.regions[&region_id].base_habitability,
+            base
+        );
+    }
+
+    #[test]
+    fn test_npc_seats() {
+        let mut state = State::default();
+        let npc_id = state.npcs.unlocked().next().unwrap().id;
+        let seats = state.npcs[&npc_id].seats;
+
+        let effect = Effect::NPCSeats(npc_id, -100.);
+        effect.apply(&mut state, None);
+        assert_eq!(state.npcs[&npc_id].seats, 0.);
+        effect.apply(&mut state, None);
+        assert_eq!(state.npcs[&npc_id].seats, 0.);
+
+        effect.unapply(&mut state, None);
+        effect.unapply(&mut state, None);
+        assert_eq!(state.npcs[&npc_id].seats, seats);
+
+        let effect = Effect::NPCSeats(npc_id, 0.1) * 0.5;
+        effect.apply(&mut state, None);
+        assert_eq!(state.npcs[&npc_id].seats, seats + 0.05);
+        assert_eq!(effect.fingerprint(), "NPCSeats:");
+    }
+
+    #[test]
+    fn test_protect_land() {
+        let mut state = State {
+            protected_land: 0.9,
+            ..Default::default()
+        };
+
+        let effect = Effect::ProtectLand(0.3);
+        effect.apply(&mut state, None);
+        assert_eq!(state.protected_land, 1.);
+        effect.apply(&mut state, None);
+        assert_eq!(state.protected_land, 1.);
+
+        effect.unapply(&mut state, None);
+        assert_eq!(state.protected_land, 1.);
+        effect.unapply(&mut state, None);
+        assert_eq!(state.protected_land, 0.9);
+
+        let effect = Effect::ProtectLand(-1.);
+        effect.apply(&mut state, None);
+        assert_eq!(state.protected_land, 0.);
+        effect.unapply(&mut state, None);
+        assert_eq!(state.protected_land, 0.9);
+    }
+
+    #[test]
+    fn test_protect_land_unapply_out_of_order() {
+        let mut state = State {
+            protected_land: 0.,
+            ..Default::default()
+        };
+        let small = Effect::ProtectLand(0.1);
+        let large = Effect::ProtectLand(0.5);
+        small.apply(&mut state, None);
+        large.apply(&mut state, None);
+        assert!((state.protected_land - 0.6).abs() < 1e-5);
+
+        // Reversing the first effect leaves
+        // the second effect's protection.
+        small.unapply(&mut state, None);
+        assert!((state.protected_land - 0.5).abs() < 1e-5);
+        large.unapply(&mut state, None);
+        assert!(state.protected_land.abs() < 1e-5);
+        assert!(state.protected_land_changes.is_empty());
+    }
+
+    #[test]
+    fn test_bailout_with_debt() {
+        let mut state = State {
+            political_capital: -30,
+            ..Default::default()
+        };
+
+        let effect = Effect::BailOut(20);
+        effect.apply(&mut state, None);
+        assert_eq!(state.political_capital, 20);
+
+        effect.unapply(&mut state, None);
+        assert_eq!(state.political_capital, -30);
+    }
+
+    #[test]
+    fn test_bailout_without_debt() {
+        let mut state = State {
+            political_capital: 30,
+            ..Default::default()
+        };
+
+        let effect = Effect::BailOut(20);
+        effect.apply(&mut state, None);
+        assert_eq!(state.political_capital, 50);
+
+        effect.unapply(&mut state, None);
+        assert_eq!(state.political_capital, 30);
+    }
+
+    #[test]
+    fn test_process_limit_percent() {
+        let mut state = State::default();
+        let id = state.world.processes.first().id;
+        state.world.processes[&id].limit = Some(100.);
+
+        let effect = Effect::ModifyProcessLimitPercent(id, 0.5);
+        effect.apply(&mut state, None);
+        assert_eq!(
+            state.world.processes[&id].limit,
+            Some(150.)
+        );
+        effect.unapply(&mut state, None);
+        assert_eq!(
+            state.world.processes[&id].limit,
+            Some(100.)
+        );
+    }
+
+    #[test]
+    fn test_process_limit_percent_without_limit() {
+        let mut state = State::default();
+        let process = state
+            .world
+            .processes
+            .iter_mut()
+            .find(|p| p.mix_share > 0)
+            .unwrap();
+        process.limit = None;
+        let id = process.id;
+        let expected = state.output_demand.total()
+            [process.output]
+            * process.mix_percent()
+            * 0.5;
+
+        let effect =
+            Effect::ModifyProcessLimitPercent(id, -0.5);
+        effect.apply(&mut state, None);
+        assert_eq!(
+            state.world.processes[&id].limit,
+            Some(expected)
+        );
+
+        // Can't divide by zero.
+        let effect = Effect::ModifyProcessLimitPercent(id, -1.);
+        effect.apply(&mut state, None);
+        effect.unapply(&mut state, None);
+        assert_eq!(state.world.processes[&id].limit, Some(0.));
+    }
+
+    #[test]
+    fn test_describe() {
+        let state = State::default();
+        let process = state.world.processes.first();
+        let effect = Effect::OutputForProcess(process.id, 0.05);
+        assert_eq!(
+            effect.describe(&state),
+            format!("+5% output for {}", process.name)
+        );
+
+        let effect = Effect::Temporary(
+            Box::new(Effect::Resource(Resource::Land, -10.)),
+            3,
+        );
+        assert_eq!(
+            effect.describe(&state),
+            "-10 Land available for 3 years"
+        );
+
+        let effect = Effect::UnlocksProject(Id::new_v4());
+        assert_eq!(effect.describe(&state), "Unlocks Unknown");
+    }
+
+    #[test]
+    fn test_outlook_change_unapply() {
+        let mut state = State::default();
+        let outlooks: Vec<f32> = state
+            .world
+            .regions
+            .iter()
+            .map(|r| r.outlook)
+            .collect();
+
+        let demand_effect =
+            Effect::DemandOutlookChange(Output::Fuel, 1.5);
+        let income_effect = Effect::IncomeOutlookChange(1.5);
+        demand_effect.apply(&mut state, None);
+        income_effect.apply(&mut state, None);
+
+        // Change demand and income levels in between.
+        for demand in &mut state.world.per_capita_demand {
+            demand.factor[Output::Fuel] += 2.;
+        }
+        for region in state.world.regions.iter_mut() {
+            region.set_income_level(3);
+        }
+
+        demand_effect.unapply(&mut state, None);
+        income_effect.unapply(&mut state, None);
+        for (region, outlook) in
+            state.world.regions.iter().zip(outlooks)
+        {
+            assert_eq!(region.outlook, outlook);
+        }
+        assert!(state.outlook_changes.is_empty());
+    }
+
+    #[test]
+    fn test_mean_outlook_change() {
+        let mut state = State::default();
+        for (i, region) in
+            state.world.regions.iter_mut().enumerate()
+        {
+            region.set_income_level(i % 4);
+        }
+        let mean_outlook = |state: &State| {
+            state
+                .world
+                .regions
+                .iter()
+                .map(|r| r.outlook)
+                .sum::<f32>()
+                / state.world.regions.len() as f32
+        };
+
+        // The mean matches what's actually applied,
+        // including for half-levels.
+        for mult in [0.5, 1.5, -0.5] {
+            let mut applied = state.clone();
+            let before = mean_outlook(&applied);
+            Effect::IncomeOutlookChange(mult)
+                .apply(&mut applied, None);
+            let change = mean_outlook(&applied) - before;
+            let expected =
+                mean_income_outlook_change(mult, &state);
+            assert!((change - expected).abs() < 1e-4);
+
+            let mut applied = state.clone();
+            let before = mean_outlook(&applied);
+            Effect::DemandOutlookChange(Output::Fuel, mult)
+                .apply(&mut applied, None);
+            let change = mean_outlook(&applied) - before;
+            let expected = mean_demand_outlook_change(
+                mult,
+                &Output::Fuel,
+                &state,
+            );
+            assert!((change - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_all_effect() {
+        let mut state = State::default();
+        let capital = state.political_capital;
+        let land = state.resources.available.land;
+
+        let effect = Effect::All(vec![
+            Effect::PlayerVariable(
+                PlayerVariable::PoliticalCapital,
+                10.,
+            ),
+            Effect::Resource(Resource::Land, 100.),
+        ]);
+        assert_eq!(
+            effect.fingerprint(),
+            "All:[PlayerVariable:PoliticalCapital,Resource:Land]"
+        );
+
+        let scaled = effect.clone() * 0.5;
+        scaled.apply(&mut state, None);
+        assert_eq!(state.political_capital, capital + 5);
+        assert_eq!(state.resources.available.land, land + 50.);
+
+        scaled.unapply(&mut state, None);
+        assert_eq!(state.political_capital, capital);
+        assert_eq!(state.resources.available.land, land);
+
+        let nested = Effect::All(vec![
+            effect.clone(),
+            Effect::AddFlag(Flag::Vegan),
+        ]);
+        let ser = serde_json::to_string(&nested).unwrap();
+        let de: Effect = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, nested);
+    }
+
     #[test]
     fn test_output_demand_amount() {
         let mut state = State::default();
@@ -1227,4 +3028,179 @@ mod tests {
             6.
         );
     }
+
+    #[test]
+    fn test_migration_unapply() {
+        let mut state = State::default();
+        let start: Vec<f32> = state
+            .world
+            .regions
+            .iter()
+            .map(|r| r.population)
+            .collect();
+        let region_id = state.world.regions.by_idx(0).id;
+
+        let effect = Effect::Migration;
+        effect.apply(&mut state, Some(region_id));
+        assert!(
+            state.world.regions.by_idx(0).population < start[0]
+        );
+
+        // Toggling borders or seceding in between
+        // shouldn't affect the reversal.
+        state.flags.push(Flag::ClosedBorders);
+        state.world.regions.by_idx_mut(1).seceded = true;
+
+        effect.unapply(&mut state, Some(region_id));
+        for (region, pop) in
+            state.world.regions.iter().zip(start.iter())
+        {
+            assert!(
+                (region.population - pop).abs() / pop < 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn test_remove_flag() {
+        let mut state = State::default();
+        let add = Effect::AddFlag(Flag::ClosedBorders);
+        let remove = Effect::RemoveFlag(Flag::ClosedBorders);
+
+        add.apply(&mut state, None);
+        assert!(state.flags.contains(&Flag::ClosedBorders));
+
+        remove.apply(&mut state, None);
+        assert!(!state.flags.contains(&Flag::ClosedBorders));
+
+        remove.unapply(&mut state, None);
+        assert!(state.flags.contains(&Flag::ClosedBorders));
+
+        assert_eq!(
+            remove.fingerprint(),
+            "RemoveFlag:ClosedBorders"
+        );
+    }
+
+    #[test]
+    fn test_project_cost_floor() {
+        use crate::{Cost, OutputMap, MIN_COST_MODIFIER};
+
+        let mut state = State::default();
+        let id = state.world.projects.iter().next().unwrap().id;
+        state.world.projects[&id].base_cost = Cost::Fixed(100);
+        state.world.projects[&id].cost_modifier = 1.;
+
+        let demand = OutputMap::default();
+        let floor = (100. * MIN_COST_MODIFIER).round() as usize;
+        for _ in 0..5 {
+            state.apply_effects(
+                &[Effect::ProjectCostModifier(id, -0.3)],
+                None,
+            );
+            let project = &mut state.world.projects[&id];
+            project.update_cost(2022, 0., 0., &demand, 1.);
+            assert!(project.cost >= floor);
+        }
+        assert!(state.world.projects[&id].cost_modifier < 0.);
+        assert_eq!(state.world.projects[&id].cost, floor);
+    }
+
+    #[test]
+    fn test_secession_aggregates() {
+        let mut state = State::default();
+        state.recompute_after_secession();
+        let demand = state.output_demand.total();
+        let population = state.world.population();
+
+        let region_id = state.world.regions.by_idx(0).id;
+        Effect::RegionLeave.apply(&mut state, Some(region_id));
+        state.recompute_after_secession();
+
+        let seceded = &state.world.regions[&region_id];
+        assert!(
+            state.world.population()
+                < population - seceded.population * 0.99
+        );
+        let new_demand = state.output_demand.total();
+        assert!(
+            new_demand.plant_calories < demand.plant_calories
+        );
+        assert!(
+            new_demand.animal_calories < demand.animal_calories
+        );
+    }
+
+    #[test]
+    fn test_migration_skips_seceded() {
+        let mut state = State::default();
+        let origin = state.world.regions.by_idx(0).id;
+        let target = state.world.regions.by_idx(1).id;
+
+        // Make the seceded region the most habitable,
+        // with another region as the next best option.
+        let other = state.world.regions.by_idx(2).id;
+        state.world.regions[&other].base_habitability = 50.;
+        state.world.regions[&target].base_habitability = 100.;
+        state.world.regions[&target].seceded = true;
+        let target_pop =
+            state.world.regions[&target].population;
+
+        Effect::Migration.apply(&mut state, Some(origin));
+        assert_eq!(
+            state.world.regions[&target].population,
+            target_pop
+        );
+        let wave = &state.migrations[&origin][0];
+        assert!(wave
+            .arrivals
+            .iter()
+            .all(|(id, _)| *id != target));
+        assert!(!wave.arrivals.is_empty());
+    }
+
+    #[test]
+    fn test_region_flags() {
+        let mut state = State::default();
+        let region_id = state.world.regions.by_idx(0).id;
+        let effect =
+            Effect::AddRegionFlag(RegionFlag::Protests);
+
+        // Applied twice, e.g. by two events.
+        effect.apply(&mut state, Some(region_id));
+        effect.apply(&mut state, Some(region_id));
+        let region = &state.world.regions[&region_id];
+        assert!(region.has_flag(RegionFlag::Protests));
+        assert!(!region.has_flag(RegionFlag::Riots));
+        assert_eq!(region.flags.len(), 2);
+
+        // Unapplying one keeps the other.
+        effect.unapply(&mut state, Some(region_id));
+        let region = &state.world.regions[&region_id];
+        assert!(region.has_flag(RegionFlag::Protests));
+
+        effect.unapply(&mut state, Some(region_id));
+        let region = &mut state.world.regions[&region_id];
+        assert!(!region.has_flag(RegionFlag::Protests));
+
+        // Removing a missing flag is a no-op.
+        assert!(!region.remove_flag(RegionFlag::Protests));
+        region.flags.push(RegionFlag::Riots);
+        assert!(region.remove_flag(RegionFlag::Riots));
+        assert!(region.flags.is_empty());
+    }
+
+    #[test]
+    fn test_scaling_preserves_kind() {
+        use strum::IntoEnumIterator;
+
+        let id = Id::new_v4();
+        for kind in EffectKind::iter() {
+            let effect =
+                Effect::from_kind(kind, id, id, id, id, id, id);
+            let scaled = effect * 0.5;
+            let scaled_kind: EffectKind = (&scaled).into();
+            assert_eq!(scaled_kind, kind);
+        }
+    }
 }
@@ -10,15 +10,43 @@ use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, fmt::Display};
 use strum::{Display, EnumIter, EnumString, IntoStaticStr};
 
-#[derive(
-    Clone, Debug, Default, Serialize, Deserialize, PartialEq,
-)]
+/// The default for how many queued events can come due
+/// in a single roll, see `EventPool::max_queued_per_turn`.
+pub const MAX_QUEUED_EVENTS_PER_TURN: usize = 3;
+
+fn default_max_queued() -> usize {
+    MAX_QUEUED_EVENTS_PER_TURN
+}
+
+/// Identifies a story arc, i.e. an ordered chain of events.
+pub type ArcId = String;
+
+/// An event which happened in a roll,
+/// as (event, region id, severity).
+pub type RolledEvent = (Event, Option<Id>, EventSeverity);
+
+/// A queued event pushed back to the next roll,
+/// as (phase, event id, region id).
+pub type DeferredEvent = (Phase, Id, Option<Id>);
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct EventPool {
     pub events: Collection<Event>,
 
     // (phase, event id, region id, countdown)
     pub queue: Vec<(Phase, Id, Option<Id>, usize)>,
     pub triggered: Vec<(Phase, Id, Option<Id>)>,
+
+    /// How many queued events can come due in a single roll;
+    /// any beyond this are deferred to the next one.
+    #[serde(default = "default_max_queued")]
+    pub max_queued_per_turn: usize,
+}
+
+impl Default for EventPool {
+    fn default() -> Self {
+        EventPool::new(Collection::default())
+    }
 }
 
 impl EventPool {
@@ -27,6 +55,7 @@ impl EventPool {
             events,
             queue: Vec::new(),
             triggered: Vec::new(),
+            max_queued_per_turn: MAX_QUEUED_EVENTS_PER_TURN,
         }
     }
 
@@ -44,7 +73,25 @@ impl EventPool {
         &mut self,
         phase: Phase,
         state: &State,
-    ) -> Vec<(Event, Option<Id>)> {
+    ) -> (Vec<RolledEvent>, Vec<DeferredEvent>) {
+        self.roll_with_seed(phase, state, fastrand::u64(..))
+    }
+
+    /// Roll events for the phase using the given seed.
+    /// The same seed, pool, and state will always
+    /// roll the same events, so runs can be replayed.
+    ///
+    /// Also returns the queued events which came due
+    /// but were pushed back to the next roll because
+    /// of the per-turn cap.
+    pub fn roll_with_seed(
+        &mut self,
+        phase: Phase,
+        state: &State,
+        seed: u64,
+    ) -> (Vec<RolledEvent>, Vec<DeferredEvent>) {
+        let mut rng = fastrand::Rng::with_seed(seed);
+
         // Prevent duplicate events
         let mut existing: HashSet<&Id> = HashSet::new();
         for (_, ev_id, _, _) in &self.queue {
@@ -63,12 +110,15 @@ impl EventPool {
                     && !ev.occurred
                     && !ev.locked
                     && !existing.contains(&ev.id)
+                    && self.arc_ready(ev)
             })
             .map(|ev| ev.id)
             .collect();
-        fastrand::shuffle(&mut valid_ids);
+        rng.shuffle(&mut valid_ids);
 
         // Tick queued countdowns
+        let mut deferred = vec![];
+        let mut n_due = 0;
         let mut i = 0;
         while i < self.queue.len() {
             let try_trigger = {
@@ -81,10 +131,24 @@ impl EventPool {
                     false
                 }
             };
+
+            // Too many queued events this turn,
+            // so push this one back to the next.
+            if try_trigger && n_due >= self.max_queued_per_turn
+            {
+                let (phase, ev_id, region_id, countdown) =
+                    &mut self.queue[i];
+                *countdown = 1;
+                deferred.push((*phase, *ev_id, *region_id));
+                i += 1;
+                continue;
+            }
+
             if try_trigger {
+                n_due += 1;
                 let (_, ev_id, region_id, _) = self.queue[i];
                 let ev = &mut self.events[&ev_id];
-                if ev.roll(state, region_id) {
+                if ev.roll(state, region_id, &mut rng) {
                     self.triggered
                         .push((ev.phase, ev_id, region_id));
                 }
@@ -102,7 +166,8 @@ impl EventPool {
             // Icon-type events are always local
             if ev.phase == Phase::Icon {
                 for region in state.world.regions.iter() {
-                    if ev.roll(state, Some(region.id)) {
+                    if ev.roll(state, Some(region.id), &mut rng)
+                    {
                         self.triggered.push((
                             ev.phase,
                             ev_id,
@@ -113,7 +178,11 @@ impl EventPool {
             } else {
                 if ev.is_regional() {
                     for region in state.world.regions.iter() {
-                        if ev.roll(state, Some(region.id)) {
+                        if ev.roll(
+                            state,
+                            Some(region.id),
+                            &mut rng,
+                        ) {
                             self.triggered.push((
                                 ev.phase,
                                 ev_id,
@@ -121,16 +190,16 @@ impl EventPool {
                             ));
                         }
                     }
-                } else if ev.roll(state, None) {
+                } else if ev.roll(state, None, &mut rng) {
                     self.triggered
                         .push((ev.phase, ev_id, None));
                 }
             }
         }
 
-        // Get the first MAX_EVENTS_PER_TURN triggered events
+        // Get the triggered events for this phase
         let mut happening = Vec::new();
-        fastrand::shuffle(&mut self.triggered);
+        rng.shuffle(&mut self.triggered);
 
         let mut i = 0;
         while i < self.triggered.len() {
@@ -153,10 +222,123 @@ impl EventPool {
 
         let mut results = vec![];
         for (ev_id, region_id) in happening {
-            results
-                .push((self.events[&ev_id].clone(), region_id));
+            let mut ev = self.events[&ev_id].clone();
+            let severity = if ev.variable_severity {
+                EventSeverity::roll(&mut rng)
+            } else {
+                EventSeverity::default()
+            };
+            ev.effects = ev.scaled_effects(severity);
+            results.push((ev, region_id, severity));
+        }
+        (results, deferred)
+    }
+
+    /// The chance of each random event occurring
+    /// the next time its phase is rolled, given the current state.
+    /// Regional events may occur in any region.
+    /// This doesn't change the pool or consume any randomness.
+    pub fn peek(&self, state: &State) -> Vec<(Id, f32)> {
+        let existing: HashSet<&Id> = self
+            .queue
+            .iter()
+            .map(|(_, ev_id, _, _)| ev_id)
+            .chain(
+                self.triggered
+                    .iter()
+                    .map(|(_, ev_id, _)| ev_id),
+            )
+            .collect();
+
+        self.events
+            .iter()
+            .filter(|ev| {
+                !ev.occurred
+                    && !ev.locked
+                    && !existing.contains(&ev.id)
+                    && self.arc_ready(ev)
+            })
+            .filter_map(|ev| {
+                let prob = if ev.phase == Phase::Icon
+                    || ev.is_regional()
+                {
+                    // Chance of occurring in at least one region.
+                    1. - state
+                        .world
+                        .regions
+                        .iter()
+                        .map(|region| {
+                            1. - ev
+                                .chance(state, Some(region.id))
+                        })
+                        .product::<f32>()
+                } else {
+                    ev.chance(state, None)
+                };
+                (prob > 0.).then_some((ev.id, prob))
+            })
+            .collect()
+    }
+
+    /// Whether the event's place in its arc has been reached,
+    /// i.e. the previous step of its arc has occurred.
+    /// Events outside of an arc are always ready.
+    fn arc_ready(&self, event: &Event) -> bool {
+        match &event.arc {
+            Some(arc) if event.arc_step > 0 => {
+                self.events.iter().any(|ev| {
+                    ev.arc.as_ref() == Some(arc)
+                        && ev.arc_step == event.arc_step - 1
+                        && ev.occurred
+                })
+            }
+            _ => true,
+        }
+    }
+}
+
+/// How severe an event is when it occurs,
+/// which scales the magnitude of its effects.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+    Default,
+)]
+pub enum EventSeverity {
+    Minor,
+    #[default]
+    Major,
+    Catastrophic,
+}
+
+impl EventSeverity {
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            Self::Minor => 0.5,
+            Self::Major => 1.,
+            Self::Catastrophic => 2.,
+        }
+    }
+
+    /// Draw a severity; most events are major,
+    /// with catastrophic ones being the rarest.
+    pub fn roll(rng: &mut fastrand::Rng) -> Self {
+        let roll = rng.f32();
+        if roll < 0.1 {
+            Self::Catastrophic
+        } else if roll < 0.4 {
+            Self::Minor
+        } else {
+            Self::Major
         }
-        results
     }
 }
 
@@ -225,6 +407,19 @@ pub struct Event {
     /// Icon event intensity
     pub intensity: usize,
 
+    /// If this event's severity is rolled when it occurs.
+    #[serde(default)]
+    pub variable_severity: bool,
+
+    /// The story arc this event belongs to, if any.
+    #[serde(default)]
+    pub arc: Option<ArcId>,
+
+    /// This event's position in its arc;
+    /// it can only occur after the previous step has.
+    #[serde(default)]
+    pub arc_step: usize,
+
     pub flavor: EventFlavor,
     pub notes: String,
 }
@@ -238,6 +433,9 @@ impl Default for Event {
             phase: Phase::WorldMain,
             prob_modifier: 1.,
             intensity: 0,
+            variable_severity: false,
+            arc: None,
+            arc_step: 0,
             effects: vec![],
             probabilities: vec![Probability {
                 likelihood: Likelihood::Guaranteed,
@@ -278,6 +476,19 @@ impl Event {
         self.probabilities.iter().any(|prob| prob.is_regional())
     }
 
+    /// This event's effects scaled for
+    /// an occurrence of the given severity.
+    pub fn scaled_effects(
+        &self,
+        severity: EventSeverity,
+    ) -> Vec<Effect> {
+        let mult = severity.multiplier();
+        self.effects
+            .iter()
+            .map(|effect| effect.clone() * mult)
+            .collect()
+    }
+
     /// Gets the likelihood of this event occurring.
     /// If there are multiple probabilities, it returns
     /// the likelihood of the first probability that has
@@ -294,16 +505,34 @@ impl Event {
         res
     }
 
+    /// This event's probability modifier,
+    /// scaled by the game's difficulty.
+    fn prob_modifier(&self, state: &State) -> f32 {
+        self.prob_modifier * state.difficulty.event_modifier()
+    }
+
+    /// The chance of this event occurring, if it's eligible.
+    fn chance(
+        &self,
+        state: &State,
+        region_id: Option<Id>,
+    ) -> f32 {
+        self.eval(state, region_id).map_or(0., |likelihood| {
+            (likelihood.p() * self.prob_modifier(state)).min(1.)
+        })
+    }
+
     /// Roll to see if the event occurs.
     fn roll(
         &self,
         state: &State,
         region_id: Option<Id>,
+        rng: &mut fastrand::Rng,
     ) -> bool {
         match self.eval(state, region_id) {
             Some(likelihood) => {
                 let prob = likelihood.p();
-                fastrand::f32() <= (prob * self.prob_modifier)
+                rng.f32() <= (prob * self.prob_modifier(state))
             }
             None => false,
         }
@@ -316,6 +545,7 @@ mod test {
         super::{
             condition::Comparator,
             LocalVariable,
+            PlayerVariable,
             WorldVariable,
         },
         *,
@@ -368,10 +598,11 @@ mod test {
             events,
             queue: vec![],
             triggered: vec![],
+            max_queued_per_turn: MAX_QUEUED_EVENTS_PER_TURN,
         };
 
         let mut state = State::default();
-        let events =
+        let (events, _) =
             pool.roll_for_phase(Phase::WorldMain, &state);
 
         // Only event B should happen
@@ -381,7 +612,7 @@ mod test {
         // But if we set it so that event A's first condition
         // is met, it should happen
         state.world.year = 10;
-        let events =
+        let (events, _) =
             pool.roll_for_phase(Phase::WorldMain, &state);
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].0.name, "Test Event A");
@@ -415,6 +646,7 @@ mod test {
             events,
             queue: vec![],
             triggered: vec![],
+            max_queued_per_turn: MAX_QUEUED_EVENTS_PER_TURN,
         };
 
         let mut state = State::default();
@@ -431,7 +663,8 @@ mod test {
             },
         ]
         .into();
-        let events = pool.roll_for_phase(Phase::Icon, &state);
+        let (events, _) =
+            pool.roll_for_phase(Phase::Icon, &state);
 
         // No events should happen
         assert_eq!(events.len(), 0);
@@ -440,7 +673,8 @@ mod test {
         let region = state.world.regions.by_idx_mut(1);
         region.population = 10.;
         let id = region.id;
-        let events = pool.roll_for_phase(Phase::Icon, &state);
+        let (events, _) =
+            pool.roll_for_phase(Phase::Icon, &state);
         assert_eq!(events.len(), 1);
         assert_eq!(events[0].0.name, "Test Event A");
         assert_eq!(events[0].1, Some(id));
@@ -463,21 +697,318 @@ mod test {
             events,
             queue: vec![(Phase::WorldMain, id, None, 2)],
             triggered: vec![],
+            max_queued_per_turn: MAX_QUEUED_EVENTS_PER_TURN,
         };
 
         let state = State::default();
 
         // No events should happen
-        let events =
+        let (events, _) =
             pool.roll_for_phase(Phase::WorldMain, &state);
         assert_eq!(events.len(), 0);
 
         // Countdown finished
-        let events =
+        let (events, _) =
             pool.roll_for_phase(Phase::WorldMain, &state);
         assert_eq!(events.len(), 1);
     }
 
+    #[test]
+    fn test_event_pool_seeded() {
+        let events: Collection<Event> = (0..20)
+            .map(|i| Event {
+                id: Id::new_v4(),
+                name: format!("Test Event {i}"),
+                phase: Phase::WorldMain,
+                probabilities: vec![Probability {
+                    likelihood: Likelihood::Likely,
+                    conditions: vec![],
+                }],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+            .into();
+        let mut pool_a = EventPool::new(events.clone());
+        let mut pool_b = EventPool::new(events);
+
+        let state = State::default();
+        for seed in [0, 1, 42] {
+            let (events_a, _) = pool_a.roll_with_seed(
+                Phase::WorldMain,
+                &state,
+                seed,
+            );
+            let (events_b, _) = pool_b.roll_with_seed(
+                Phase::WorldMain,
+                &state,
+                seed,
+            );
+            let ids = |events: Vec<(
+                Event,
+                Option<Id>,
+                EventSeverity,
+            )>| {
+                events
+                    .into_iter()
+                    .map(|(ev, region_id, _)| {
+                        (ev.id, region_id)
+                    })
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(ids(events_a), ids(events_b));
+        }
+        assert_eq!(pool_a, pool_b);
+    }
+
+    #[test]
+    fn test_event_arc() {
+        let arc: ArcId = "Test Arc".into();
+        let steps: Vec<Event> = (0..3)
+            .map(|step| Event {
+                id: Id::new_v4(),
+                name: format!("Test Arc Step {step}"),
+                phase: Phase::WorldMain,
+                arc: Some(arc.clone()),
+                arc_step: step,
+                ..Default::default()
+            })
+            .collect();
+        let ids: Vec<Id> =
+            steps.iter().map(|ev| ev.id).collect();
+
+        // Later steps come first so they're
+        // not ordered correctly just by chance.
+        let mut pool = EventPool::new(
+            steps.into_iter().rev().collect::<Vec<_>>().into(),
+        );
+
+        let state = State::default();
+        for (step, id) in ids.iter().enumerate() {
+            let (events, _) = pool.roll_with_seed(
+                Phase::WorldMain,
+                &state,
+                step as u64,
+            );
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].0.id, *id);
+        }
+
+        // The arc is finished.
+        let (events, _) =
+            pool.roll_with_seed(Phase::WorldMain, &state, 3);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_event_severity() {
+        let id = Id::new_v4();
+        let mut state = State {
+            event_pool: EventPool::new(
+                vec![Event {
+                    id,
+                    name: "Test Event A".into(),
+                    effects: vec![Effect::PlayerVariable(
+                        PlayerVariable::PoliticalCapital,
+                        10.,
+                    )],
+                    ..Default::default()
+                }]
+                .into(),
+            ),
+            ..Default::default()
+        };
+
+        let mut change_for = |severity| {
+            let before = state.political_capital;
+            state.apply_event(id, None, severity);
+            state.political_capital - before
+        };
+        let minor = change_for(EventSeverity::Minor);
+        let major = change_for(EventSeverity::Major);
+        let catastrophic =
+            change_for(EventSeverity::Catastrophic);
+        assert_eq!(major, 10);
+        assert!(minor < major);
+        assert!(catastrophic > major);
+    }
+
+    #[test]
+    fn test_event_severity_rolled() {
+        let id = Id::new_v4();
+        let mut pool = EventPool::new(
+            vec![Event {
+                id,
+                name: "Test Event A".into(),
+                phase: Phase::Icon,
+                variable_severity: true,
+                effects: vec![Effect::PlayerVariable(
+                    PlayerVariable::PoliticalCapital,
+                    10.,
+                )],
+                ..Default::default()
+            }]
+            .into(),
+        );
+
+        // Icon events repeat, so roll them a bunch of times.
+        let state = State::default();
+        let mut seen = vec![];
+        for seed in 0..50 {
+            let (events, _) =
+                pool.roll_with_seed(Phase::Icon, &state, seed);
+            for (ev, _, severity) in events {
+                let Effect::PlayerVariable(_, amount) =
+                    ev.effects[0]
+                else {
+                    unreachable!();
+                };
+                assert_eq!(amount, 10. * severity.multiplier());
+                if !seen.contains(&severity) {
+                    seen.push(severity);
+                }
+            }
+        }
+        assert_eq!(seen.len(), 3);
+
+        // The pool's event itself is left unscaled.
+        assert_eq!(
+            pool.events[&id].effects,
+            vec![Effect::PlayerVariable(
+                PlayerVariable::PoliticalCapital,
+                10.
+            )]
+        );
+    }
+
+    #[test]
+    fn test_event_pool_queue_cap() {
+        let events: Vec<Event> = (0
+            ..MAX_QUEUED_EVENTS_PER_TURN + 2)
+            .map(|i| Event {
+                id: Id::new_v4(),
+                name: format!("Test Event {i}"),
+                phase: Phase::WorldMain,
+
+                // Note: locked so they don't trigger on their own
+                locked: true,
+                ..Default::default()
+            })
+            .collect();
+        let mut pool = EventPool::new(events.clone().into());
+        for ev in &events {
+            pool.queue_event(ev.id, None, 1);
+        }
+
+        let state = State::default();
+        let (happened, deferred) =
+            pool.roll_with_seed(Phase::WorldMain, &state, 0);
+        assert_eq!(happened.len(), MAX_QUEUED_EVENTS_PER_TURN);
+        assert_eq!(deferred.len(), 2);
+        assert_eq!(pool.queue.len(), 2);
+        for (_, _, _, countdown) in &pool.queue {
+            assert_eq!(*countdown, 1);
+        }
+
+        // Deferred events happen the next turn.
+        let deferred: Vec<Id> =
+            deferred.iter().map(|(_, id, _)| *id).collect();
+        let (happened, deferred_again) =
+            pool.roll_with_seed(Phase::WorldMain, &state, 1);
+        let mut ids: Vec<Id> =
+            happened.iter().map(|(ev, _, _)| ev.id).collect();
+        ids.sort();
+        let mut expected = deferred;
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert!(deferred_again.is_empty());
+        assert!(pool.queue.is_empty());
+    }
+
+    #[test]
+    fn test_event_pool_configured_queue_cap() {
+        let state = State::default();
+        for cap in [1, 10] {
+            let events: Vec<Event> = (0..6)
+                .map(|i| Event {
+                    id: Id::new_v4(),
+                    name: format!("Test Event {i}"),
+                    phase: Phase::WorldMain,
+                    locked: true,
+                    ..Default::default()
+                })
+                .collect();
+            let mut pool =
+                EventPool::new(events.clone().into());
+            pool.max_queued_per_turn = cap;
+            for ev in &events {
+                pool.queue_event(ev.id, None, 1);
+            }
+
+            let mut rolls = vec![];
+            while !pool.queue.is_empty() {
+                let (happened, _) = pool.roll_with_seed(
+                    Phase::WorldMain,
+                    &state,
+                    rolls.len() as u64,
+                );
+                assert!(happened.len() <= cap);
+                rolls.push(happened.len());
+            }
+            if cap == 1 {
+                assert_eq!(rolls, vec![1; 6]);
+            } else {
+                assert_eq!(rolls, vec![6]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_pool_peek() {
+        let likely = Event {
+            id: Id::new_v4(),
+            name: "Test Event A".into(),
+            phase: Phase::WorldMain,
+            prob_modifier: 0.5,
+            probabilities: vec![Probability {
+                likelihood: Likelihood::Likely,
+                conditions: vec![],
+            }],
+            ..Default::default()
+        };
+        let locked = Event {
+            id: Id::new_v4(),
+            name: "Test Event B".into(),
+            locked: true,
+            ..Default::default()
+        };
+        let id = likely.id;
+        let pool = EventPool::new(vec![likely, locked].into());
+
+        let state = State::default();
+        let peeked = pool.peek(&state);
+        assert_eq!(peeked, vec![(id, 0.25)]);
+
+        // Peeking doesn't change anything.
+        assert_eq!(pool.peek(&state), peeked);
+
+        let rolls = 4000;
+        let occurred = (0..rolls)
+            .filter(|seed| {
+                let mut pool = pool.clone();
+                !pool
+                    .roll_with_seed(
+                        Phase::WorldMain,
+                        &state,
+                        *seed as u64,
+                    )
+                    .0
+                    .is_empty()
+            })
+            .count();
+        let share = occurred as f32 / rolls as f32;
+        assert!((share - 0.25).abs() < 0.03);
+    }
+
     #[test]
     fn test_event_pool_no_dupes() {
         fastrand::seed(0);
@@ -498,10 +1029,11 @@ mod test {
                 (Phase::WorldMain, id, None),
                 (Phase::WorldMain, id, None),
             ],
+            max_queued_per_turn: MAX_QUEUED_EVENTS_PER_TURN,
         };
 
         let state = State::default();
-        let events =
+        let (events, _) =
             pool.roll_for_phase(Phase::WorldMain, &state);
 
         // Only 1 event should happen
@@ -509,7 +1041,7 @@ mod test {
 
         // Shouldn't happen again, even though they're pre-triggered
         for _ in 0..4 {
-            let events =
+            let (events, _) =
                 pool.roll_for_phase(Phase::WorldMain, &state);
             assert_eq!(events.len(), 0);
         }
@@ -6,17 +6,27 @@ mod probability;
 mod vars;
 
 pub use self::{
-    condition::{Condition, ConditionKind},
+    condition::{Comparator, Condition, ConditionKind},
     effects::{
         mean_demand_outlook_change,
         mean_income_outlook_change,
         Effect,
+        EffectChange,
+        EffectError,
         EffectKind,
         Flag,
+        MigrationWave,
         RegionFlag,
         Request,
     },
-    events::{Event, EventPool, Phase},
+    events::{
+        ArcId,
+        DeferredEvent,
+        Event,
+        EventPool,
+        EventSeverity,
+        Phase,
+    },
     icons::{IconEvent, ICON_EVENTS},
     probability::{Likelihood, Probability},
     vars::{LocalVariable, PlayerVariable, WorldVariable},
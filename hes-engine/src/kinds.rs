@@ -435,6 +435,14 @@ define_enum_map!(Feedstock {
     Other
 });
 
+impl Feedstock {
+    /// Whether this feedstock is drawn down by extraction.
+    /// Soil and "other" feedstocks are treated as renewable.
+    pub fn is_finite(&self) -> bool {
+        !matches!(self, Feedstock::Soil | Feedstock::Other)
+    }
+}
+
 // Would like to define these as part of the `define_enum_map`
 // macro but it looks like nested macros aren't well supported.
 /// Macro for quickly creating a maps with default values.
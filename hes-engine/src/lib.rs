@@ -1,6 +1,8 @@
 #![feature(generic_arg_infer)]
 
+mod achievements;
 mod diff;
+mod difficulty;
 mod events;
 pub mod flavor;
 mod industries;
@@ -9,19 +11,33 @@ mod npcs;
 mod production;
 mod projects;
 mod regions;
+pub mod schema;
+pub mod sim;
 mod state;
 mod util;
 mod world;
 
+pub use achievements::{
+    Achievement,
+    AchievementId,
+    Achievements,
+};
 pub use diff::{Change, Diff};
+pub use difficulty::Difficulty;
 pub use events::{
     mean_demand_outlook_change,
     mean_income_outlook_change,
+    ArcId,
+    Comparator,
     Condition,
     ConditionKind,
+    DeferredEvent,
     Effect,
+    EffectChange,
+    EffectError,
     EffectKind,
     Event,
+    EventSeverity,
     Flag,
     IconEvent,
     Likelihood,
@@ -35,20 +51,45 @@ pub use events::{
 };
 pub use industries::Industry;
 pub use kinds::*;
-pub use npcs::{NPCRelation, NPC};
-pub use production::{Process, ProcessFeature};
+pub use npcs::{NPCKind, NPCRelation, NPC};
+pub use production::{
+    FeatureModifier,
+    Process,
+    ProcessFeature,
+};
 pub use projects::{
+    points_for_years,
+    years_for_points,
     Cost,
     Factor,
     FactorKind,
     Group,
+    LockReason,
     Outcome,
     Project,
     Status,
     Type as ProjectType,
     Upgrade,
+    MIN_COST_MODIFIER,
+};
+pub use regions::{
+    HabitabilityFactor,
+    Income,
+    Latitude,
+    Region,
+};
+pub use state::{
+    ContentError,
+    Emissions,
+    MixError,
+    ResolvedEvent,
+    State,
+    StateSnapshot,
+    StepProgress,
+    Update,
+    CH4_GWP,
+    N2O_GWP,
+    WARMING_PER_GT,
 };
-pub use regions::{Income, Latitude, Region};
-pub use state::{Emissions, ResolvedEvent, State, Update};
 pub use util::*;
 pub use world::World;
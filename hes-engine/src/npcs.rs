@@ -12,6 +12,105 @@ use strum::{Display, EnumIter, EnumString, IntoStaticStr};
 
 pub const RELATIONSHIP_CHANGE_AMOUNT: f32 = 0.5;
 
+/// At or above this relationship an NPC is an ally.
+pub const ALLY_THRESHOLD: f32 = 5.;
+
+/// At or above this relationship an NPC is friendly
+/// (but still counts as neutral).
+pub const FRIENDLY_THRESHOLD: f32 = 4.;
+
+/// At or below this relationship an NPC is a nemesis.
+pub const NEMESIS_THRESHOLD: f32 = 1.;
+
+/// Project cost reduction for each ally supporting it.
+pub const ALLY_SUPPORT_DISCOUNT: f32 = 0.1;
+
+/// Stable identifiers for the hardcoded NPCs (see `NPC::load`),
+/// so they can be looked up without relying on their names,
+/// which may be localized or edited.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+    Display,
+)]
+pub enum NPCKind {
+    Malthusian,
+    Utopian,
+    Consumerist,
+    Posadist,
+    Fanonist,
+    Ecofeminist,
+    Authoritarian,
+    Accelerationist,
+    Environmentalist,
+    AnimalLiberationist,
+}
+
+impl NPCKind {
+    pub fn id(&self) -> Id {
+        match self {
+            NPCKind::Malthusian => {
+                uuid::uuid!(
+                    "04b92453-026e-40a5-9a25-e98244023a69"
+                )
+            }
+            NPCKind::Utopian => {
+                uuid::uuid!(
+                    "cf5a64e6-e61d-405a-9e37-634c45aa1563"
+                )
+            }
+            NPCKind::Consumerist => {
+                uuid::uuid!(
+                    "f952fd1f-6bf3-418f-ac02-0d6ff87955ca"
+                )
+            }
+            NPCKind::Posadist => {
+                uuid::uuid!(
+                    "53fe6eb5-e144-4cc1-816f-21bf46d4f27e"
+                )
+            }
+            NPCKind::Fanonist => {
+                uuid::uuid!(
+                    "655dd0c3-3542-4524-98bb-97d61a318669"
+                )
+            }
+            NPCKind::Ecofeminist => {
+                uuid::uuid!(
+                    "5a59715a-3deb-4288-9aa6-14859ad54d5a"
+                )
+            }
+            NPCKind::Authoritarian => {
+                uuid::uuid!(
+                    "b6694f1d-5597-4bb8-a484-254930838982"
+                )
+            }
+            NPCKind::Accelerationist => {
+                uuid::uuid!(
+                    "eb491cfc-01fa-4af4-a560-49ad55975868"
+                )
+            }
+            NPCKind::Environmentalist => {
+                uuid::uuid!(
+                    "ccca3d9d-2d69-4c98-aec4-29df691c0c4a"
+                )
+            }
+            NPCKind::AnimalLiberationist => {
+                uuid::uuid!(
+                    "5cd771c9-2622-4bd9-8478-c2cbf2294080"
+                )
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NPC {
     pub id: Id,
@@ -53,27 +152,66 @@ impl NPC {
         self.relation() == NPCRelation::Ally
     }
 
+    /// The relationship values at which an NPC becomes a
+    /// nemesis (at or below) and an ally (at or above).
+    pub fn relation_thresholds() -> (f32, f32) {
+        (NEMESIS_THRESHOLD, ALLY_THRESHOLD)
+    }
+
     pub fn relation(&self) -> NPCRelation {
-        if self.relationship >= 5. {
-            NPCRelation::Ally
-        } else if self.relationship <= 1. {
-            NPCRelation::Nemesis
-        } else {
-            NPCRelation::Neutral
-        }
+        relation_for(self.relationship)
     }
 
     pub fn relationship_name(&self) -> &'static str {
-        if self.relationship >= 5. {
+        if self.relationship >= ALLY_THRESHOLD {
             "Ally"
-        } else if self.relationship >= 4. {
+        } else if self.relationship >= FRIENDLY_THRESHOLD {
             "Friendly"
-        } else if self.relationship <= 1. {
+        } else if self.relationship <= NEMESIS_THRESHOLD {
             "Nemesis"
         } else {
             "Neutral"
         }
     }
+
+    /// Check if the relationship crossed a threshold since
+    /// it was `prev_relationship`, returning the new relation if so.
+    ///
+    /// Note that a change into or out of `Ally` affects
+    /// projects' required majorities, so those
+    /// should be updated (see `Project::update_required_majority`).
+    pub fn on_relationship_changed(
+        &self,
+        prev_relationship: f32,
+    ) -> Option<NPCRelation> {
+        let relation = self.relation();
+        if relation != relation_for(prev_relationship) {
+            Some(relation)
+        } else {
+            None
+        }
+    }
+
+    /// Change the relationship, returning the new relation
+    /// if it crossed a threshold.
+    pub fn change_relationship(
+        &mut self,
+        change: f32,
+    ) -> Option<NPCRelation> {
+        let prev = self.relationship;
+        self.relationship += change;
+        self.on_relationship_changed(prev)
+    }
+}
+
+fn relation_for(relationship: f32) -> NPCRelation {
+    if relationship >= ALLY_THRESHOLD {
+        NPCRelation::Ally
+    } else if relationship <= NEMESIS_THRESHOLD {
+        NPCRelation::Nemesis
+    } else {
+        NPCRelation::Neutral
+    }
 }
 
 #[derive(
@@ -99,13 +237,9 @@ impl Collection<NPC> {
         self.iter().filter(|npc| !npc.locked)
     }
 
-    pub fn is_ally(&self, name: &'static str) -> bool {
-        let npc = self.iter().find(|n| n.name == name);
-        if let Some(npc) = npc {
-            npc.is_ally()
-        } else {
-            false
-        }
+    pub fn is_ally(&self, kind: NPCKind) -> bool {
+        self.try_get(&kind.id())
+            .is_some_and(|npc| npc.is_ally())
     }
 
     pub fn update_seats(
@@ -159,3 +293,113 @@ impl Collection<NPC> {
             .sum()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_npc_kinds() {
+        let mut npcs = NPC::load();
+        assert_eq!(NPCKind::iter().count(), npcs.len());
+        for kind in NPCKind::iter() {
+            let npc = npcs.try_get(&kind.id()).unwrap();
+            assert_eq!(
+                npc.name.replace(' ', ""),
+                format!("The{kind}")
+            );
+        }
+
+        // Lookups don't depend on names.
+        for npc in npcs.iter_mut() {
+            npc.name = npc.name.to_uppercase();
+        }
+        let id = NPCKind::Fanonist.id();
+        npcs[&id].relationship = ALLY_THRESHOLD;
+        assert!(npcs.is_ally(NPCKind::Fanonist));
+        assert!(!npcs.is_ally(NPCKind::Consumerist));
+    }
+
+    fn npc_with(relationship: f32) -> NPC {
+        let mut npc = NPC::load().by_idx(0).clone();
+        npc.relationship = relationship;
+        npc
+    }
+
+    #[test]
+    fn test_relation_thresholds() {
+        let (nemesis, ally) = NPC::relation_thresholds();
+        assert_eq!(
+            npc_with(ally).relation(),
+            NPCRelation::Ally
+        );
+        assert_eq!(
+            npc_with(ally - 0.1).relation(),
+            NPCRelation::Neutral
+        );
+        assert_eq!(
+            npc_with(nemesis).relation(),
+            NPCRelation::Nemesis
+        );
+        assert_eq!(
+            npc_with(nemesis + 0.1).relation(),
+            NPCRelation::Neutral
+        );
+    }
+
+    #[test]
+    fn test_relationship_transitions() {
+        let mut npc = npc_with(ALLY_THRESHOLD - 0.5);
+        assert_eq!(npc.change_relationship(0.25), None);
+        assert_eq!(
+            npc.change_relationship(0.25),
+            Some(NPCRelation::Ally)
+        );
+        assert_eq!(npc.change_relationship(0.5), None);
+        assert_eq!(npc.change_relationship(-0.5), None);
+        assert_eq!(
+            npc.change_relationship(-0.5),
+            Some(NPCRelation::Neutral)
+        );
+
+        let mut npc = npc_with(NEMESIS_THRESHOLD + 0.5);
+        assert_eq!(
+            npc.change_relationship(-0.5),
+            Some(NPCRelation::Nemesis)
+        );
+        assert_eq!(npc.change_relationship(-0.5), None);
+        assert_eq!(npc.change_relationship(0.5), None);
+        assert_eq!(
+            npc.change_relationship(0.5),
+            Some(NPCRelation::Neutral)
+        );
+
+        // Jumping straight from nemesis to ally.
+        let mut npc = npc_with(NEMESIS_THRESHOLD);
+        assert_eq!(
+            npc.change_relationship(ALLY_THRESHOLD),
+            Some(NPCRelation::Ally)
+        );
+    }
+
+    #[test]
+    fn test_ally_transition_required_majority() {
+        let mut npcs = NPC::load();
+        let id = npcs.by_idx(0).id;
+        npcs[&id].locked = false;
+        npcs[&id].relationship = ALLY_THRESHOLD - 0.5;
+
+        let mut project = Project::new();
+        project.opposers = vec![id];
+        project.update_required_majority(&npcs);
+        assert_eq!(project.required_majority, 0.5);
+
+        // Allies don't count as opposition.
+        let relation = npcs[&id]
+            .change_relationship(RELATIONSHIP_CHANGE_AMOUNT);
+        assert_eq!(relation, Some(NPCRelation::Ally));
+        project.update_required_majority(&npcs);
+        assert_eq!(project.required_majority, 0.);
+    }
+}
@@ -6,7 +6,12 @@ use std::collections::BTreeMap;
 
 pub use self::{
     planner::{calculate_required, ProductionOrder},
-    processes::{Process, ProcessChanges, ProcessFeature},
+    processes::{
+        FeatureModifier,
+        Process,
+        ProcessChanges,
+        ProcessFeature,
+    },
 };
 use crate::{
     kinds::{
@@ -18,6 +23,108 @@ use crate::{
     Id,
 };
 
+/// How much more a process could produce, given
+/// what it's already producing, its limit (if any),
+/// and the remaining supply of its feedstock.
+fn spare_capacity(
+    process: &Process,
+    amount: f32,
+    feedstocks: &FeedstockMap,
+) -> f32 {
+    let mut spare = match process.limit {
+        Some(limit) => limit - amount,
+        None => f32::INFINITY,
+    };
+    let (feedstock, _) = process.feedstock;
+    let per_output = process.adj_feedstock_amount();
+    if feedstock.is_finite() && per_output > 0. {
+        spare = spare.min(feedstocks[feedstock] / per_output);
+    }
+    spare.max(0.)
+}
+
+/// Cap orders at their process's limit and the available
+/// feedstock, then shift any shortfall to the other processes
+/// for the same output which have spare capacity.
+///
+/// The shortfall is picked up by processes in order of
+/// their emissions (CO2eq) per unit of output, cleanest first.
+/// Only processes already in the mix pick up extra demand,
+/// so banned processes stay banned.
+pub fn redistribute_orders(
+    orders: &mut [ProductionOrder],
+    demand: &OutputMap,
+    feedstocks: &FeedstockMap,
+) {
+    let mut feedstocks = *feedstocks;
+    let mut shortfall = OutputMap::default();
+    for order in orders.iter_mut() {
+        let process = order.process;
+        let wanted =
+            demand[process.output] * process.mix_percent();
+        order.amount = wanted.min(spare_capacity(
+            process,
+            0.,
+            &feedstocks,
+        ));
+        feedstocks[process.feedstock.0] -=
+            process.adj_feedstock_amount() * order.amount;
+        shortfall[process.output] += wanted - order.amount;
+    }
+
+    let mut idxs: Vec<usize> = (0..orders.len())
+        .filter(|i| orders[*i].process.mix_share > 0)
+        .collect();
+    idxs.sort_by(|a, b| {
+        let a = orders[*a].process.adj_byproducts().co2eq();
+        let b = orders[*b].process.adj_byproducts().co2eq();
+        a.total_cmp(&b)
+    });
+    for i in idxs {
+        let order = &mut orders[i];
+        let process = order.process;
+        let remaining = shortfall[process.output];
+        if remaining <= 0. {
+            continue;
+        }
+        let extra = remaining.min(spare_capacity(
+            process,
+            order.amount,
+            &feedstocks,
+        ));
+        order.amount += extra;
+        feedstocks[process.feedstock.0] -=
+            process.adj_feedstock_amount() * extra;
+        shortfall[process.output] -= extra;
+    }
+}
+
+/// Scale down orders so that the combined output of
+/// the processes sharing a capped feature doesn't exceed its cap.
+/// Each affected order is scaled proportionally.
+pub fn apply_feature_caps(
+    orders: &mut [ProductionOrder],
+    caps: &BTreeMap<ProcessFeature, f32>,
+) {
+    for (feat, cap) in caps {
+        let total: f32 = orders
+            .iter()
+            .filter(|order| {
+                order.process.features.contains(feat)
+            })
+            .map(|order| order.amount)
+            .sum();
+        if total > *cap {
+            let scale = cap.max(0.) / total;
+            for order in orders.iter_mut().filter(|order| {
+                order.process.features.contains(feat)
+            }) {
+                order.amount *= scale;
+            }
+        }
+    }
+}
+
 pub fn produce(
     orders: &[ProductionOrder],
     resources: &ResourceMap,
@@ -57,3 +164,98 @@ pub fn produce(
         byproducts,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        feedstocks,
+        kinds::{Feedstock, Output},
+        outputs,
+    };
+
+    fn process(mix_share: usize, co2: f32) -> Process {
+        let mut process = Process::new();
+        process.output = Output::Electricity;
+        process.mix_share = mix_share;
+        process.byproducts.co2 = co2;
+        process.feedstock = (Feedstock::Other, 0.);
+        process
+    }
+
+    fn amounts(orders: &[ProductionOrder]) -> Vec<f32> {
+        orders.iter().map(|order| order.amount).collect()
+    }
+
+    #[test]
+    fn test_redistribute_limit() {
+        let mut capped = process(10, 0.);
+        capped.limit = Some(10.);
+        let dirty = process(5, 100.);
+        let clean = process(5, 1.);
+        let banned = process(0, 0.);
+        let processes = [capped, dirty, clean, banned];
+
+        let demand = outputs!(electricity: 100.);
+        let mut orders: Vec<_> = processes
+            .iter()
+            .map(|p| p.production_order(&demand))
+            .collect();
+        redistribute_orders(
+            &mut orders,
+            &demand,
+            &feedstocks!(),
+        );
+
+        // The capped process's shortfall all goes
+        // to the cleanest process still in the mix.
+        assert_eq!(amounts(&orders), vec![10., 25., 65., 0.]);
+    }
+
+    #[test]
+    fn test_redistribute_feedstock() {
+        let mut oil = process(10, 50.);
+        oil.feedstock = (Feedstock::Oil, 1.);
+        let mut clean = process(5, 1.);
+        clean.limit = Some(40.);
+        let dirty = process(5, 100.);
+        let processes = [oil, clean, dirty];
+
+        let demand = outputs!(electricity: 100.);
+        let feedstocks = feedstocks!(oil: 30.);
+        let mut orders: Vec<_> = processes
+            .iter()
+            .map(|p| p.production_order(&demand))
+            .collect();
+        redistribute_orders(&mut orders, &demand, &feedstocks);
+
+        // The oil process runs out of oil, the clean one
+        // fills up to its limit, and the rest goes to the dirty one.
+        assert_eq!(amounts(&orders), vec![30., 40., 30.]);
+        let total: f32 = amounts(&orders).iter().sum();
+        assert_eq!(total, demand.electricity);
+    }
+
+    #[test]
+    fn test_redistribute_unmet() {
+        let mut a = process(10, 0.);
+        a.limit = Some(20.);
+        let mut b = process(10, 0.);
+        b.limit = Some(30.);
+        let processes = [a, b];
+
+        let demand = outputs!(electricity: 100.);
+        let mut orders: Vec<_> = processes
+            .iter()
+            .map(|p| p.production_order(&demand))
+            .collect();
+        redistribute_orders(
+            &mut orders,
+            &demand,
+            &feedstocks!(),
+        );
+
+        // Everything is capped, so demand goes unmet.
+        assert_eq!(amounts(&orders), vec![20., 30.]);
+    }
+}
@@ -23,6 +23,9 @@ use strum::{Display, EnumIter, EnumString, IntoStaticStr};
     Copy,
     Clone,
     PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
     Serialize,
     Deserialize,
     IntoStaticStr,
@@ -45,6 +48,62 @@ pub enum ProcessFeature {
     IsLaborIntensive,
 }
 
+/// A process modifier which feature effects contribute to.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub enum FeatureModifier {
+    Output,
+    CO2,
+}
+
+/// Pairs of features which can't
+/// both describe the same process.
+const FEATURE_CONFLICTS: &[(ProcessFeature, ProcessFeature)] =
+    &[
+        (
+            ProcessFeature::MakesNuclearWaste,
+            ProcessFeature::IsFossil,
+        ),
+        (
+            ProcessFeature::MakesNuclearWaste,
+            ProcessFeature::IsCombustion,
+        ),
+        (
+            ProcessFeature::MakesNuclearWaste,
+            ProcessFeature::IsSolar,
+        ),
+        (ProcessFeature::CanMeltdown, ProcessFeature::IsFossil),
+        (
+            ProcessFeature::CanMeltdown,
+            ProcessFeature::IsCombustion,
+        ),
+        (ProcessFeature::CanMeltdown, ProcessFeature::IsSolar),
+        (ProcessFeature::IsSolar, ProcessFeature::IsFossil),
+    ];
+
+impl ProcessFeature {
+    /// Whether this feature can't be
+    /// combined with the other on one process.
+    pub fn conflicts_with(
+        &self,
+        other: &ProcessFeature,
+    ) -> bool {
+        FEATURE_CONFLICTS.iter().any(|(a, b)| {
+            (a == self && b == other)
+                || (a == other && b == self)
+        })
+    }
+}
+
 #[derive(
     Debug, Clone, Serialize, Deserialize, PartialEq, Default,
 )]
@@ -64,6 +123,14 @@ pub struct Process {
 
     pub features: Vec<ProcessFeature>,
 
+    /// Feature modifier contributions made by sourced effects,
+    /// as (source id, modifier, feature, amount), so that a
+    /// source can't stack an effect's contribution more than
+    /// once. See `Effect::apply_sourced`.
+    #[serde(default)]
+    pub sourced_modifiers:
+        Vec<(Id, FeatureModifier, ProcessFeature, f32)>,
+
     // If the player has unlocked this process.
     pub locked: bool,
 
@@ -116,6 +183,34 @@ impl Process {
         }
     }
 
+    /// The first pair of this process' features
+    /// which conflict with each other, if any.
+    pub fn feature_conflict(
+        &self,
+    ) -> Option<(ProcessFeature, ProcessFeature)> {
+        self.features.iter().enumerate().find_map(|(i, a)| {
+            self.features[i + 1..]
+                .iter()
+                .find(|b| a.conflicts_with(b))
+                .map(|b| (*a, *b))
+        })
+    }
+
+    pub fn add_feature_modifier(
+        &mut self,
+        modifier: FeatureModifier,
+        amount: f32,
+    ) {
+        match modifier {
+            FeatureModifier::Output => {
+                self.output_modifier += amount
+            }
+            FeatureModifier::CO2 => {
+                self.byproduct_modifiers.co2 += amount
+            }
+        }
+    }
+
     pub fn mix_percent(&self) -> f32 {
         return self.mix_share as f32 * 0.05;
     }
@@ -310,4 +405,25 @@ mod test {
         let order = p.production_order(&demand);
         assert_eq!(order.amount, 100.);
     }
+
+    #[test]
+    fn test_feature_conflicts() {
+        use ProcessFeature::*;
+        assert!(MakesNuclearWaste.conflicts_with(&IsFossil));
+        assert!(IsFossil.conflicts_with(&MakesNuclearWaste));
+        assert!(!IsFossil.conflicts_with(&IsCombustion));
+        assert!(!IsSolar.conflicts_with(&IsSolar));
+
+        let mut process = Process {
+            features: vec![IsCombustion, IsFossil, UsesOil],
+            ..Default::default()
+        };
+        assert_eq!(process.feature_conflict(), None);
+
+        process.features.push(CanMeltdown);
+        assert_eq!(
+            process.feature_conflict(),
+            Some((IsCombustion, CanMeltdown))
+        );
+    }
 }
@@ -3,6 +3,7 @@ use crate::{
     flavor::ProjectFlavor,
     kinds::{Output, OutputMap},
     npcs::{NPCRelation, NPC, RELATIONSHIP_CHANGE_AMOUNT},
+    state::State,
     Collection,
     HasId,
     Id,
@@ -17,6 +18,17 @@ use strum::{
     IntoStaticStr,
 };
 
+/// The lowest multiplier a project's cost can be scaled by,
+/// so that accumulated cost reductions can't make it free.
+pub const MIN_COST_MODIFIER: f32 = 0.05;
+
+/// The id of the solar radiation management project,
+/// i.e. the one subject to termination shock.
+/// Ids are stable across edits to a project's
+/// name or description, unlike its (English) name.
+pub const SRM_PROJECT_ID: Id =
+    uuid::uuid!("4d39c88b-08e1-4f96-a0ea-acc9b244c279");
+
 /// The project's status.
 #[derive(
     Display,
@@ -130,6 +142,7 @@ pub enum Factor {
     Time,
     Income,
     Output(Output),
+    Population,
 }
 
 impl From<FactorKind> for Factor {
@@ -140,6 +153,7 @@ impl From<FactorKind> for Factor {
             FactorKind::Output => {
                 Factor::Output(Output::default())
             }
+            FactorKind::Population => Factor::Population,
         }
     }
 }
@@ -190,15 +204,48 @@ pub struct Project {
     pub effects: Vec<Effect>,
     pub outcomes: Vec<Outcome>,
     pub upgrades: Vec<Upgrade>,
-    pub active_outcome: Option<usize>,
+
+    /// The outcomes which resolved for this project.
+    /// Older saves only had a single `active_outcome`.
+    #[serde(
+        default,
+        alias = "active_outcome",
+        deserialize_with = "deserialize_outcomes"
+    )]
+    pub active_outcomes: Vec<usize>,
 
     pub supporters: Vec<Id>,
     pub opposers: Vec<Id>,
 
+    /// Projects which must be completed
+    /// before this one is unlocked.
+    #[serde(default)]
+    pub requires: Vec<Id>,
+
     pub flavor: ProjectFlavor,
     pub notes: String,
 }
 
+/// Accepts either a list of outcomes or the
+/// legacy single optional outcome.
+fn deserialize_outcomes<'de, D>(
+    deserializer: D,
+) -> Result<Vec<usize>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Outcomes {
+        Many(Vec<usize>),
+        One(Option<usize>),
+    }
+    Ok(match Outcomes::deserialize(deserializer)? {
+        Outcomes::Many(ids) => ids,
+        Outcomes::One(id) => id.into_iter().collect(),
+    })
+}
+
 impl Display for Project {
     fn fmt(
         &self,
@@ -223,6 +270,29 @@ pub fn years_for_points(points: usize, cost: usize) -> f32 {
         .max(1.)
 }
 
+/// The fewest points needed for a project
+/// to complete within the given number of years.
+/// The inverse of `years_for_points`.
+/// Has to be at least 1
+pub fn points_for_years(years: usize, cost: usize) -> usize {
+    let years = years.max(1);
+    let mut points =
+        (cost as f32 / years as f32).powf(2.75).floor().max(1.)
+            as usize;
+
+    // Points are whole numbers and `years_for_points`
+    // rounds, so adjust to the fewest sufficient points.
+    while years_for_points(points, cost) > years as f32 {
+        points += 1;
+    }
+    while points > 1
+        && years_for_points(points - 1, cost) <= years as f32
+    {
+        points -= 1;
+    }
+    points
+}
+
 impl Project {
     pub fn new() -> Project {
         Project {
@@ -255,24 +325,58 @@ impl Project {
         self.status == Status::Building
     }
 
+    /// Whether this is the solar radiation management project,
+    /// i.e. the one subject to termination shock.
+    pub fn is_srm(&self) -> bool {
+        self.id == SRM_PROJECT_ID
+    }
+
     pub fn is_haltable(&self) -> bool {
         self.is_online()
             && (self.kind == Type::Policy || self.ongoing)
     }
 
-    pub fn can_upgrade(&self) -> bool {
-        self.next_upgrade().is_some()
+    /// Whether all of this project's prerequisites
+    /// are among the `completed` projects.
+    pub fn is_unlockable(&self, completed: &[Id]) -> bool {
+        self.requires.iter().all(|id| completed.contains(id))
+    }
+
+    /// Whether this project has another upgrade
+    /// and it's affordable with the available political capital.
+    pub fn can_upgrade(&self, available_pc: isize) -> bool {
+        self.next_upgrade().is_some_and(|upgrade| {
+            upgrade.cost as isize <= available_pc
+        })
     }
 
     pub fn can_downgrade(&self) -> bool {
         self.kind == Type::Policy && self.level > 0
     }
 
-    pub fn years_remaining(&self) -> usize {
-        let remaining = 1. - self.progress;
-        let progress_per_year =
-            1. / years_for_points(self.points, self.cost);
-        (remaining / progress_per_year).round() as usize
+    /// The whole years left until this project is completed,
+    /// accounting for progress made so far.
+    /// Partial years count as a full year, so projects being
+    /// built always have at least 1 year left.
+    /// Projects which aren't being built (e.g. stalled or halted)
+    /// report their full estimate, and online projects report 0.
+    pub fn estimate_remaining(&self) -> usize {
+        match self.status {
+            Status::Building => {
+                let remaining = 1. - self.progress;
+                let progress_per_year = 1.
+                    / years_for_points(self.points, self.cost);
+                // Progress accumulates in floats, so allow a little slack
+                // before rounding up to the next year.
+                let years =
+                    remaining / progress_per_year - 1e-3;
+                years.ceil().max(1.) as usize
+            }
+            Status::Active | Status::Finished => 0,
+            Status::Inactive
+            | Status::Halted
+            | Status::Stalled => self.estimate,
+        }
     }
 
     /// Advance this project's implementation
@@ -302,7 +406,10 @@ impl Project {
     }
 
     pub fn stop(&mut self) -> (ProjectChanges, bool) {
-        let mut changes = ProjectChanges::default();
+        let mut changes = ProjectChanges {
+            source: self.id,
+            ..Default::default()
+        };
 
         if self.status == Status::Active
             || self.status == Status::Finished
@@ -311,9 +418,9 @@ impl Project {
                 .remove_effects
                 .extend(self.active_effects().clone());
 
-            if let Some(outcome_id) = self.active_outcome {
+            for outcome_id in &self.active_outcomes {
                 let effects =
-                    &self.outcomes[outcome_id].effects;
+                    &self.outcomes[*outcome_id].effects;
                 changes.remove_effects.extend(effects.clone());
             }
 
@@ -352,6 +459,7 @@ impl Project {
         &mut self,
         year: usize,
         income_level: f32,
+        population: f32,
         demand: &OutputMap,
         modifier: f32,
     ) {
@@ -365,17 +473,23 @@ impl Project {
                     Factor::Output(output) => {
                         m * demand[output]
                     }
+                    Factor::Population => m * population,
                 };
                 c.round() as usize
             }
         };
-        self.cost =
-            (cost as f32 * self.cost_modifier * modifier)
-                .round() as usize;
+        // Clamp the combined modifier so that stacked
+        // reductions can't bring the cost to zero or below.
+        let modifier = (self.cost_modifier * modifier)
+            .max(MIN_COST_MODIFIER);
+        self.cost = (cost as f32 * modifier).round() as usize;
     }
 
     pub fn upgrade(&mut self) -> ProjectChanges {
-        let mut changes = ProjectChanges::default();
+        let mut changes = ProjectChanges {
+            source: self.id,
+            ..Default::default()
+        };
 
         // Upgrade effects replace the previous effects.
         changes
@@ -399,7 +513,10 @@ impl Project {
     }
 
     pub fn downgrade(&mut self) -> ProjectChanges {
-        let mut changes = ProjectChanges::default();
+        let mut changes = ProjectChanges {
+            source: self.id,
+            ..Default::default()
+        };
         changes
             .remove_effects
             .extend(self.active_effects().clone());
@@ -434,8 +551,18 @@ impl Project {
         }
     }
 
+    /// The political capital refunded when downgrading,
+    /// i.e. the cost of the upgrade being undone.
+    /// Projects at their base level have nothing to refund.
+    pub fn refund_for_downgrade(&self) -> usize {
+        self.prev_upgrade().map_or(0, |upgrade| upgrade.cost)
+    }
+
     pub fn advance(&mut self, year: usize) -> ProjectChanges {
-        let mut changes = ProjectChanges::default();
+        let mut changes = ProjectChanges {
+            source: self.id,
+            ..Default::default()
+        };
 
         // For gradual projects, we apply
         // interpolated effects.
@@ -487,18 +614,65 @@ impl Project {
         }
     }
 
+    /// How much of this project's active effects are in place.
+    /// Gradual projects ramp their effects in as they're built,
+    /// so callers should scale each of `active_effects()` by this,
+    /// e.g. `effect.clone() * project.active_effect_factor()`.
+    /// Non-gradual projects always apply their effects in full.
+    pub fn active_effect_factor(&self) -> f32 {
+        if self.gradual {
+            self.progress
+        } else {
+            1.
+        }
+    }
+
     pub fn active_effects_with_outcomes(&self) -> Vec<&Effect> {
         let mut effects = vec![];
         if self.is_online() {
             effects.extend(self.active_effects().iter());
-            if let Some(id) = self.active_outcome {
+            for id in &self.active_outcomes {
                 effects
-                    .extend(self.outcomes[id].effects.iter());
+                    .extend(self.outcomes[*id].effects.iter());
             }
         }
         effects
     }
 
+    /// Roll for this project's outcome.
+    /// Outcomes whose conditions are met are sampled
+    /// weighted by their likelihoods; if none are eligible
+    /// this falls back to the first outcome.
+    pub fn roll_outcome(
+        &self,
+        state: &State,
+        rng: &mut fastrand::Rng,
+    ) -> Option<(&Outcome, usize)> {
+        let eligible: Vec<(usize, f32)> = self
+            .outcomes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, o)| {
+                o.probability
+                    .eval(state, None)
+                    .map(|likelihood| (i, likelihood.p()))
+            })
+            .filter(|(_, p)| *p > 0.)
+            .collect();
+
+        let total: f32 = eligible.iter().map(|(_, p)| p).sum();
+        let mut roll = rng.f32() * total;
+        let idx = eligible
+            .iter()
+            .find(|(_, p)| {
+                roll -= p;
+                roll < 0.
+            })
+            .or(eligible.last())
+            .map_or(0, |(i, _)| *i);
+        self.outcomes.get(idx).map(|o| (o, idx))
+    }
+
     pub fn update_required_majority(
         &mut self,
         npcs: &Collection<NPC>,
@@ -521,8 +695,24 @@ impl Project {
     }
 }
 
+/// Why a project is locked, so players
+/// can be told what they need to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockReason {
+    /// These prerequisite projects need to be completed first.
+    Prerequisite(Vec<Id>),
+
+    /// Only unlocked by an event or another effect.
+    EventGated,
+
+    /// Locked by this active project.
+    PolicyConflict(Id),
+}
+
 #[derive(Default, Debug)]
 pub struct ProjectChanges {
+    /// The project the changes are from.
+    pub source: Id,
     pub completed: bool,
     pub remove_effects: Vec<Effect>,
     pub add_effects: Vec<Effect>,
@@ -550,6 +740,48 @@ impl Collection<Project> {
         self.iter().filter(|p| !p.locked)
     }
 
+    pub fn active(&self) -> impl Iterator<Item = &Project> {
+        self.filter_by(|p| p.is_active())
+    }
+
+    pub fn in_group(
+        &self,
+        group: Group,
+    ) -> impl Iterator<Item = &Project> {
+        self.filter_by(move |p| p.group == group)
+    }
+
+    pub fn of_kind(
+        &self,
+        kind: Type,
+    ) -> impl Iterator<Item = &Project> {
+        self.filter_by(move |p| p.kind == kind)
+    }
+
+    /// Unlock any locked projects with prerequisites
+    /// which have all been completed, returning their ids.
+    /// Projects without prerequisites are left alone,
+    /// as they're only unlocked through effects.
+    pub fn unlock_dependents(&mut self) -> Vec<Id> {
+        let completed: Vec<Id> = self
+            .iter()
+            .filter(|p| p.is_online())
+            .map(|p| p.id)
+            .collect();
+
+        let mut unlocked = vec![];
+        for project in self.iter_mut() {
+            if project.locked
+                && !project.requires.is_empty()
+                && project.is_unlockable(&completed)
+            {
+                project.locked = false;
+                unlocked.push(project.id);
+            }
+        }
+        unlocked
+    }
+
     pub fn recent(
         &self,
         year: usize,
@@ -583,7 +815,13 @@ impl Collection<Project> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::events::Likelihood;
+    use crate::events::{
+        Comparator,
+        Condition,
+        Likelihood,
+        WorldVariable,
+    };
+    use serde_json::json;
 
     #[test]
     fn test_build_project() {
@@ -620,6 +858,359 @@ mod test {
         assert_eq!(p.status, Status::Active);
     }
 
+    #[test]
+    fn test_collection_queries() {
+        let project = |group, kind, status| Project {
+            id: Id::new_v4(),
+            group,
+            kind,
+            status,
+            ..Default::default()
+        };
+        let projects: Collection<Project> = vec![
+            project(
+                Group::Energy,
+                Type::Policy,
+                Status::Active,
+            ),
+            project(
+                Group::Energy,
+                Type::Research,
+                Status::Finished,
+            ),
+            project(
+                Group::Energy,
+                Type::Initiative,
+                Status::Inactive,
+            ),
+            project(Group::Space, Type::Policy, Status::Active),
+            project(
+                Group::Space,
+                Type::Initiative,
+                Status::Building,
+            ),
+        ]
+        .into();
+
+        let ids = |iter: &mut dyn Iterator<Item = &Project>| {
+            iter.map(|p| p.id).collect::<Vec<_>>()
+        };
+        let nth = |idx: usize| projects.by_idx(idx).id;
+
+        assert_eq!(
+            ids(&mut projects.active()),
+            vec![nth(0), nth(3)]
+        );
+        assert_eq!(
+            ids(&mut projects.in_group(Group::Energy)),
+            vec![nth(0), nth(1), nth(2)]
+        );
+        assert_eq!(
+            ids(&mut projects
+                .in_group(Group::Energy)
+                .filter(|p| p.is_active())),
+            vec![nth(0)]
+        );
+        assert_eq!(
+            ids(&mut projects.of_kind(Type::Initiative)),
+            vec![nth(2), nth(4)]
+        );
+        assert_eq!(projects.in_group(Group::Cities).count(), 0);
+        assert_eq!(
+            ids(&mut projects.filter_by(|p| p.is_building())),
+            vec![nth(4)]
+        );
+    }
+
+    #[test]
+    fn test_points_for_years() {
+        for cost in [1, 5, 10, 30, 60] {
+            for years in 1..=cost {
+                let points = points_for_years(years, cost);
+                let estimate = years_for_points(points, cost);
+                assert!(estimate <= years as f32);
+                if points > 1 {
+                    let estimate =
+                        years_for_points(points - 1, cost);
+                    assert!(estimate > years as f32);
+                }
+            }
+
+            // Round-trips for any reachable number of years.
+            for points in 1..=20 {
+                let years = years_for_points(points, cost);
+                let pts =
+                    points_for_years(years as usize, cost);
+                assert_eq!(years_for_points(pts, cost), years);
+            }
+        }
+        assert_eq!(points_for_years(100, 10), 1);
+        assert_eq!(
+            points_for_years(0, 10),
+            points_for_years(1, 10)
+        );
+    }
+
+    fn dependent(requires: Vec<Id>) -> Project {
+        Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            locked: true,
+            requires,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_project_chain() {
+        let a = dependent(vec![]);
+        let b = dependent(vec![a.id]);
+        let c = dependent(vec![b.id]);
+        let (a_id, b_id, c_id) = (a.id, b.id, c.id);
+
+        let mut projects: Collection<Project> =
+            vec![a, b, c].into();
+        assert!(projects[&a_id].is_unlockable(&[]));
+        assert!(!projects[&b_id].is_unlockable(&[]));
+        assert!(projects.unlock_dependents().is_empty());
+
+        projects[&a_id].status = Status::Finished;
+        assert_eq!(projects.unlock_dependents(), vec![b_id]);
+        assert!(!projects[&b_id].locked);
+        assert!(projects[&c_id].locked);
+
+        // Ongoing projects count once they're active.
+        projects[&b_id].status = Status::Active;
+        assert_eq!(projects.unlock_dependents(), vec![c_id]);
+        assert!(!projects[&c_id].locked);
+
+        // Projects without prerequisites stay locked.
+        assert!(projects[&a_id].locked);
+    }
+
+    #[test]
+    fn test_project_diamond() {
+        let a = dependent(vec![]);
+        let b = dependent(vec![a.id]);
+        let c = dependent(vec![a.id]);
+        let d = dependent(vec![b.id, c.id]);
+        let (a_id, b_id, c_id, d_id) = (a.id, b.id, c.id, d.id);
+
+        let mut projects: Collection<Project> =
+            vec![a, b, c, d].into();
+        projects[&a_id].status = Status::Finished;
+        let mut unlocked = projects.unlock_dependents();
+        unlocked.sort();
+        let mut expected = vec![b_id, c_id];
+        expected.sort();
+        assert_eq!(unlocked, expected);
+
+        // Only one branch finished.
+        projects[&b_id].status = Status::Finished;
+        assert!(projects.unlock_dependents().is_empty());
+        assert!(projects[&d_id].locked);
+
+        projects[&c_id].status = Status::Finished;
+        assert_eq!(projects.unlock_dependents(), vec![d_id]);
+        assert!(!projects[&d_id].locked);
+    }
+
+    #[test]
+    fn test_active_effect_factor() {
+        let mut p = Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            status: Status::Building,
+            progress: 0.5,
+            gradual: true,
+            ..Default::default()
+        };
+        assert_eq!(p.active_effect_factor(), 0.5);
+
+        p.gradual = false;
+        for progress in [0., 0.5, 1.] {
+            p.progress = progress;
+            assert_eq!(p.active_effect_factor(), 1.);
+        }
+    }
+
+    #[test]
+    fn test_can_upgrade() {
+        let mut p = Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            kind: Type::Policy,
+            status: Status::Active,
+            upgrades: [5, 10]
+                .into_iter()
+                .map(|cost| Upgrade {
+                    cost,
+                    effects: vec![],
+                    active: false,
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        // Insufficient capital.
+        assert!(!p.can_upgrade(4));
+        assert!(!p.can_upgrade(-10));
+        assert!(p.can_upgrade(5));
+
+        p.upgrade();
+        assert!(!p.can_upgrade(5));
+        assert!(p.can_upgrade(10));
+
+        // At the level cap.
+        p.upgrade();
+        assert_eq!(p.level, 2);
+        assert!(!p.can_upgrade(1000));
+    }
+
+    #[test]
+    fn test_refund_for_downgrade() {
+        let chains: [&[usize]; 4] =
+            [&[], &[5], &[2, 4, 8], &[10, 0, 3]];
+        for costs in chains {
+            let mut p = Project {
+                id: Id::new_v4(),
+                name: "Test Project".into(),
+                kind: Type::Policy,
+                status: Status::Active,
+                upgrades: costs
+                    .iter()
+                    .map(|cost| Upgrade {
+                        cost: *cost,
+                        effects: vec![],
+                        active: false,
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+
+            // Nothing to refund at the base level.
+            assert_eq!(p.refund_for_downgrade(), 0);
+
+            for _ in 0..costs.len() {
+                p.upgrade();
+            }
+            assert_eq!(p.level, costs.len());
+
+            // Each downgrade refunds the upgrade that
+            // got the project to its current level.
+            for cost in costs.iter().rev() {
+                assert_eq!(p.refund_for_downgrade(), *cost);
+                p.downgrade();
+            }
+            assert_eq!(p.level, 0);
+            assert_eq!(p.refund_for_downgrade(), 0);
+        }
+    }
+
+    #[test]
+    fn test_population_cost() {
+        let mut p = Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            base_cost: Cost::Dynamic(1e-9, Factor::Population),
+            cost_modifier: 1.,
+            ..Default::default()
+        };
+        let demand = OutputMap::default();
+
+        p.update_cost(2022, 0., 8e9, &demand, 1.);
+        let cost = p.cost;
+        assert_eq!(cost, 8);
+
+        p.update_cost(2022, 0., 10e9, &demand, 1.);
+        assert!(p.cost > cost);
+        assert_eq!(p.cost, 10);
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        let mut p = Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            cost: 10,
+            base_cost: Cost::Fixed(10),
+            cost_modifier: 1.,
+            kind: Type::Research,
+            status: Status::Building,
+            ..Default::default()
+        };
+        p.set_points(1);
+        assert_eq!(p.estimate, 10);
+        assert_eq!(p.estimate_remaining(), 10);
+
+        for _ in 0..5 {
+            p.build();
+        }
+        assert_eq!(p.estimate_remaining(), 5);
+
+        for _ in 0..4 {
+            p.build();
+        }
+        assert_eq!(p.estimate_remaining(), 1);
+
+        // Partial years count as a full year.
+        p.progress = 0.99;
+        assert_eq!(p.estimate_remaining(), 1);
+        p.progress = 0.45;
+        assert_eq!(p.estimate_remaining(), 6);
+
+        p.progress = 0.99;
+        p.build();
+        assert_eq!(p.status, Status::Finished);
+        assert_eq!(p.estimate_remaining(), 0);
+
+        // Projects not being built report the full estimate.
+        for status in [Status::Halted, Status::Stalled] {
+            p.status = status;
+            p.progress = 0.5;
+            assert_eq!(p.estimate_remaining(), 10);
+        }
+    }
+
+    #[test]
+    fn test_active_outcomes_migration() {
+        let mut p = Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            ..Default::default()
+        };
+        let mut json = serde_json::to_value(&p).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("active_outcomes");
+
+        // Old saves without an outcome.
+        fields.insert("active_outcome".into(), json!(null));
+        let loaded: Project =
+            serde_json::from_value(json.clone()).unwrap();
+        assert!(loaded.active_outcomes.is_empty());
+
+        // Old saves with an outcome.
+        let fields = json.as_object_mut().unwrap();
+        fields.insert("active_outcome".into(), json!(1));
+        let loaded: Project =
+            serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(loaded.active_outcomes, vec![1]);
+
+        // Missing entirely.
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("active_outcome");
+        let loaded: Project =
+            serde_json::from_value(json).unwrap();
+        assert!(loaded.active_outcomes.is_empty());
+
+        // Round-trips in the new shape.
+        p.active_outcomes = vec![0, 2];
+        let ser = serde_json::to_string(&p).unwrap();
+        let loaded: Project =
+            serde_json::from_str(&ser).unwrap();
+        assert_eq!(loaded.active_outcomes, vec![0, 2]);
+    }
+
     #[test]
     fn test_project_estimate() {
         let mut p = Project {
@@ -648,54 +1239,93 @@ mod test {
         assert!(prev_estimate > p.estimate);
     }
 
+    fn outcome(
+        likelihood: Likelihood,
+        conditions: Vec<Condition>,
+    ) -> Outcome {
+        Outcome {
+            effects: vec![],
+            probability: Probability {
+                likelihood,
+                conditions,
+            },
+        }
+    }
+
     #[test]
     fn test_project_outcomes() {
-        // let p = Project {
-        //     id: Id::new_v4(),
-        //     name: "Test Project".into(),
-        //     cost: 1,
-        //     base_cost: Cost::Fixed(1),
-        //     cost_modifier: 1.,
-        //     kind: Type::Policy,
-        //     status: Status::Building,
-        //     outcomes: vec![
-        //         Outcome {
-        //             effects: vec![],
-        //             probability: Probability {
-        //                 likelihood: Likelihood::Guaranteed,
-        //                 conditions: vec![
-        //                     Condition::WorldVariable(
-        //                         WorldVariable::Year,
-        //                         Comparator::Equal,
-        //                         10.,
-        //                     ),
-        //                 ],
-        //             },
-        //         },
-        //         Outcome {
-        //             effects: vec![],
-        //             probability: Probability {
-        //                 likelihood: Likelihood::Guaranteed,
-        //                 conditions: vec![],
-        //             },
-        //         },
-        //     ],
-        //     ..Default::default()
-        // };
-
-        // let mut state = State::default();
-        // TODO move this test to state
+        let mut state = State::default();
+        state.world.year = 2022;
+        let p = Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            outcomes: vec![
+                outcome(
+                    Likelihood::Guaranteed,
+                    vec![Condition::WorldVariable(
+                        WorldVariable::Year,
+                        Comparator::Equal,
+                        2030.,
+                    )],
+                ),
+                outcome(Likelihood::Guaranteed, vec![]),
+            ],
+            ..Default::default()
+        };
 
         // Should be the second outcome
-        // since the first condition isn't met
-        // let outcome = p.roll_outcome(&state, &mut rng);
-        // let (_outcome, i) = outcome.unwrap();
-        // assert_eq!(i, 1);
-        //
-        // // Now should be the first,
-        // state.world.year = 10;
-        // let outcome = p.roll_outcome(&state, &mut rng);
-        // let (_outcome, i) = outcome.unwrap();
-        // assert_eq!(i, 0);
+        // since the first condition isn't met.
+        let mut rng = fastrand::Rng::with_seed(0);
+        for _ in 0..20 {
+            let (_, i) =
+                p.roll_outcome(&state, &mut rng).unwrap();
+            assert_eq!(i, 1);
+        }
+
+        // Nothing eligible falls back to the first outcome.
+        let p = Project {
+            outcomes: vec![outcome(
+                Likelihood::Impossible,
+                vec![],
+            )],
+            ..p
+        };
+        let (_, i) = p.roll_outcome(&state, &mut rng).unwrap();
+        assert_eq!(i, 0);
+    }
+
+    #[test]
+    fn test_weighted_outcomes() {
+        let state = State::default();
+        let p = Project {
+            id: Id::new_v4(),
+            name: "Test Project".into(),
+            outcomes: vec![
+                outcome(Likelihood::Guaranteed, vec![]),
+                outcome(Likelihood::Likely, vec![]),
+            ],
+            ..Default::default()
+        };
+
+        let rolls = 10_000;
+        let roll_counts = |seed| {
+            let mut rng = fastrand::Rng::with_seed(seed);
+            let mut counts = [0; 2];
+            for _ in 0..rolls {
+                let (_, i) =
+                    p.roll_outcome(&state, &mut rng).unwrap();
+                counts[i] += 1;
+            }
+            counts
+        };
+
+        // Guaranteed (1.0) vs likely (0.5),
+        // so expect roughly a 2:1 split.
+        let counts = roll_counts(0);
+        let share = counts[0] as f32 / rolls as f32;
+        assert!((share - 2. / 3.).abs() < 0.02);
+
+        // Deterministic under a fixed seed.
+        assert_eq!(counts, roll_counts(0));
     }
 }
@@ -14,6 +14,10 @@ use strum::{Display, EnumIter, EnumString, IntoStaticStr};
 // 40 years per level
 const DEVELOP_SPEED: f32 = 1. / 40.;
 
+/// Habitability lost per meter of sea level
+/// rise for an entirely coastal region.
+pub const SEA_LEVEL_HABITABILITY_PENALTY: f32 = 10.;
+
 #[derive(
     Debug, Clone, Serialize, Deserialize, PartialEq, Default,
 )]
@@ -39,6 +43,16 @@ pub struct Region {
     /// from this value
     pub base_habitability: f32,
 
+    /// How exposed the region is to sea level rise,
+    /// from 0 (entirely inland) to 1 (entirely coastal).
+    #[serde(default)]
+    pub coastal_exposure: f32,
+
+    /// Habitability lost to sea level rise,
+    /// see `Region::update_sea_level_impact`.
+    #[serde(default)]
+    pub sea_level_penalty: f32,
+
     /// Local temperature and precipitation
     pub temp_lo: f32,
     pub temp_hi: f32,
@@ -50,6 +64,15 @@ pub struct Region {
     pub pattern_idxs: Vec<usize>,
 }
 
+impl Display for Region {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 impl HasId for Region {
     fn id(&self) -> &Id {
         &self.id
@@ -76,13 +99,64 @@ impl Region {
     }
 
     pub fn habitability(&self) -> f32 {
+        self.habitability_factors()
+            .iter()
+            .map(|(_, val)| val)
+            .sum()
+    }
+
+    /// The contributions to this region's habitability,
+    /// which sum to its total habitability.
+    pub fn habitability_factors(
+        &self,
+    ) -> Vec<(HabitabilityFactor, f32)> {
         // Factors:
         // - [X] regional temp
         // - [ ] precip TODO
-        // - [ ] sea_level_rise TODO
+        // - [X] sea_level_rise
         // - [X] number of negative events
-        self.base_habitability
-            - (f32::max(0., self.temp_hi - 35.).powf(2.) * 10.)
+        vec![
+            (HabitabilityFactor::Base, self.base_habitability),
+            (
+                HabitabilityFactor::Temperature,
+                -(f32::max(0., self.temp_hi - 35.).powf(2.)
+                    * 10.),
+            ),
+            (
+                HabitabilityFactor::SeaLevelRise,
+                -self.sea_level_penalty,
+            ),
+        ]
+    }
+
+    /// Set the habitability lost to sea level rise (in meters),
+    /// which scales with the region's coastal exposure.
+    /// This replaces rather than adds to the previous penalty,
+    /// so it can be called every year.
+    pub fn update_sea_level_impact(
+        &mut self,
+        sea_level_rise: f32,
+    ) {
+        self.sea_level_penalty = self.coastal_exposure
+            * sea_level_rise.max(0.)
+            * SEA_LEVEL_HABITABILITY_PENALTY;
+    }
+
+    pub fn has_flag(&self, flag: RegionFlag) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    /// Remove one instance of the flag,
+    /// returning whether the region had it.
+    pub fn remove_flag(&mut self, flag: RegionFlag) -> bool {
+        if let Some(idx) =
+            self.flags.iter().position(|f| *f == flag)
+        {
+            self.flags.remove(idx);
+            true
+        } else {
+            false
+        }
     }
 
     pub fn set_income_level(&mut self, level: usize) {
@@ -93,6 +167,17 @@ impl Region {
         self.income.level() as f32 + self.development
     }
 
+    /// Progress (0 to 1) towards the next income band.
+    /// High-income regions have nowhere further to go,
+    /// so they're always at 1.
+    pub fn income_progress(&self) -> f32 {
+        if self.is_max_income() {
+            1.
+        } else {
+            self.development.clamp(0., 1.)
+        }
+    }
+
     pub fn demand_level(
         &self,
         output: &Output,
@@ -127,14 +212,36 @@ impl Region {
         modifier: f32,
         income_pop_coefs: &[[f32; 4]; 4],
     ) {
-        let coefs = income_pop_coefs[self.income.level()];
-        let change = coefs[0]
-            + (coefs[1] * year)
-            + (coefs[2] * year.powf(2.0))
-            + (coefs[3] * year.powf(3.0));
+        let change =
+            self.income.growth_rate(year, income_pop_coefs);
         self.population *= 1. + (change * modifier);
     }
 
+    /// Project this region's population `years` into the future
+    /// from `year`, applying the same annual growth as `update_pop`:
+    ///
+    /// `population * Π (1 + rate(year + i) * growth_modifier)`
+    ///
+    /// where `growth_modifier` is the same as `update_pop`'s
+    /// `modifier`, i.e. `1 + world.population_growth_modifier`.
+    /// The region's income level is held constant, so this is
+    /// meant for showing population trends rather than simulating.
+    pub fn projected_population(
+        &self,
+        year: usize,
+        years: usize,
+        growth_modifier: f32,
+        income_pop_coefs: &[[f32; 4]; 4],
+    ) -> f32 {
+        (0..years).fold(self.population, |population, i| {
+            let rate = self.income.growth_rate(
+                (year + i) as f32,
+                income_pop_coefs,
+            );
+            population * (1. + rate * growth_modifier)
+        })
+    }
+
     // Outlook slowly rebounds over time
     pub fn update_outlook(
         &mut self,
@@ -157,7 +264,7 @@ impl Region {
             self.development = 0.;
             self.income = self.income.next();
         } else if self.development < 0. {
-            self.development = 1. - self.development;
+            self.development = 1. + self.development;
             self.income = self.income.prev();
         }
     }
@@ -279,6 +386,30 @@ impl Income {
         }
     }
 
+    /// The adjusted income (see `Region::adjusted_income`)
+    /// at which each band above `Income::Low` begins.
+    pub fn thresholds() -> [f32; 3] {
+        [
+            Income::LowerMiddle.level() as f32,
+            Income::UpperMiddle.level() as f32,
+            Income::High.level() as f32,
+        ]
+    }
+
+    /// Annual population growth rate in the given year,
+    /// from the world's per-income-level cubic coefficients.
+    pub fn growth_rate(
+        &self,
+        year: f32,
+        income_pop_coefs: &[[f32; 4]; 4],
+    ) -> f32 {
+        let coefs = income_pop_coefs[self.level()];
+        coefs[0]
+            + (coefs[1] * year)
+            + (coefs[2] * year.powf(2.0))
+            + (coefs[3] * year.powf(3.0))
+    }
+
     pub fn level(&self) -> usize {
         match self {
             Income::Low => 0,
@@ -299,6 +430,28 @@ impl From<usize> for Income {
     }
 }
 
+/// What contributes to a region's habitability.
+#[derive(
+    PartialEq,
+    Clone,
+    Copy,
+    Debug,
+    EnumIter,
+    IntoStaticStr,
+    EnumString,
+    Display,
+)]
+pub enum HabitabilityFactor {
+    /// Includes the impact of e.g. disasters.
+    Base,
+
+    /// Penalty from extreme heat.
+    Temperature,
+
+    /// Penalty from coastal flooding.
+    SeaLevelRise,
+}
+
 #[derive(
     Default,
     PartialEq,
@@ -319,3 +472,180 @@ pub enum Latitude {
     Temperate,
     Frigid,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::world::World;
+
+    #[test]
+    fn test_habitability_factors() {
+        let mut region = Region {
+            id: Id::new_v4(),
+            name: "Test Region".into(),
+            base_habitability: 10.,
+            temp_hi: 30.,
+            ..Default::default()
+        };
+        for temp_hi in [30., 36., 40.5] {
+            region.temp_hi = temp_hi;
+            let total: f32 = region
+                .habitability_factors()
+                .iter()
+                .map(|(_, val)| val)
+                .sum();
+            assert!(
+                (total - region.habitability()).abs() < 1e-5
+            );
+        }
+
+        // Only the heat penalty past 35°C.
+        region.temp_hi = 37.;
+        let factors = region.habitability_factors();
+        assert_eq!(
+            factors,
+            vec![
+                (HabitabilityFactor::Base, 10.),
+                (HabitabilityFactor::Temperature, -40.),
+                (HabitabilityFactor::SeaLevelRise, 0.),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sea_level_impact() {
+        let mut coastal = Region {
+            id: Id::new_v4(),
+            name: "Coastal Region".into(),
+            base_habitability: 10.,
+            coastal_exposure: 0.5,
+            ..Default::default()
+        };
+        let mut inland = Region {
+            id: Id::new_v4(),
+            name: "Inland Region".into(),
+            base_habitability: 10.,
+            ..Default::default()
+        };
+
+        let mut prev = coastal.habitability();
+        for rise in [0.1, 0.5, 1.] {
+            coastal.update_sea_level_impact(rise);
+            inland.update_sea_level_impact(rise);
+            assert!(coastal.habitability() < prev);
+            assert_eq!(inland.habitability(), 10.);
+            prev = coastal.habitability();
+        }
+
+        // Doesn't stack when updated for the same sea level.
+        coastal.update_sea_level_impact(1.);
+        assert_eq!(coastal.habitability(), prev);
+    }
+
+    #[test]
+    fn test_income_progress() {
+        let mut region = Region {
+            id: Id::new_v4(),
+            name: "Test Region".into(),
+            ..Default::default()
+        };
+        assert_eq!(region.income_progress(), 0.);
+
+        for _ in 0..20 {
+            region.develop(1., false, false);
+        }
+        assert!((region.income_progress() - 0.5).abs() < 1e-4);
+
+        // Right before and at the band boundary.
+        region.development = 1. - DEVELOP_SPEED / 2.;
+        assert_eq!(region.income, Income::Low);
+        assert!(region.income_progress() < 1.);
+        region.develop(1., false, false);
+        assert_eq!(region.income, Income::LowerMiddle);
+        assert_eq!(region.income_progress(), 0.);
+        assert_eq!(
+            region.adjusted_income(),
+            Income::thresholds()[0]
+        );
+
+        // Fast development
+        for _ in 0..8 {
+            region.develop(1.25, false, false);
+        }
+        assert!((region.income_progress() - 0.25).abs() < 1e-4);
+
+        // High income stays maxed out.
+        region.income = Income::High;
+        region.development = 0.;
+        assert_eq!(region.income_progress(), 1.);
+        assert_eq!(
+            region.adjusted_income(),
+            Income::thresholds()[2]
+        );
+
+        // Degrowth drops a band and
+        // progress stays within bounds.
+        region.develop(1., false, true);
+        assert_eq!(region.income, Income::UpperMiddle);
+        let progress = region.income_progress();
+        assert!((0. ..=1.).contains(&progress));
+        assert!((progress - 39. / 40.).abs() < 1e-4);
+        assert!(
+            region.adjusted_income() < Income::thresholds()[2]
+        );
+        assert!(
+            region.adjusted_income() > Income::thresholds()[1]
+        );
+    }
+
+    #[test]
+    fn test_projected_population() {
+        let world = World::default();
+        let coefs = &world.income_pop_coefs;
+        let year = world.year;
+        let mut region = Region {
+            id: Id::new_v4(),
+            name: "Test Region".into(),
+            population: 1000.,
+            ..Default::default()
+        };
+
+        // Zero growth
+        assert_eq!(
+            region.projected_population(year, 10, 0., coefs),
+            1000.
+        );
+        assert_eq!(
+            region.projected_population(year, 0, 1., coefs),
+            1000.
+        );
+
+        // Matches stepping the population year by year.
+        let projected =
+            region.projected_population(year, 10, 2., coefs);
+        let mut stepped = region.clone();
+        for i in 0..10 {
+            stepped.update_pop((year + i) as f32, 2., coefs);
+        }
+        assert!((projected - stepped.population).abs() < 1e-2);
+        assert!(projected > 1000.);
+        assert!(
+            projected
+                > region
+                    .projected_population(year, 10, 1., coefs)
+        );
+
+        // Richer regions grow more slowly.
+        region.income = Income::High;
+        assert!(
+            region.projected_population(year, 10, 2., coefs)
+                < projected
+        );
+
+        // Shrinking
+        assert!(
+            region.projected_population(year, 10, -1., coefs)
+                < 1000.
+        );
+    }
+}
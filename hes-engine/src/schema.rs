@@ -0,0 +1,212 @@
+//! JSON Schema for authored content, so content files
+//! can be validated before they're loaded.
+//!
+//! The schema is derived from how serde serializes each type,
+//! by serializing default instances (and one instance of each
+//! `Effect` and `Condition` variant) and inferring types from them.
+//! NOTE that this means fields aren't marked as required,
+//! as many have serde defaults and we can't tell which.
+
+use crate::{
+    events::{
+        Condition,
+        ConditionKind,
+        Effect,
+        EffectKind,
+        Event,
+        Probability,
+    },
+    projects::{Outcome, Project, Upgrade},
+    Id,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use strum::IntoEnumIterator;
+
+/// Export the JSON Schema for `Project`, `Outcome`,
+/// `Event`, and `Effect` (and the types they depend on).
+pub fn export() -> String {
+    let definitions = json!({
+        "Project": project_schema(),
+        "Upgrade": with_refs(
+            infer(&to_value(&Upgrade::default())),
+            &[("effects", "Effect")],
+        ),
+        "Outcome": with_refs(
+            infer(&to_value(&Outcome::default())),
+            &[
+                ("effects", "Effect"),
+                ("probability", "Probability"),
+            ],
+        ),
+        "Event": with_refs(
+            infer(&to_value(&Event::default())),
+            &[
+                ("effects", "Effect"),
+                ("probabilities", "Probability"),
+            ],
+        ),
+        "Probability": with_refs(
+            infer(&to_value(&Probability::default())),
+            &[("conditions", "Condition")],
+        ),
+        "Effect": effect_schema(),
+        "Condition": condition_schema(),
+    });
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": definitions,
+    });
+    serde_json::to_string_pretty(&schema).unwrap()
+}
+
+fn to_value<T: Serialize>(val: &T) -> Value {
+    serde_json::to_value(val).unwrap()
+}
+
+fn project_schema() -> Value {
+    with_refs(
+        infer(&to_value(&Project::default())),
+        &[
+            ("effects", "Effect"),
+            ("outcomes", "Outcome"),
+            ("upgrades", "Upgrade"),
+        ],
+    )
+}
+
+fn effect_schema() -> Value {
+    let id = Id::nil();
+    let variants: Vec<Value> = EffectKind::iter()
+        .map(|kind| {
+            let effect =
+                Effect::from_kind(kind, id, id, id, id, id, id);
+            variant_schema(&to_value(&effect))
+        })
+        .collect();
+    json!({ "oneOf": variants })
+}
+
+fn condition_schema() -> Value {
+    let id = Id::nil();
+    let variants: Vec<Value> = ConditionKind::iter()
+        .map(|kind| {
+            let cond = Condition::from_kind(kind, id, id, id);
+            variant_schema(&to_value(&cond))
+        })
+        .collect();
+    json!({ "oneOf": variants })
+}
+
+/// Schema for a single (externally tagged) enum variant.
+fn variant_schema(value: &Value) -> Value {
+    match value {
+        // Unit variants are serialized as just their name.
+        Value::String(name) => json!({ "const": name }),
+        Value::Object(map) => {
+            let properties: Map<String, Value> = map
+                .iter()
+                .map(|(k, v)| (k.clone(), infer(v)))
+                .collect();
+            let required: Vec<&String> = map.keys().collect();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            })
+        }
+        _ => infer(value),
+    }
+}
+
+/// Infer a schema from a serialized value.
+fn infer(value: &Value) -> Value {
+    match value {
+        // Only options serialize to null,
+        // and we don't know the inner type.
+        Value::Null => json!({}),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(_) => json!({ "type": "number" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => {
+            let schemas: Vec<Value> =
+                items.iter().map(infer).collect();
+            match schemas.first() {
+                None => json!({ "type": "array" }),
+                Some(first)
+                    if schemas.iter().all(|s| s == first) =>
+                {
+                    json!({ "type": "array", "items": first })
+                }
+
+                // Mixed types means this is a tuple.
+                Some(_) => json!({
+                    "type": "array",
+                    "items": schemas,
+                    "minItems": schemas.len(),
+                    "maxItems": schemas.len(),
+                }),
+            }
+        }
+        Value::Object(map) => {
+            let properties: Map<String, Value> = map
+                .iter()
+                .map(|(k, v)| (k.clone(), infer(v)))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+/// Point the given fields to other definitions,
+/// or their items if the field is an array.
+fn with_refs(
+    mut schema: Value,
+    refs: &[(&str, &str)],
+) -> Value {
+    for (field, def) in refs {
+        let reference =
+            json!({ "$ref": format!("#/definitions/{}", def) });
+        if let Some(prop) = schema["properties"].get_mut(*field)
+        {
+            *prop = if prop["type"] == "array" {
+                json!({ "type": "array", "items": reference })
+            } else {
+                reference
+            };
+        }
+    }
+    schema
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_schema_export() {
+        let schema: Value =
+            serde_json::from_str(&export()).unwrap();
+        let defs = &schema["definitions"];
+        for def in ["Project", "Outcome", "Event", "Effect"] {
+            assert!(defs.get(def).is_some());
+        }
+        assert_eq!(
+            defs["Project"]["properties"]["outcomes"]["items"]
+                ["$ref"],
+            "#/definitions/Outcome"
+        );
+
+        let variants =
+            defs["Effect"]["oneOf"].as_array().unwrap();
+        for kind in EffectKind::iter() {
+            let name: &'static str = kind.into();
+            let found = variants.iter().any(|v| {
+                v["const"] == name
+                    || v["properties"].get(name).is_some()
+            });
+            assert!(found, "Missing effect: {}", name);
+        }
+    }
+}
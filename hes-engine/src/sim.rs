@@ -0,0 +1,235 @@
+//! Run the game without a frontend, e.g. for balance experiments.
+//!
+//! NOTE that the game uses Hector to calculate the temperature
+//! anomaly, which isn't available here. Instead warming is
+//! approximated as proportional to emissions
+//! (see `SimConfig::warming_per_gt`).
+
+use crate::{
+    projects::{Status, Type as ProjectType},
+    state::{State, WARMING_PER_GT},
+    world::World,
+    Id,
+};
+use serde::{Deserialize, Serialize};
+
+/// How projects are chosen during the run.
+#[derive(
+    Debug, Clone, PartialEq, Default, Serialize, Deserialize,
+)]
+pub enum SimPolicy {
+    /// Don't start any projects.
+    #[default]
+    Idle,
+
+    /// Start one randomly chosen, affordable
+    /// project each planning year.
+    Random,
+
+    /// Start the given projects at the given turns: (turn, project id).
+    Scripted(Vec<(usize, Id)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimConfig {
+    pub world: World,
+
+    /// How many turns (years) to run for.
+    pub turns: usize,
+
+    /// Seeds all randomness in the run, so that
+    /// the same config produces the same report.
+    pub seed: u64,
+
+    pub policy: SimPolicy,
+
+    /// Warming (in C) per gigatonne of CO2eq emitted.
+    pub warming_per_gt: f32,
+}
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            world: World::default(),
+            turns: 10,
+            seed: 0,
+            policy: SimPolicy::default(),
+            warming_per_gt: WARMING_PER_GT,
+        }
+    }
+}
+
+/// Time series of key variables, one entry per turn.
+#[derive(
+    Debug, Clone, PartialEq, Default, Serialize, Deserialize,
+)]
+pub struct SimReport {
+    pub years: Vec<usize>,
+    pub emissions: Vec<f32>,
+    pub temperature: Vec<f32>,
+    pub extinction_rate: Vec<f32>,
+    pub outlook: Vec<f32>,
+    pub population: Vec<f32>,
+    pub political_capital: Vec<isize>,
+
+    /// The projects started: (year, project id).
+    pub started: Vec<(usize, Id)>,
+
+    /// The events that occurred: (year, event id).
+    pub events: Vec<(usize, Id)>,
+
+    pub won: bool,
+    pub game_over: bool,
+}
+
+/// Advance a new game for the configured number of turns.
+pub fn run_headless(config: SimConfig) -> SimReport {
+    let mut rng = fastrand::Rng::with_seed(config.seed);
+
+    // Seed the state's event rng from ours too,
+    // so event rolls and project outcomes are reproducible.
+    let mut state = State::new(config.world);
+    state.event_rng = rng.u64(..);
+    let mut report = SimReport::default();
+
+    for turn in 0..config.turns {
+        let year = state.world.year;
+        let to_start: Vec<Id> = match &config.policy {
+            SimPolicy::Idle => vec![],
+            SimPolicy::Random => {
+                if state.is_planning_year() {
+                    let candidates = startable_projects(&state);
+                    if candidates.is_empty() {
+                        vec![]
+                    } else {
+                        vec![
+                            candidates
+                                [rng.usize(..candidates.len())],
+                        ]
+                    }
+                } else {
+                    vec![]
+                }
+            }
+            SimPolicy::Scripted(script) => script
+                .iter()
+                .filter(|(t, _)| *t == turn)
+                .map(|(_, id)| *id)
+                .collect(),
+        };
+        for id in to_start {
+            if start_project(&mut state, &id) {
+                report.started.push((year, id));
+            }
+        }
+
+        let tgav = state.world.temperature
+            - state.world.temperature_modifier
+            + state.emissions.as_gtco2eq()
+                * config.warming_per_gt;
+        let step = state.advance_step(tgav);
+        report
+            .events
+            .extend(step.events.iter().map(|ev| (year, ev.id)));
+
+        report.years.push(state.world.year);
+        report.emissions.push(state.emissions.as_gtco2eq());
+        report.temperature.push(state.world.temperature);
+        report
+            .extinction_rate
+            .push(state.world.extinction_rate);
+        report.outlook.push(state.outlook());
+        report.population.push(state.world.population());
+        report.political_capital.push(state.political_capital);
+
+        if state.game_over {
+            break;
+        }
+    }
+
+    report.won = state.won();
+    report.game_over = state.game_over;
+    report
+}
+
+/// Projects which can be started and, for policies,
+/// which can be afforded.
+fn startable_projects(state: &State) -> Vec<Id> {
+    state
+        .world
+        .projects
+        .iter()
+        .filter(|p| !p.locked && p.status == Status::Inactive)
+        .filter(|p| {
+            p.kind != ProjectType::Policy
+                || p.cost as isize <= state.political_capital
+        })
+        .map(|p| p.id)
+        .collect()
+}
+
+/// Start a project, paying for it if it's a policy.
+/// Returns false if the project couldn't be started.
+fn start_project(state: &mut State, id: &Id) -> bool {
+    let project = &state.world.projects[id];
+    if project.locked || project.status != Status::Inactive {
+        return false;
+    }
+    match project.kind {
+        ProjectType::Policy => {
+            let cost = project.cost as isize;
+            if cost > state.political_capital {
+                return false;
+            }
+            state.change_political_capital(-cost);
+        }
+        _ => {
+            // Research and initiatives need
+            // at least one point to make progress.
+            state.set_project_points(id, 1);
+        }
+    }
+    state.start_project(id);
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reproducible_report() {
+        let config = SimConfig {
+            turns: 6,
+            seed: 42,
+            policy: SimPolicy::Random,
+            ..Default::default()
+        };
+        let a = run_headless(config.clone());
+        let b = run_headless(config);
+        assert_eq!(a, b);
+        assert_eq!(a.years.len(), 6);
+        assert!(!a.started.is_empty());
+    }
+
+    #[test]
+    fn test_scripted_policy() {
+        let world = World::default();
+        let id = world
+            .projects
+            .iter()
+            .find(|p| {
+                !p.locked && p.kind == ProjectType::Research
+            })
+            .unwrap()
+            .id;
+        let config = SimConfig {
+            world,
+            turns: 2,
+            policy: SimPolicy::Scripted(vec![(1, id)]),
+            ..Default::default()
+        };
+        let report = run_headless(config);
+        assert_eq!(report.started.len(), 1);
+        assert_eq!(report.started[0].1, id);
+    }
+}
@@ -1,21 +1,40 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::{
+    achievements::{AchievementId, Achievements},
+    difficulty::Difficulty,
     events::{
         Condition,
+        DeferredEvent,
         Effect,
+        EffectChange,
+        EffectError,
         Event,
         EventPool,
+        EventSeverity,
         Flag,
+        MigrationWave,
         Phase,
         Request,
+        ICON_EVENTS,
     },
+    feedstocks,
+    flavor::DialogueNext,
     kinds::*,
-    npcs::NPC,
+    npcs::{NPCKind, ALLY_SUPPORT_DISCOUNT, NPC},
     outputs,
-    production::{calculate_required, produce, ProcessChanges},
+    production::{
+        apply_feature_caps,
+        calculate_required,
+        produce,
+        redistribute_orders,
+        ProcessChanges,
+        ProcessFeature,
+        ProductionOrder,
+    },
     projects::{
         Group,
+        LockReason,
         Outcome,
         Project,
         ProjectChanges,
@@ -28,6 +47,7 @@ use crate::{
     Id,
 };
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
 const LIFESPAN: usize = 60;
 const PRODUCTION_SHORTAGE_PENALTY: f32 = 60.;
@@ -37,6 +57,37 @@ const WIN_EMISSIONS: f32 = 0.0;
 const WIN_EXTINCTION: f32 = 20.0;
 const WIN_TEMPERATURE: f32 = 1.0;
 
+/// Global warming potentials, for converting to CO2-equivalents.
+pub const CH4_GWP: f32 = 36.;
+pub const N2O_GWP: f32 = 298.;
+
+/// Warming (in C) per gigatonne of CO2eq emitted,
+/// roughly the IPCC's transient climate response
+/// to cumulative emissions (TCRE).
+pub const WARMING_PER_GT: f32 = 0.45 / 1000.;
+
+/// Factor to compute contentedness change resulting from an
+/// icon/world event, by its intensity.
+pub const EVENT_INTENSITY_TO_CONTENTEDNESS: f32 = 0.1;
+
+/// An output's full mix, in 5% mix share increments.
+const FULL_MIX_SHARE: isize = 20;
+
+/// Demand can't be dampened by shortfalls below this.
+const MIN_DEMAND_DAMPENING: f32 = 0.5;
+
+/// Water stress (demand over available water)
+/// above which regions start losing outlook.
+pub const WATER_STRESS_THRESHOLD: f32 = 0.8;
+
+/// Consecutive years of high water stress
+/// before regions start losing outlook.
+pub const WATER_STRESS_YEARS: usize = 2;
+
+/// Default outlook lost per year, per unit of
+/// water stress above `WATER_STRESS_THRESHOLD`.
+pub const WATER_STRESS_OUTLOOK: f32 = 1.;
+
 /// Represents the game state.
 #[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct State {
@@ -67,8 +118,33 @@ pub struct State {
     pub resources: Resources,
     pub feedstocks: Feedstocks,
     pub output_demand: OutputDemand,
+
+    /// How strongly demand for each output responds
+    /// to shortfalls in its supply; 0 means not at all.
+    #[serde(default)]
+    pub demand_elasticity: OutputMap,
+
+    /// The current dampening of demand for each output
+    /// due to persistent shortfalls, where 1 is undampened.
+    #[serde(default = "undampened")]
+    pub demand_dampening: OutputMap,
+
+    /// Outlook lost per year, per unit of sustained
+    /// water stress above `WATER_STRESS_THRESHOLD`.
+    #[serde(default = "default_water_stress_outlook")]
+    pub water_stress_outlook: f32,
+
+    /// Consecutive years water stress has been
+    /// above `WATER_STRESS_THRESHOLD`.
+    #[serde(default)]
+    pub water_stress_years: usize,
     pub resource_demand: ResourceDemand,
 
+    /// Hard caps on the combined output of all processes
+    /// with a given feature, e.g. total nuclear capacity.
+    #[serde(default)]
+    pub feature_caps: BTreeMap<ProcessFeature, f32>,
+
     /// Note that the biodiversity values here
     /// should be ignored, as we do the proper
     /// scaling in `Process::extinction_rate`.
@@ -83,6 +159,146 @@ pub struct State {
     pub events: Vec<Event>,
 
     pub event_pool: EventPool,
+
+    /// The seeds used to roll events: (year, phase, seed),
+    /// so a run's events can be replayed.
+    #[serde(default)]
+    pub event_seeds: Vec<(usize, Phase, u64)>,
+
+    /// The state of the rng used to seed event rolls
+    /// and project outcomes, persisted so that reloading
+    /// a save doesn't reroll them.
+    #[serde(default = "random_seed")]
+    pub event_rng: u64,
+
+    /// Migration waves per origin region,
+    /// so they can be reversed if needed.
+    #[serde(default)]
+    pub migrations: BTreeMap<Id, Vec<MigrationWave>>,
+
+    // Temporary effects: (
+    //  effect,
+    //  region id,
+    //  year the effect expires
+    // )
+    #[serde(default)]
+    pub temporary_effects: Vec<(Effect, Option<Id>, usize)>,
+
+    /// Seat changes actually made per NPC,
+    /// as they may be clamped, so they can be reversed.
+    #[serde(default)]
+    pub seat_changes: BTreeMap<Id, Vec<f32>>,
+
+    /// Protected land changes actually made, as they're
+    /// clamped to [0, 1], so they can be reversed.
+    /// Stored as `(requested change, actual change)` so
+    /// each effect reverses its own change, in any order.
+    #[serde(default)]
+    pub protected_land_changes: Vec<(f32, f32)>,
+
+    /// Political capital debts cleared by bailouts,
+    /// so they can be restored if a bailout is reversed.
+    #[serde(default)]
+    pub bailout_debts: Vec<isize>,
+
+    /// Per-region outlook changes made by outlook effects,
+    /// so they can be reversed exactly.
+    #[serde(default)]
+    pub outlook_changes: Vec<(Effect, Vec<(Id, f32)>)>,
+
+    /// Project cost modifier changes made because of
+    /// ally supporters, so they can be reversed
+    /// if the relationship drops.
+    #[serde(default)]
+    pub npc_support_modifiers: BTreeMap<Id, f32>,
+
+    /// The difficulty currently applied, which scales
+    /// project costs and event probabilities on top of
+    /// their own modifiers. See `State::apply_difficulty`.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+
+    #[serde(default)]
+    pub achievements: Achievements,
+}
+
+fn undampened() -> OutputMap {
+    OutputMap::splat(1.)
+}
+
+fn default_water_stress_outlook() -> f32 {
+    WATER_STRESS_OUTLOOK
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher`
+/// is stable across platforms and Rust versions.
+struct Fnv64(u64);
+impl Fnv64 {
+    fn new() -> Self {
+        Fnv64(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_value(&mut self, value: &serde_json::Value) {
+        use serde_json::Value;
+        match value {
+            // Non-finite floats (including any NaN)
+            // serialize as null.
+            Value::Null => self.write(&[0]),
+            Value::Bool(b) => self.write(&[1, *b as u8]),
+            Value::Number(n) => {
+                if let Some(n) = n.as_u64() {
+                    self.write(&[2]);
+                    self.write(&n.to_le_bytes());
+                } else if let Some(n) = n.as_i64() {
+                    self.write(&[3]);
+                    self.write(&n.to_le_bytes());
+                } else {
+                    // Treat -0 and 0 the same.
+                    let n = n.as_f64().unwrap_or(0.) + 0.;
+                    self.write(&[4]);
+                    self.write(&n.to_bits().to_le_bytes());
+                }
+            }
+            Value::String(s) => {
+                self.write(&[5]);
+                self.write_len(s.len());
+                self.write(s.as_bytes());
+            }
+            Value::Array(vals) => {
+                self.write(&[6]);
+                self.write_len(vals.len());
+                for val in vals {
+                    self.write_value(val);
+                }
+            }
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                entries.sort_by_key(|(key, _)| *key);
+                self.write(&[7]);
+                self.write_len(entries.len());
+                for (key, val) in entries {
+                    self.write_len(key.len());
+                    self.write(key.as_bytes());
+                    self.write_value(val);
+                }
+            }
+        }
+    }
+}
+
+fn random_seed() -> u64 {
+    fastrand::u64(..)
 }
 
 impl Default for State {
@@ -130,6 +346,8 @@ impl State {
 
             events: vec![],
             event_pool: EventPool::new(events),
+            event_seeds: vec![],
+            event_rng: random_seed(),
 
             runs: 0,
             game_over: false,
@@ -139,12 +357,26 @@ impl State {
             emissions: Emissions::default(),
             produced: Production::default(),
             output_demand: OutputDemand::default(),
+            demand_elasticity: OutputMap::default(),
+            demand_dampening: undampened(),
+            water_stress_outlook: WATER_STRESS_OUTLOOK,
+            water_stress_years: 0,
+            feature_caps: BTreeMap::default(),
             resource_demand: ResourceDemand::default(),
             byproducts: Byproducts::default(),
 
             flags: vec![],
             requests: vec![],
             policy_queue: vec![],
+            migrations: BTreeMap::default(),
+            temporary_effects: vec![],
+            seat_changes: BTreeMap::default(),
+            protected_land_changes: vec![],
+            bailout_debts: vec![],
+            outlook_changes: vec![],
+            npc_support_modifiers: BTreeMap::default(),
+            difficulty: Difficulty::default(),
+            achievements: Achievements::default(),
         };
         state.initialize();
         state
@@ -180,10 +412,71 @@ impl State {
             intensity as f32;
     }
 
+    /// Apply an occurrence of an icon event, i.e. a disaster,
+    /// adjusting its region's habitability by its intensity
+    /// (as the game does) in addition to the event's own effects.
+    /// Returns a log of what the event's effects changed.
+    pub fn apply_icon_event(
+        &mut self,
+        event_id: Id,
+        region_id: Id,
+        severity: EventSeverity,
+    ) -> Vec<EffectChange> {
+        let intensity = ICON_EVENTS
+            .get(&event_id)
+            .map_or(0, |ev| ev.intensity);
+        let effect =
+            intensity as f32 * EVENT_INTENSITY_TO_CONTENTEDNESS;
+        self.apply_disaster(
+            -effect.round() as isize,
+            &region_id,
+        );
+        self.apply_event(event_id, Some(region_id), severity)
+    }
+
     pub fn outlook(&self) -> f32 {
         self.world.outlook() - self.shortages_outlook
     }
 
+    /// A hash of the world's content that's stable across
+    /// platforms, so that two engines given the same inputs
+    /// can compare hashes each turn to catch desyncs.
+    /// Floats are hashed by their bit patterns,
+    /// with all NaNs hashing the same.
+    ///
+    /// The world is hashed via its `serde_json` representation,
+    /// so the hash isn't guaranteed to be stable across
+    /// `serde_json` versions; only compare hashes from
+    /// engines built with the same dependencies.
+    pub fn content_hash(&self) -> u64 {
+        let value = serde_json::to_value(&self.world)
+            .expect("World is always serializable");
+        let mut hasher = Fnv64::new();
+        hasher.write_value(&value);
+        hasher.0
+    }
+
+    /// Switch to a different difficulty, adjusting political
+    /// capital. Project costs and event probabilities read
+    /// the difficulty when they're computed, so effects on
+    /// their modifiers are unaffected by switching.
+    /// The previous difficulty's changes are replaced rather
+    /// than stacked, so this can be called again while the
+    /// game is being set up.
+    pub fn apply_difficulty(&mut self, difficulty: Difficulty) {
+        let prev = self.difficulty;
+        if prev == difficulty {
+            return;
+        }
+
+        self.change_political_capital(
+            difficulty.political_capital()
+                - prev.political_capital(),
+        );
+        self.difficulty = difficulty;
+        self.update_project_costs();
+    }
+
     pub fn change_political_capital(&mut self, amount: isize) {
         self.political_capital += amount;
     }
@@ -207,15 +500,125 @@ impl State {
         self.step_production();
         updates.extend(self.step_world(tgav));
         self.world.year += 1;
+        self.tick_temporary_effects();
+        self.prune_migrations();
+        self.prune_outlook_changes();
+        self.recompute_after_secession();
 
         if self.is_planning_year() {
             let mut outcomes = self.roll_new_policy_outcomes();
             updates.append(&mut outcomes);
         }
+        self.update_achievements();
 
         updates
     }
 
+    /// Check for any newly met achievements.
+    pub fn update_achievements(&mut self) {
+        let mut achievements =
+            std::mem::take(&mut self.achievements);
+        achievements.update(&self.world.achievements, self);
+        self.achievements = achievements;
+    }
+
+    /// Achievements unlocked for the first time
+    /// in the most recent year.
+    pub fn newly_unlocked_achievements(
+        &self,
+    ) -> Vec<AchievementId> {
+        self.achievements.newly_unlocked.clone()
+    }
+
+    /// Refresh aggregates which depend on the regions
+    /// still in the world, e.g. after one has seceded.
+    /// Population and outlook are computed from the remaining
+    /// regions directly, so it's demand that needs updating.
+    pub fn recompute_after_secession(&mut self) {
+        self.update_demand();
+    }
+
+    /// Reverse any temporary effects which have expired.
+    pub fn tick_temporary_effects(&mut self) {
+        let year = self.world.year;
+        let (expired, pending) =
+            std::mem::take(&mut self.temporary_effects)
+                .into_iter()
+                .partition(|(_, _, expires)| *expires <= year);
+        self.temporary_effects = pending;
+        for (effect, region_id, _) in expired {
+            effect.unapply(self, region_id);
+        }
+    }
+
+    /// Drop migration waves which can no longer be reversed.
+    /// Migrations only happen for a region, i.e. from events,
+    /// and those effects are only unapplied when temporary ones
+    /// expire. So for each region we keep only as many of its
+    /// most recent waves as it has pending temporary migrations.
+    fn prune_migrations(&mut self) {
+        let mut pending: BTreeMap<Id, usize> =
+            BTreeMap::default();
+        for (effect, region_id, _) in &self.temporary_effects {
+            if let Some(id) = region_id {
+                *pending.entry(*id).or_default() +=
+                    effect.migrations();
+            }
+        }
+        self.migrations.retain(|id, waves| {
+            let keep = pending.get(id).copied().unwrap_or(0);
+            waves.drain(..waves.len().saturating_sub(keep));
+            !waves.is_empty()
+        });
+    }
+
+    /// Drop outlook changes which can no longer be reversed,
+    /// i.e. those no applied project effect or pending temporary
+    /// effect could unapply, such as those from events.
+    /// Effects are matched by fingerprint, as gradual projects
+    /// apply their effects in parts.
+    fn prune_outlook_changes(&mut self) {
+        fn collect(
+            effect: &Effect,
+            into: &mut BTreeSet<String>,
+        ) {
+            match effect {
+                Effect::All(effects) => {
+                    for effect in effects {
+                        collect(effect, into);
+                    }
+                }
+                Effect::Temporary(effect, _) => {
+                    collect(effect, into)
+                }
+                effect => {
+                    into.insert(effect.fingerprint());
+                }
+            }
+        }
+
+        let mut reversible = BTreeSet::default();
+        for project in self.world.projects.iter() {
+            if project.is_online() {
+                for effect in
+                    project.active_effects_with_outcomes()
+                {
+                    collect(effect, &mut reversible);
+                }
+            } else if project.gradual && project.progress > 0. {
+                for effect in &project.effects {
+                    collect(effect, &mut reversible);
+                }
+            }
+        }
+        for (effect, _, _) in &self.temporary_effects {
+            collect(effect, &mut reversible);
+        }
+        self.outlook_changes.retain(|(effect, _)| {
+            reversible.contains(&effect.fingerprint())
+        });
+    }
+
     pub fn is_planning_year(&self) -> bool {
         self.world.year % 5 == 0
     }
@@ -230,22 +633,98 @@ impl State {
         }
     }
 
+    /// Apply effects and return a log of what they changed.
+    pub fn apply_effects_logged(
+        &mut self,
+        effects: &[Effect],
+        region_id: Option<Id>,
+    ) -> Vec<EffectChange> {
+        let mut log = vec![];
+        for effect in effects {
+            effect.apply_logged(self, region_id, &mut log);
+        }
+        log
+    }
+
+    /// Check that all effects in the world content
+    /// reference things that exist, so that authoring
+    /// mistakes can be caught when the content is loaded.
+    pub fn validate_effects(&self) -> Result<(), EffectError> {
+        let project_effects =
+            self.world.projects.iter().flat_map(|project| {
+                project
+                    .effects
+                    .iter()
+                    .chain(project.outcomes.iter().flat_map(
+                        |outcome| outcome.effects.iter(),
+                    ))
+                    .chain(project.upgrades.iter().flat_map(
+                        |upgrade| upgrade.effects.iter(),
+                    ))
+            });
+        let event_effects =
+            self.event_pool.events.iter().flat_map(|event| {
+                event.effects.iter().chain(
+                    event
+                        .flavor
+                        .dialogue
+                        .lines
+                        .iter()
+                        .filter_map(|line| match &line.next {
+                            Some(DialogueNext::Responses(
+                                responses,
+                            )) => Some(responses),
+                            _ => None,
+                        })
+                        .flatten()
+                        .flat_map(|resp| resp.effects.iter()),
+                )
+            });
+        for effect in project_effects.chain(event_effects) {
+            effect.validate(self)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the world content is consistent:
+    /// effects reference things that exist and
+    /// processes don't have conflicting features.
+    pub fn validate_content(&self) -> Result<(), ContentError> {
+        self.validate_effects()?;
+        for process in self.world.processes.iter() {
+            if let Some(features) = process.feature_conflict() {
+                return Err(ContentError::ConflictingFeatures(
+                    process.id, features,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply an occurrence of an event,
+    /// scaling its effects by the occurrence's severity.
+    /// Returns a log of what the event's effects changed.
     pub fn apply_event(
         &mut self,
         event_id: Id,
         region_id: Option<Id>,
-    ) {
+        severity: EventSeverity,
+    ) -> Vec<EffectChange> {
         let mut effects = vec![];
         let event = &self.event_pool.events[&event_id];
         self.events.push(event.clone());
 
-        for effect in &event.effects {
-            effects.push((effect.clone(), region_id));
+        for effect in event.scaled_effects(severity) {
+            effects.push((effect, region_id));
         }
 
+        let mut log = vec![];
         for (effect, region_id) in effects {
-            effect.apply(self, region_id);
+            effect.apply_sourced_logged(
+                self, region_id, event_id, &mut log,
+            );
         }
+        log
     }
 
     pub fn eval_conditions(
@@ -332,7 +811,8 @@ impl State {
                 industry_byproducts,
             )
         };
-        self.output_demand.base = output_demand;
+        self.output_demand.base =
+            output_demand * self.demand_dampening;
 
         // Generate production orders based on current process mixes and demand
         let total_demand = self.output_demand.total();
@@ -345,15 +825,16 @@ impl State {
             required_resources.electricity;
         self.output_demand.base.fuel += required_resources.fuel;
 
-        // Now re-calculate orders
-        let total_demand = self.output_demand.total();
-        let orders = self.world.processes.orders(&total_demand);
-
         // Apply land protection
         self.resources.available.land =
             self.world.starting_resources.land
                 * (1. - self.protected_land);
 
+        // Now re-calculate orders
+        let total_demand = self.output_demand.total();
+        let mut orders = self.solve_production(&total_demand);
+        apply_feature_caps(&mut orders, &self.feature_caps);
+
         // Run production function
         let (
             produced_by_process,
@@ -386,8 +867,171 @@ impl State {
         self.emissions.update(self.byproducts.total());
     }
 
+    /// Production orders for the given demand, with
+    /// the shortfall from processes at their limit or out of
+    /// feedstock shifted to other processes for the same output.
+    /// See `redistribute_orders` for how it's distributed.
+    pub fn solve_production(
+        &self,
+        demand: &OutputMap,
+    ) -> Vec<ProductionOrder> {
+        let mut orders = self.world.processes.orders(demand);
+        redistribute_orders(
+            &mut orders,
+            demand,
+            &self.feedstocks.available,
+        );
+        orders
+    }
+
+    /// Each process's contribution to the extinction rate
+    /// given its current production, largest first.
+    pub fn extinction_contributions(&self) -> Vec<(Id, f32)> {
+        let starting_land = self.world.starting_resources.land;
+        let mut contribs: Vec<(Id, f32)> = self
+            .world
+            .processes
+            .iter()
+            .map(|p| {
+                let amount = self
+                    .produced
+                    .by_process
+                    .get(&p.id)
+                    .unwrap_or(&0.);
+                (
+                    p.id,
+                    p.extinction_rate(starting_land) * amount,
+                )
+            })
+            .collect();
+        contribs.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        contribs
+    }
+
+    /// Emissions (grams CO2eq) per unit of an output,
+    /// given what the current mix produced.
+    /// If none of the output was produced this is zero.
+    pub fn emissions_intensity(&self, output: Output) -> f32 {
+        let produced = self.produced.of(output);
+        if produced <= 0. {
+            return 0.;
+        }
+        let emissions: f32 = self
+            .world
+            .processes
+            .iter()
+            .filter(|p| p.output == output)
+            .map(|p| {
+                let amount = self
+                    .produced
+                    .by_process
+                    .get(&p.id)
+                    .unwrap_or(&0.);
+                p.adj_byproducts().co2eq() * amount
+            })
+            .sum();
+        emissions / produced
+    }
+
+    /// Project the temperature anomaly for the next `years` years.
+    ///
+    /// This is a much simpler model than Hector:
+    /// emissions are held at their current level and warming
+    /// is proportional to cumulative emissions (see `WARMING_PER_GT`).
+    /// The `temperature_modifier` is already included in the
+    /// current temperature and is assumed to stay constant.
+    /// Lagged warming from past emissions isn't modeled,
+    /// so zero emissions means a flat curve.
+    pub fn project_temperature(
+        &self,
+        years: usize,
+    ) -> Vec<f32> {
+        let annual_warming =
+            self.emissions.as_gtco2eq() * WARMING_PER_GT;
+        (1..=years)
+            .map(|i| {
+                self.world.temperature
+                    + annual_warming * i as f32
+            })
+            .collect()
+    }
+
+    /// Water demand as a percent of available water.
+    pub fn water_use_percent(&self) -> f32 {
+        self.water_stress() * 100.
+    }
+
+    /// Water demand as a fraction of available water.
+    pub fn water_stress(&self) -> f32 {
+        let usage = self.resource_demand.of(Resource::Water);
+        usage / self.resources.available.water
+    }
+
+    /// Sustained high water stress wears down every
+    /// region's outlook, in proportion to how far over
+    /// `WATER_STRESS_THRESHOLD` it is. Once stress falls
+    /// regions recover through the usual outlook rebound.
+    pub fn apply_water_stress(&mut self) {
+        let excess =
+            self.water_stress() - WATER_STRESS_THRESHOLD;
+        if excess > 0. {
+            self.water_stress_years += 1;
+        } else {
+            self.water_stress_years = 0;
+        }
+
+        if self.water_stress_years >= WATER_STRESS_YEARS {
+            let change = -excess * self.water_stress_outlook;
+            for region in self.world.regions.iter_mut() {
+                region.outlook += change;
+            }
+        }
+    }
+
+    /// Current annual emissions of each gas,
+    /// as (CO2, CH4, N2O) in gigatonnes of that gas
+    /// (i.e. not CO2-equivalents).
+    pub fn emissions_by_gas(&self) -> (f64, f64, f64) {
+        let Emissions { co2, ch4, n2o } = self.emissions;
+        (
+            co2 as f64 * 1e-15,
+            ch4 as f64 * 1e-15,
+            n2o as f64 * 1e-15,
+        )
+    }
+
+    /// Draw down finite feedstock reserves by what
+    /// production extracted this turn.
+    pub fn deplete_feedstocks(&mut self) {
+        let mut consumed = self.feedstocks.consumed;
+        for (fs, val) in consumed.items_mut() {
+            if !fs.is_finite() {
+                *val = 0.;
+            }
+        }
+        self.feedstocks.consume(consumed);
+    }
+
+    /// Remaining reserves of each finite feedstock,
+    /// with the estimated years until exhaustion
+    /// at the current rate of consumption.
+    pub fn feedstock_reserves(
+        &self,
+    ) -> Vec<(Feedstock, f32, f32)> {
+        Feedstock::iter()
+            .filter(|fs| fs.is_finite())
+            .map(|fs| {
+                (
+                    fs,
+                    self.feedstocks.available[fs],
+                    self.feedstocks.until_exhaustion(fs),
+                )
+            })
+            .collect()
+    }
+
     fn step_production(&mut self) {
-        self.feedstocks.consume(self.feedstocks.consumed);
+        self.deplete_feedstocks();
 
         // Water and land aren't "consumed" as land
         // can obviously be re-purposed and we assume water
@@ -424,10 +1068,36 @@ impl State {
                 / 4.))
             .max(0.);
 
+        self.update_demand_dampening(&demand_met);
+
         self.world
             .update_extinction_rate(&self.produced.by_process);
     }
 
+    /// Dampen demand for outputs that are falling short,
+    /// according to their elasticity, and let it recover
+    /// once supply has caught up.
+    fn update_demand_dampening(
+        &mut self,
+        demand_met: &OutputMap,
+    ) {
+        for (output, dampening) in
+            self.demand_dampening.items_mut()
+        {
+            let elasticity = self.demand_elasticity[output];
+            let met = demand_met[output];
+            let met = if met.is_finite() { met } else { 1. };
+            let shortfall = (1. - met).max(0.);
+            if shortfall > 0. {
+                *dampening *= 1. - elasticity * shortfall;
+            } else {
+                *dampening += (1. - *dampening) * elasticity;
+            }
+            *dampening =
+                dampening.clamp(MIN_DEMAND_DAMPENING, 1.);
+        }
+    }
+
     fn step_world(&mut self, tgav: f32) -> Vec<Update> {
         if self.world.year >= self.death_year {
             self.game_over = true;
@@ -435,6 +1105,7 @@ impl State {
 
         self.world.update_populations();
         let temp_change = self.world.update_climate(tgav);
+        self.apply_sea_level_impact();
 
         let stop = self.flags.contains(&Flag::StopDevelopment);
         let fast = self.flags.contains(&Flag::FastDevelopment);
@@ -442,9 +1113,11 @@ impl State {
         let (regions_up, regions_down) =
             self.world.regions.develop(stop, fast, degrow);
 
-        let wretched_ally = self.npcs.is_ally("The Fanonist");
+        let wretched_ally =
+            self.npcs.is_ally(NPCKind::Fanonist);
         let consumerist_ally =
-            self.npcs.is_ally("The Consumerist");
+            self.npcs.is_ally(NPCKind::Consumerist);
+        self.apply_water_stress();
         self.world.update_outlook(
             temp_change,
             wretched_ally,
@@ -461,6 +1134,15 @@ impl State {
             .collect()
     }
 
+    /// Update how much habitability coastal
+    /// regions have lost to sea level rise.
+    pub fn apply_sea_level_impact(&mut self) {
+        let sea_level_rise = self.world.sea_level_rise;
+        for region in self.world.regions.iter_mut() {
+            region.update_sea_level_impact(sea_level_rise);
+        }
+    }
+
     // Every planning cycle
     pub fn finish_cycle(&mut self) {
         let outlook_change = self.outlook() - self.last_outlook;
@@ -511,14 +1193,271 @@ impl State {
         completed
     }
 
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            political_capital: self.political_capital,
+            research_points: self.research_points,
+            policy_queue: self.policy_queue.clone(),
+            mix_shares: self
+                .world
+                .processes
+                .iter()
+                .map(|p| (p.id, p.mix_share))
+                .collect(),
+            projects: self
+                .world
+                .projects
+                .iter()
+                .map(|p| (p.id, p.status, p.points, p.estimate))
+                .collect(),
+            relationships: self
+                .npcs
+                .iter()
+                .map(|npc| (npc.id, npc.relationship))
+                .collect(),
+            produced: self.produced.clone(),
+            resources: self.resources.clone(),
+            feedstocks: self.feedstocks.clone(),
+            output_demand: self.output_demand.clone(),
+            resource_demand: self.resource_demand.clone(),
+            byproducts: self.byproducts.clone(),
+            emissions: self.emissions.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: StateSnapshot) {
+        self.political_capital = snapshot.political_capital;
+        self.research_points = snapshot.research_points;
+        self.policy_queue = snapshot.policy_queue;
+        for (id, mix_share) in snapshot.mix_shares {
+            self.world.processes[&id].mix_share = mix_share;
+        }
+        for (id, status, points, estimate) in snapshot.projects
+        {
+            let project = &mut self.world.projects[&id];
+            project.status = status;
+            project.points = points;
+            project.estimate = estimate;
+        }
+        for (id, relationship) in snapshot.relationships {
+            self.npcs[&id].relationship = relationship;
+        }
+        self.produced = snapshot.produced;
+        self.resources = snapshot.resources;
+        self.feedstocks = snapshot.feedstocks;
+        self.output_demand = snapshot.output_demand;
+        self.resource_demand = snapshot.resource_demand;
+        self.byproducts = snapshot.byproducts;
+        self.emissions = snapshot.emissions;
+    }
+
+    /// Saves from before processes tracked `sourced_modifiers`
+    /// have the project feature modifiers applied but not keyed,
+    /// so stopping or advancing those projects wouldn't remove them.
+    /// This rebuilds the keys for online and in-progress gradual
+    /// projects without changing the modifiers themselves.
+    /// Call it after loading a save; it does nothing if any
+    /// keys are already present.
+    pub fn rebuild_sourced_modifiers(&mut self) {
+        if self
+            .world
+            .processes
+            .iter()
+            .any(|p| !p.sourced_modifiers.is_empty())
+        {
+            return;
+        }
+
+        let mut effects: Vec<(Id, Effect)> = vec![];
+        for project in self.world.projects.iter() {
+            if project.is_online() {
+                for effect in
+                    project.active_effects_with_outcomes()
+                {
+                    effects.push((project.id, effect.clone()));
+                }
+            } else if project.is_building()
+                && project.gradual
+                && project.progress > 0.
+            {
+                for effect in &project.effects {
+                    effects.push((
+                        project.id,
+                        effect.clone() * project.progress,
+                    ));
+                }
+            }
+        }
+
+        for (source, effect) in effects {
+            if let Some((modifier, feat, amount)) =
+                effect.feature_modifier()
+            {
+                for process in self
+                    .world
+                    .processes
+                    .iter_mut()
+                    .filter(|p| p.features.contains(&feat))
+                {
+                    process
+                        .sourced_modifiers
+                        .push((source, modifier, feat, amount));
+                }
+            }
+        }
+    }
+
     pub fn change_process_mix_share(
         &mut self,
         process_id: &Id,
         change: isize,
     ) {
-        let changes = self.world.processes[process_id]
-            .change_mix_share(change);
-        self.apply_changes(changes);
+        if let Some(process) =
+            self.world.process_mut(process_id)
+        {
+            let changes = process.change_mix_share(change);
+            self.apply_changes(changes);
+        }
+    }
+
+    /// Check that planned mix share changes for an output
+    /// are valid before they're committed.
+    pub fn validate_process_mix(
+        &self,
+        output: Output,
+        changes: &BTreeMap<Id, isize>,
+    ) -> Result<(), MixError> {
+        for id in changes.keys() {
+            match self.world.processes.try_get(id) {
+                Some(process) if process.output == output => (),
+                _ => return Err(MixError::MissingProcess(*id)),
+            }
+        }
+
+        let mut total = 0;
+        for process in self.world.processes.iter() {
+            if process.output != output {
+                continue;
+            }
+            let change =
+                changes.get(&process.id).copied().unwrap_or(0);
+            let share = process.mix_share as isize + change;
+            if share < 0 {
+                return Err(MixError::Negative(process.id));
+            }
+
+            // Only check the limit for increases, so that
+            // processes already over their limit can be reduced.
+            let max_share =
+                self.process_max_share(&process.id) as isize;
+            if change > 0 && share > max_share {
+                return Err(MixError::OverLimit(process.id));
+            }
+            total += share;
+        }
+        if total > FULL_MIX_SHARE {
+            return Err(MixError::OverAllocated(output));
+        }
+        Ok(())
+    }
+
+    /// Land used by production processes if they
+    /// had the given mix shares (in 5% increments),
+    /// at the current output demand.
+    fn land_use_for_mix(
+        &self,
+        shares: &BTreeMap<Id, usize>,
+    ) -> f32 {
+        let demand = self.output_demand.total();
+        self.world
+            .processes
+            .iter()
+            .map(|p| {
+                let share =
+                    shares.get(&p.id).copied().unwrap_or(0);
+                demand[p.output]
+                    * share as f32
+                    * 0.05
+                    * p.adj_resources().land
+            })
+            .sum()
+    }
+
+    /// Suggest process mix changes (in 5% increments) which
+    /// bring the land used by production processes under the
+    /// `budget` (in the same units as `resources.available.land`)
+    /// while still meeting demand.
+    ///
+    /// This is a greedy heuristic: each step moves 5% of an output
+    /// from one process to another, choosing the move that saves
+    /// the most land, until the budget is met or no move helps.
+    /// Moves keep each output's total share the same and respect
+    /// each process's max share, and locked processes are never used.
+    /// The budget may not be reachable, in which case these
+    /// are the changes that get closest to it.
+    pub fn suggest_mix_for_land_budget(
+        &self,
+        budget: f32,
+    ) -> Vec<(Id, isize)> {
+        let demand = self.output_demand.total();
+        let mut shares: BTreeMap<Id, usize> = self
+            .world
+            .processes
+            .iter()
+            .map(|p| (p.id, p.mix_share))
+            .collect();
+        let max_shares: BTreeMap<Id, usize> = self
+            .world
+            .processes
+            .iter()
+            .map(|p| (p.id, self.process_max_share(&p.id)))
+            .collect();
+
+        let mut land_use = self.land_use_for_mix(&shares);
+        while land_use > budget {
+            let mut best: Option<(Id, Id, f32)> = None;
+            for from in self.world.processes.iter() {
+                if shares[&from.id] == 0 {
+                    continue;
+                }
+                for to in self.world.processes.iter() {
+                    if to.locked
+                        || to.output != from.output
+                        || shares[&to.id] >= max_shares[&to.id]
+                    {
+                        continue;
+                    }
+                    let saved = demand[from.output]
+                        * 0.05
+                        * (from.adj_resources().land
+                            - to.adj_resources().land);
+                    if saved > best.map_or(0., |(.., s)| s) {
+                        best = Some((from.id, to.id, saved));
+                    }
+                }
+            }
+            match best {
+                Some((from, to, saved)) => {
+                    *shares.get_mut(&from).unwrap() -= 1;
+                    *shares.get_mut(&to).unwrap() += 1;
+                    land_use -= saved;
+                }
+                None => break,
+            }
+        }
+
+        self.world
+            .processes
+            .iter()
+            .map(|p| {
+                (
+                    p.id,
+                    shares[&p.id] as isize
+                        - p.mix_share as isize,
+                )
+            })
+            .filter(|(_, change)| *change != 0)
+            .collect()
     }
 
     pub fn process_max_share(&self, process_id: &Id) -> usize {
@@ -528,18 +1467,29 @@ impl State {
             .max_share(&output_demand, &feedstocks)
     }
 
+    /// Roll and apply the events for the phase.
+    /// Also returns the queued events which were pushed
+    /// back to the next roll (see `EventPool::roll_with_seed`).
     pub fn roll_events(
         &mut self,
         phase: Phase,
-    ) -> Vec<ResolvedEvent> {
+    ) -> (Vec<ResolvedEvent>, Vec<DeferredEvent>) {
+        let mut rng = fastrand::Rng::with_seed(self.event_rng);
+        let seed = rng.u64(..);
+        self.event_rng = rng.get_seed();
+        self.event_seeds.push((self.world.year, phase, seed));
+
         let mut pool = self.event_pool.clone();
-        let events = pool.roll_for_phase(phase, &self);
+        let (events, deferred) =
+            pool.roll_with_seed(phase, &self, seed);
         self.event_pool = pool;
 
-        let events: Vec<ResolvedEvent> = events
+        let mut events: Vec<ResolvedEvent> = events
             .into_iter()
-            .map(|(ev, region_id)| ResolvedEvent {
+            .map(|(ev, region_id, severity)| ResolvedEvent {
                 event: ev,
+                severity,
+                changes: vec![],
                 region: region_id.map(|id| {
                     (
                         id,
@@ -555,15 +1505,70 @@ impl State {
         // are handled differently, so we don't
         // apply their effects immediately here.
         if phase != Phase::Icon {
-            for ev in &events {
-                self.apply_event(
+            for ev in &mut events {
+                ev.changes = self.apply_event(
                     ev.id,
                     ev.region.as_ref().map(|(id, _)| *id),
+                    ev.severity,
                 );
             }
         }
 
-        events
+        (events, deferred)
+    }
+
+    /// Advance the world by a single year: roll and apply the
+    /// year's events and disasters, then step the year with the
+    /// given temperature anomaly, finishing the cycle if that
+    /// reaches the next planning year. This lets a frontend
+    /// spread a turn across frames rather than block on it.
+    pub fn advance_step(&mut self, tgav: f32) -> StepProgress {
+        let (mut events, _) =
+            self.roll_events(Phase::WorldMain);
+
+        // Frontends apply disasters over the course of the
+        // year as they're shown; here they're applied at once.
+        let (mut disasters, _) = self.roll_events(Phase::Icon);
+        for ev in &mut disasters {
+            if let Some((region_id, _)) = &ev.region {
+                ev.changes = self.apply_icon_event(
+                    ev.id,
+                    *region_id,
+                    ev.severity,
+                );
+            }
+        }
+        events.extend(disasters);
+
+        let updates = self.step_year(tgav);
+
+        let complete =
+            self.game_over || self.is_planning_year();
+        if self.is_planning_year() {
+            self.finish_cycle();
+        }
+        StepProgress {
+            events,
+            updates,
+            complete,
+        }
+    }
+
+    /// Advance through to the next planning year,
+    /// i.e. `State::advance_step` until the turn is complete,
+    /// getting each year's temperature anomaly from `tgav`.
+    pub fn advance(
+        &mut self,
+        mut tgav: impl FnMut(&State) -> f32,
+    ) -> StepProgress {
+        let mut progress = StepProgress::default();
+        while !progress.complete {
+            let step = self.advance_step(tgav(self));
+            progress.events.extend(step.events);
+            progress.updates.extend(step.updates);
+            progress.complete = step.complete;
+        }
+        progress
     }
 }
 
@@ -576,9 +1581,10 @@ impl State {
         let mut outcomes: Vec<(Id, usize)> = Vec::new();
         for (id, changes) in &mut changes {
             if changes.completed {
-                let project = &self.world.projects[&id];
-                match self.roll_project_outcome(project) {
-                    Some((outcome, i)) => {
+                match self.roll_project_outcome(id) {
+                    Some(i) => {
+                        let outcome = &self.world.projects[&id]
+                            .outcomes[i];
                         for effect in &outcome.effects {
                             changes
                                 .add_effects
@@ -592,7 +1598,12 @@ impl State {
         }
 
         for (id, i) in outcomes {
-            self.world.projects[&id].active_outcome = Some(i);
+            self.world.projects[&id].active_outcomes = vec![i];
+        }
+
+        if changes.iter().any(|(_, changes)| changes.completed)
+        {
+            self.world.projects.unlock_dependents();
         }
 
         self.update_project_costs();
@@ -600,19 +1611,88 @@ impl State {
         changes
     }
 
+    /// Why a project is locked,
+    /// or `None` if it isn't.
+    pub fn why_locked(&self, id: &Id) -> Option<LockReason> {
+        let project = &self.world.projects[id];
+        if !project.locked {
+            return None;
+        }
+
+        let lock = Effect::LocksProject(*id);
+        let locked_by = self.world.projects.iter().find(|p| {
+            p.active_effects_with_outcomes().contains(&&lock)
+        });
+        if let Some(other) = locked_by {
+            return Some(LockReason::PolicyConflict(other.id));
+        }
+
+        let missing: Vec<Id> = project
+            .requires
+            .iter()
+            .filter(|req| {
+                !self.world.projects[*req].is_online()
+            })
+            .copied()
+            .collect();
+        if missing.is_empty() {
+            Some(LockReason::EventGated)
+        } else {
+            Some(LockReason::Prerequisite(missing))
+        }
+    }
+
+    /// Give projects a cost break for each
+    /// ally amongst their supporters, undoing
+    /// any previous break that no longer applies.
+    pub fn apply_npc_support_modifiers(&mut self) {
+        for project in self.world.projects.iter_mut() {
+            let allies = project
+                .supporters
+                .iter()
+                .filter(|id| {
+                    let npc = &self.npcs[*id];
+                    !npc.locked && npc.is_ally()
+                })
+                .count();
+            let modifier =
+                -(allies as f32 * ALLY_SUPPORT_DISCOUNT);
+            let prev = self
+                .npc_support_modifiers
+                .get(&project.id)
+                .copied()
+                .unwrap_or(0.);
+            project.cost_modifier += modifier - prev;
+            if allies > 0 {
+                self.npc_support_modifiers
+                    .insert(project.id, modifier);
+            } else {
+                self.npc_support_modifiers.remove(&project.id);
+            }
+        }
+    }
+
     fn update_project_costs(&mut self) {
+        self.apply_npc_support_modifiers();
+
         let base_modifier = self.base_project_cost_modifier();
+        let difficulty_modifier =
+            self.difficulty.cost_modifier();
         let total_demand = self.output_demand.total();
         let income_level = self.world.regions.income_level();
+        let population = self.world.population();
 
-        let posadist_ally = self.npcs.is_ally("The Posadist");
-        let utopian_ally = self.npcs.is_ally("The Utopian");
+        let posadist_ally =
+            self.npcs.is_ally(NPCKind::Posadist);
+        let utopian_ally = self.npcs.is_ally(NPCKind::Utopian);
         let animal_ally =
-            self.npcs.is_ally("The Animal Liberationist");
+            self.npcs.is_ally(NPCKind::AnimalLiberationist);
         let environ_ally =
-            self.npcs.is_ally("The Environmentalist");
-        let ecofem_ally = self.npcs.is_ally("The Ecofeminist");
-        let malthus_ally = self.npcs.is_ally("The Malthusian");
+            self.npcs.is_ally(NPCKind::Environmentalist);
+        let ecofem_ally =
+            self.npcs.is_ally(NPCKind::Ecofeminist);
+        let malthus_ally =
+            self.npcs.is_ally(NPCKind::Malthusian);
 
         for project in self.world.projects.iter_mut() {
             let mut group_modifier = 1.0;
@@ -661,14 +1741,16 @@ impl State {
             project.update_cost(
                 self.world.year,
                 income_level,
+                population,
                 &total_demand,
                 // Modifier only relevant for built projects,
                 // not policies.
-                if project.kind == ProjectType::Policy {
-                    1.0
-                } else {
-                    base_modifier * group_modifier
-                },
+                difficulty_modifier
+                    * if project.kind == ProjectType::Policy {
+                        1.0
+                    } else {
+                        base_modifier * group_modifier
+                    },
             );
             project.update_required_majority(&self.npcs);
         }
@@ -736,56 +1818,49 @@ impl State {
         self.world.projects[project_id].set_points(points);
     }
 
-    /// Roll to see the outcome of this project
-    fn roll_project_outcome<'a>(
-        &self,
-        project: &'a Project,
-    ) -> Option<(&'a Outcome, usize)> {
-        let mut outcome = None;
-        for (i, o) in project.outcomes.iter().enumerate() {
-            match o.probability.eval(self, None) {
-                Some(likelihood) => {
-                    let prob = likelihood.p();
-                    if fastrand::f32() <= prob {
-                        outcome = Some((o, i));
-                        break;
-                    }
-                }
-                None => (),
-            }
-        }
-        if outcome.is_none() {
-            outcome = Some((&project.outcomes[0], 0));
-        }
+    /// Roll to see the outcome of this project,
+    /// returning the index of the outcome.
+    /// This draws from the state's seeded rng,
+    /// so a given seed always rolls the same outcomes.
+    fn roll_project_outcome(
+        &mut self,
+        project_id: &Id,
+    ) -> Option<usize> {
+        let mut rng = fastrand::Rng::with_seed(self.event_rng);
+        let outcome = self.world.projects[project_id]
+            .roll_outcome(self, &mut rng)
+            .map(|(_, i)| i);
+        self.event_rng = rng.get_seed();
         outcome
     }
 
     fn roll_new_policy_outcomes(&mut self) -> Vec<Update> {
-        let mut effects: Vec<Effect> = Vec::new();
+        let mut effects: Vec<(Id, Effect)> = Vec::new();
         let ids: Vec<Id> =
             self.policy_queue.drain(..).collect();
         for id in &ids {
-            let mut active_outcome = None;
-            let proj = &self.world.projects[id];
-            match self.roll_project_outcome(proj) {
-                Some((outcome, i)) => {
+            let mut active_outcomes = vec![];
+            match self.roll_project_outcome(id) {
+                Some(i) => {
+                    let outcome =
+                        &self.world.projects[id].outcomes[i];
                     for effect in &outcome.effects {
-                        effects.push(effect.clone());
+                        effects.push((*id, effect.clone()));
                     }
-                    active_outcome = Some(i);
+                    active_outcomes.push(i);
                 }
                 None => (),
             }
             let proj = &mut self.world.projects[id];
-            proj.active_outcome = active_outcome;
+            proj.active_outcomes = active_outcomes;
             proj.status = Status::Active;
             for effect in &proj.effects {
-                effects.push(effect.clone());
+                effects.push((*id, effect.clone()));
             }
         }
 
-        for effect in effects {
-            effect.apply(self, None);
+        for (id, effect) in effects {
+            effect.apply_sourced(self, None, id);
         }
         self.update_demand();
 
@@ -832,7 +1907,7 @@ impl Emissions {
     }
 
     pub fn as_co2eq(&self) -> f32 {
-        self.co2 + (self.n2o * 298.) + (self.ch4 * 36.)
+        self.co2 + (self.n2o * N2O_GWP) + (self.ch4 * CH4_GWP)
     }
 
     pub fn as_gtco2eq(&self) -> f32 {
@@ -869,10 +1944,10 @@ impl Changes for ProcessChanges {
 impl Changes for ProjectChanges {
     fn apply(self, state: &mut State) {
         for effect in self.remove_effects {
-            effect.unapply(state, None);
+            effect.unapply_sourced(state, None, self.source);
         }
         for effect in self.add_effects {
-            effect.apply(state, None);
+            effect.apply_sourced(state, None, self.source);
         }
         for (id, change) in self.relationships {
             state.npcs[&id].relationship += change;
@@ -880,10 +1955,111 @@ impl Changes for ProjectChanges {
     }
 }
 
+/// A planned process mix which isn't possible.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MixError {
+    MissingProcess(Id),
+    Negative(Id),
+    OverLimit(Id),
+    OverAllocated(Output),
+}
+impl std::fmt::Display for MixError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::MissingProcess(id) => {
+                write!(
+                    f,
+                    "No process with id {id} for this output"
+                )
+            }
+            Self::Negative(id) => {
+                write!(f, "Process {id} would have a negative mix share")
+            }
+            Self::OverLimit(id) => {
+                write!(f, "Process {id} would exceed its limit")
+            }
+            Self::OverAllocated(output) => {
+                write!(f, "{output} mix would exceed 100%")
+            }
+        }
+    }
+}
+impl std::error::Error for MixError {}
+
+/// Problems with the world content.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentError {
+    InvalidEffect(EffectError),
+    ConflictingFeatures(Id, (ProcessFeature, ProcessFeature)),
+}
+impl std::fmt::Display for ContentError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            Self::InvalidEffect(err) => write!(f, "{err}"),
+            Self::ConflictingFeatures(id, (a, b)) => {
+                write!(
+                    f,
+                    "Process {id} can't be both {a} and {b}"
+                )
+            }
+        }
+    }
+}
+impl std::error::Error for ContentError {}
+impl From<EffectError> for ContentError {
+    fn from(err: EffectError) -> Self {
+        Self::InvalidEffect(err)
+    }
+}
+
+/// The parts of the state that planning changes,
+/// so a planning session can be cheaply reverted.
+/// Note that upgrades aren't covered, as they apply effects.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    political_capital: isize,
+    research_points: isize,
+    policy_queue: Vec<Id>,
+
+    /// (process id, mix share)
+    mix_shares: Vec<(Id, usize)>,
+
+    /// (project id, status, points, estimate)
+    projects: Vec<(Id, Status, usize, usize)>,
+
+    /// (npc id, relationship)
+    relationships: Vec<(Id, f32)>,
+
+    // Production is recomputed when the mix changes,
+    // so these are captured as well.
+    produced: Production,
+    resources: Resources,
+    feedstocks: Feedstocks,
+    output_demand: OutputDemand,
+    resource_demand: ResourceDemand,
+    byproducts: Byproducts,
+    emissions: Emissions,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ResolvedEvent {
     pub event: Event,
     pub region: Option<(Id, String)>,
+
+    /// The severity of this occurrence of the event.
+    #[serde(default)]
+    pub severity: EventSeverity,
+
+    /// What this occurrence's effects changed,
+    /// once they've been applied.
+    #[serde(default)]
+    pub changes: Vec<EffectChange>,
 }
 impl std::ops::Deref for ResolvedEvent {
     type Target = Event;
@@ -892,6 +2068,18 @@ impl std::ops::Deref for ResolvedEvent {
     }
 }
 
+/// What happened in a call to `State::advance_step`,
+/// or across a whole turn for `State::advance`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StepProgress {
+    pub events: Vec<ResolvedEvent>,
+    pub updates: Vec<Update>,
+
+    /// Whether the turn is over, i.e. it's a
+    /// planning year or the game has ended.
+    pub complete: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Update {
     Region {
@@ -1278,3 +2466,1024 @@ impl Update {
 //         );
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        byproducts,
+        events::{Comparator, PlayerVariable},
+        production::Process,
+    };
+
+    #[test]
+    fn test_prune_migrations() {
+        let mut state = State::default();
+        let a = state.world.regions.by_idx(0).id;
+        let b = state.world.regions.by_idx(1).id;
+
+        Effect::Migration.apply(&mut state, Some(a));
+        Effect::Migration.apply(&mut state, Some(b));
+        let temporary =
+            Effect::Temporary(Box::new(Effect::Migration), 5);
+        temporary.apply(&mut state, Some(a));
+        let latest = state.migrations[&a][1].clone();
+        assert_eq!(state.migrations[&a].len(), 2);
+
+        // Only the wave from the pending temporary effect
+        // can still be reversed.
+        state.prune_migrations();
+        assert_eq!(state.migrations[&a], vec![latest]);
+        assert!(!state.migrations.contains_key(&b));
+
+        // And it's pruned once the effect expires.
+        state.world.year += 5;
+        state.tick_temporary_effects();
+        state.prune_migrations();
+        assert!(state.migrations.is_empty());
+    }
+
+    #[test]
+    fn test_prune_outlook_changes() {
+        let mut state = State::default();
+        let income = Effect::IncomeOutlookChange(1.);
+        let demand =
+            Effect::DemandOutlookChange(Output::Fuel, 1.);
+        let id = state.world.projects.by_idx(0).id;
+        state.world.projects[&id].status = Status::Active;
+        state.world.projects[&id].effects =
+            vec![income.clone()];
+
+        // One from the project and one as if from an event.
+        income.apply(&mut state, None);
+        demand.apply(&mut state, None);
+        state.prune_outlook_changes();
+        assert_eq!(state.outlook_changes.len(), 1);
+        assert_eq!(state.outlook_changes[0].0, income);
+
+        // Temporary effects can still be reversed.
+        Effect::Temporary(Box::new(demand.clone()), 2)
+            .apply(&mut state, None);
+        state.prune_outlook_changes();
+        assert_eq!(state.outlook_changes.len(), 2);
+
+        // Nothing can reverse these once the project stops.
+        state.world.projects[&id].status = Status::Inactive;
+        state.temporary_effects.clear();
+        state.prune_outlook_changes();
+        assert!(state.outlook_changes.is_empty());
+    }
+
+    #[test]
+    fn test_validate_content() {
+        let mut state = State::default();
+        assert_eq!(state.validate_content(), Ok(()));
+
+        let process = state
+            .world
+            .processes
+            .iter_mut()
+            .find(|p| {
+                p.features.contains(&ProcessFeature::IsSolar)
+            })
+            .unwrap();
+        let id = process.id;
+        process
+            .features
+            .push(ProcessFeature::MakesNuclearWaste);
+        assert_eq!(
+            state.validate_content(),
+            Err(ContentError::ConflictingFeatures(
+                id,
+                (
+                    ProcessFeature::IsSolar,
+                    ProcessFeature::MakesNuclearWaste
+                )
+            ))
+        );
+    }
+
+    #[test]
+    fn test_why_locked() {
+        let mut state = State::default();
+        let mut projects = state.world.projects.iter();
+        let a = projects.next().unwrap().id;
+        let b = projects.next().unwrap().id;
+        let c = projects.next().unwrap().id;
+
+        state.world.projects[&a].locked = false;
+        assert_eq!(state.why_locked(&a), None);
+
+        // Locked without prerequisites.
+        state.world.projects[&b].locked = true;
+        state.world.projects[&b].requires = vec![];
+        assert_eq!(
+            state.why_locked(&b),
+            Some(LockReason::EventGated)
+        );
+
+        // Locked until a prerequisite is completed.
+        state.world.projects[&a].status = Status::Inactive;
+        state.world.projects[&b].requires = vec![a];
+        assert_eq!(
+            state.why_locked(&b),
+            Some(LockReason::Prerequisite(vec![a]))
+        );
+        state.world.projects[&a].status = Status::Finished;
+        assert_eq!(
+            state.why_locked(&b),
+            Some(LockReason::EventGated)
+        );
+
+        // Locked by another active project.
+        let other = &mut state.world.projects[&c];
+        other.level = 0;
+        other.status = Status::Active;
+        other.active_outcomes = vec![];
+        other.effects = vec![Effect::LocksProject(b)];
+        assert_eq!(
+            state.why_locked(&b),
+            Some(LockReason::PolicyConflict(c))
+        );
+    }
+
+    #[test]
+    fn test_npc_support_modifiers() {
+        let mut state = State::default();
+        let (project_id, npc_id) = state
+            .world
+            .projects
+            .iter()
+            .filter(|p| p.cost >= 10)
+            .find_map(|p| {
+                p.supporters.first().map(|npc| (p.id, *npc))
+            })
+            .unwrap();
+        state.npcs[&npc_id].locked = false;
+        state.npcs[&npc_id].relationship = 3.;
+        state.update_project_costs();
+        let base_modifier =
+            state.world.projects[&project_id].cost_modifier;
+        let base_cost = state.world.projects[&project_id].cost;
+
+        // Gaining an ally lowers the cost.
+        state.npcs[&npc_id].relationship = 5.;
+        state.update_project_costs();
+        let project = &state.world.projects[&project_id];
+        assert_eq!(
+            project.cost_modifier,
+            base_modifier - ALLY_SUPPORT_DISCOUNT
+        );
+        assert!(project.cost < base_cost);
+
+        // Applying again doesn't stack.
+        state.apply_npc_support_modifiers();
+        assert_eq!(
+            state.world.projects[&project_id].cost_modifier,
+            base_modifier - ALLY_SUPPORT_DISCOUNT
+        );
+
+        // Losing the ally restores it.
+        state.npcs[&npc_id].relationship = 3.;
+        state.update_project_costs();
+        let project = &state.world.projects[&project_id];
+        assert!(
+            (project.cost_modifier - base_modifier).abs()
+                < 1e-6
+        );
+        assert_eq!(project.cost, base_cost);
+        assert!(state.npc_support_modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_demand_elasticity() {
+        let mut state = State::default();
+        state.demand_elasticity.plant_calories = 0.5;
+        state.update_demand();
+        let base = state.output_demand.base;
+
+        // Persistent shortfall of plant calories.
+        let mut demand_met = OutputMap::splat(1.);
+        demand_met.plant_calories = 0.8;
+        let mut prev = base.plant_calories;
+        for _ in 0..3 {
+            state.update_demand_dampening(&demand_met);
+            state.update_demand();
+            let demand = state.output_demand.base;
+            assert!(demand.plant_calories < prev);
+            prev = demand.plant_calories;
+
+            // No elasticity, so no dampening.
+            assert_eq!(state.demand_dampening.fuel, 1.);
+            assert_eq!(
+                demand.animal_calories,
+                base.animal_calories
+            );
+        }
+        assert!(
+            prev >= base.plant_calories * MIN_DEMAND_DAMPENING
+        );
+
+        // Supply catches up.
+        let demand_met = OutputMap::splat(1.);
+        for _ in 0..30 {
+            state.update_demand_dampening(&demand_met);
+            state.update_demand();
+            let demand = state.output_demand.base;
+            assert!(demand.plant_calories >= prev);
+            prev = demand.plant_calories;
+        }
+        assert!(
+            (prev - base.plant_calories).abs()
+                / base.plant_calories
+                < 1e-3
+        );
+    }
+
+    #[test]
+    fn test_validate_process_mix() {
+        let state = State::default();
+        let output = Output::Electricity;
+        let processes: Vec<&Process> = state
+            .world
+            .processes
+            .iter()
+            .filter(|p| p.output == output)
+            .collect();
+        assert!(state
+            .validate_process_mix(output, &BTreeMap::default())
+            .is_ok());
+
+        // Negative allocation
+        let active =
+            processes.iter().find(|p| p.mix_share > 0).unwrap();
+        let mut changes = BTreeMap::default();
+        changes.insert(
+            active.id,
+            -(active.mix_share as isize) - 1,
+        );
+        assert_eq!(
+            state.validate_process_mix(output, &changes),
+            Err(MixError::Negative(active.id))
+        );
+
+        // Shifting share between processes is fine.
+        let other = processes
+            .iter()
+            .find(|p| {
+                p.id != active.id
+                    && state.process_max_share(&p.id)
+                        > p.mix_share
+            })
+            .unwrap();
+        let mut changes = BTreeMap::default();
+        changes.insert(active.id, -1);
+        changes.insert(other.id, 1);
+        assert!(state
+            .validate_process_mix(output, &changes)
+            .is_ok());
+
+        // Over allocation
+        let mut changes = BTreeMap::default();
+        changes.insert(other.id, 1);
+        assert_eq!(
+            state.validate_process_mix(output, &changes),
+            Err(MixError::OverAllocated(output))
+        );
+
+        // Over a process's limit
+        let max_share = state.process_max_share(&other.id);
+        let mut changes = BTreeMap::default();
+        changes.insert(
+            other.id,
+            (max_share - other.mix_share) as isize + 1,
+        );
+        assert_eq!(
+            state.validate_process_mix(output, &changes),
+            Err(MixError::OverLimit(other.id))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut state = State::default();
+        let original = state.clone();
+        let snapshot = state.snapshot();
+
+        let process_id = state
+            .world
+            .processes
+            .iter()
+            .find(|p| p.mix_share > 0)
+            .unwrap()
+            .id;
+        state.change_process_mix_share(&process_id, -1);
+
+        let project_ids: Vec<Id> = state
+            .world
+            .projects
+            .iter()
+            .filter(|p| !p.locked)
+            .map(|p| p.id)
+            .take(3)
+            .collect();
+        for id in &project_ids {
+            state.set_project_points(id, 2);
+            state.start_project(id);
+        }
+        state.change_political_capital(-10);
+        assert!(state != original);
+
+        state.restore(snapshot);
+        assert!(state == original);
+    }
+
+    #[test]
+    fn test_project_outcome_seeded() {
+        let state = State::default();
+        let id = state
+            .world
+            .projects
+            .iter()
+            .find(|p| p.outcomes.len() > 1)
+            .unwrap()
+            .id;
+
+        for seed in 0..20 {
+            let mut a = State {
+                event_rng: seed,
+                ..state.clone()
+            };
+            let mut b = a.clone();
+            assert_eq!(
+                a.roll_project_outcome(&id),
+                b.roll_project_outcome(&id)
+            );
+            assert_eq!(a.event_rng, b.event_rng);
+            assert_ne!(a.event_rng, seed);
+        }
+    }
+
+    #[test]
+    fn test_event_rng_persists() {
+        let mut state = State::default();
+        state.roll_events(Phase::WorldMain);
+
+        let mut reloaded: State = serde_json::from_str(
+            &serde_json::to_string(&state).unwrap(),
+        )
+        .unwrap();
+        let rolled = state.roll_events(Phase::WorldMain);
+        let rolled_after_reload =
+            reloaded.roll_events(Phase::WorldMain);
+        assert_eq!(rolled, rolled_after_reload);
+        assert_eq!(state.event_seeds, reloaded.event_seeds);
+        assert_eq!(state.event_rng, reloaded.event_rng);
+    }
+
+    #[test]
+    fn test_extinction_contributions() {
+        let mut state = State::default();
+        state.update_demand();
+        state
+            .world
+            .update_extinction_rate(&state.produced.by_process);
+        let with = state.world.extinction_rate;
+        state
+            .world
+            .update_extinction_rate(&BTreeMap::default());
+        let without = state.world.extinction_rate;
+
+        let contribs = state.extinction_contributions();
+        assert!(contribs.windows(2).all(|w| w[0].1 >= w[1].1));
+        let total: f32 = contribs.iter().map(|(_, c)| c).sum();
+        assert!(
+            ((with - without) - total).abs() / total < 1e-3
+        );
+    }
+
+    #[test]
+    fn test_emissions_intensity() {
+        let mut state = State::default();
+        let mut processes = state
+            .world
+            .processes
+            .iter_mut()
+            .filter(|p| p.output == Output::Electricity);
+        let a = processes.next().unwrap();
+        a.byproducts = byproducts!(co2: 2., ch4: 0.5);
+        a.byproduct_modifiers = byproducts!();
+        a.output_modifier = 0.;
+        let a = a.id;
+        let b = processes.next().unwrap();
+        b.byproducts = byproducts!(co2: 4.);
+        b.byproduct_modifiers = byproducts!();
+        b.output_modifier = 0.;
+        let b = b.id;
+
+        state.produced.by_process =
+            BTreeMap::from([(a, 1.), (b, 3.)]);
+        state.produced.amount = OutputMap::default();
+        state.produced.amount.electricity = 4.;
+
+        // (1 * (2 + 0.5 * 36) + 3 * 4) / 4
+        assert_eq!(
+            state.emissions_intensity(Output::Electricity),
+            8.
+        );
+
+        // Nothing produced.
+        assert_eq!(state.emissions_intensity(Output::Fuel), 0.);
+    }
+
+    #[test]
+    fn test_suggest_mix_for_land_budget() {
+        let state = State::default();
+        let shares = |state: &State| -> BTreeMap<Id, usize> {
+            state
+                .world
+                .processes
+                .iter()
+                .map(|p| (p.id, p.mix_share))
+                .collect()
+        };
+        let land_use = state.land_use_for_mix(&shares(&state));
+        assert!(land_use > 0.);
+
+        // Already under budget.
+        assert!(state
+            .suggest_mix_for_land_budget(land_use * 1.1)
+            .is_empty());
+
+        // Unreachable budgets get as close as possible.
+        let closest = state.suggest_mix_for_land_budget(0.);
+        assert!(!closest.is_empty());
+
+        let budget = land_use * 0.95;
+        let changes = state.suggest_mix_for_land_budget(budget);
+        assert!(!changes.is_empty());
+        for output in Output::iter() {
+            let mix = changes
+                .iter()
+                .filter(|(id, _)| {
+                    state.world.processes[id].output == output
+                })
+                .map(|(_, change)| change)
+                .sum::<isize>();
+            assert_eq!(mix, 0);
+        }
+
+        let mut suggested = shares(&state);
+        for (id, change) in &changes {
+            assert!(!state.world.processes[id].locked);
+            let share = suggested.get_mut(id).unwrap();
+            *share = (*share as isize + change) as usize;
+            if *change > 0 {
+                assert!(*share <= state.process_max_share(id));
+            }
+        }
+        assert!(state.land_use_for_mix(&suggested) <= budget);
+    }
+
+    #[test]
+    fn test_project_temperature() {
+        let mut state = State::default();
+        state.world.temperature = 1.2;
+        state.emissions.co2 = 40e15;
+        let path = state.project_temperature(10);
+        assert_eq!(path.len(), 10);
+        assert!(path.windows(2).all(|w| w[1] > w[0]));
+        assert!(path[0] > 1.2);
+
+        state.emissions = Emissions::default();
+        let path = state.project_temperature(10);
+        assert!(path.iter().all(|t| *t == 1.2));
+    }
+
+    #[test]
+    fn test_protect_land_reduces_available_land() {
+        let mut state = State::default();
+        state.update_demand();
+        let available = state.resources.available.land;
+
+        Effect::ProtectLand(0.2).apply(&mut state, None);
+        state.update_demand();
+        let protected = state.resources.available.land;
+        assert!(protected < available);
+        assert!(
+            (protected
+                - state.world.starting_resources.land
+                    * (1. - state.protected_land))
+                .abs()
+                < 1.
+        );
+    }
+
+    #[test]
+    fn test_water_use_percent() {
+        let mut state = State::default();
+        state.resources.available.water = 400.;
+        state.resource_demand.base.water = 80.;
+        state.resource_demand.modifier.water = 20.;
+        state.resource_demand.factor.water = 1.;
+        assert_eq!(state.water_use_percent(), 25.);
+    }
+
+    #[test]
+    fn test_apply_difficulty() {
+        let state = State::default();
+        let costs = |state: &State| {
+            state
+                .world
+                .projects
+                .iter()
+                .map(|p| p.cost)
+                .sum::<usize>()
+        };
+        let event_rate = |state: &State| {
+            state
+                .event_pool
+                .peek(state)
+                .iter()
+                .map(|(_, p)| p)
+                .sum::<f32>()
+        };
+
+        let mut hard = state.clone();
+        hard.apply_difficulty(Difficulty::Hard);
+        assert!(costs(&hard) > costs(&state));
+        assert!(event_rate(&hard) > event_rate(&state));
+        assert!(
+            hard.political_capital < state.political_capital
+        );
+
+        let mut easy = state.clone();
+        easy.apply_difficulty(Difficulty::Easy);
+        assert!(costs(&easy) < costs(&state));
+        assert!(event_rate(&easy) < event_rate(&state));
+        assert!(
+            easy.political_capital > state.political_capital
+        );
+
+        // Reapplying doesn't stack.
+        let pc = hard.political_capital;
+        let cost = costs(&hard);
+        hard.apply_difficulty(Difficulty::Hard);
+        assert_eq!(hard.political_capital, pc);
+        assert_eq!(costs(&hard), cost);
+
+        // Changing difficulty replaces the previous one.
+        hard.apply_difficulty(Difficulty::Easy);
+        assert_eq!(
+            hard.political_capital,
+            easy.political_capital
+        );
+        assert_eq!(costs(&hard), costs(&easy));
+
+        hard.apply_difficulty(Difficulty::Normal);
+        assert_eq!(
+            hard.political_capital,
+            state.political_capital
+        );
+        for (a, b) in hard
+            .event_pool
+            .events
+            .iter()
+            .zip(state.event_pool.events.iter())
+        {
+            assert_eq!(a.prob_modifier, b.prob_modifier);
+        }
+    }
+
+    #[test]
+    fn test_difficulty_with_active_effects() {
+        let mut state = State::default();
+        let costs = |state: &State| {
+            state
+                .world
+                .projects
+                .iter()
+                .map(|p| p.cost)
+                .collect::<Vec<_>>()
+        };
+
+        // An ally discount and an event probability effect
+        // that are active while the difficulty changes.
+        let posadist = NPCKind::Posadist.id();
+        state.npcs[&posadist].relationship =
+            crate::npcs::ALLY_THRESHOLD;
+        state.update_project_costs();
+        let ally_costs = costs(&state);
+
+        let event_id = state.event_pool.events.by_idx(0).id;
+        let prob_modifier =
+            state.event_pool.events[&event_id].prob_modifier;
+        let effect =
+            Effect::ModifyEventProbability(event_id, 0.5);
+        effect.apply(&mut state, None);
+
+        state.apply_difficulty(Difficulty::Hard);
+        assert!(costs(&state) > ally_costs);
+
+        // Reversing the effect under a different
+        // difficulty restores the original modifier.
+        effect.unapply(&mut state, None);
+        assert_eq!(
+            state.event_pool.events[&event_id].prob_modifier,
+            prob_modifier
+        );
+
+        state.apply_difficulty(Difficulty::Normal);
+        assert_eq!(costs(&state), ally_costs);
+        for project in state.world.projects.iter() {
+            assert_eq!(project.cost_modifier, 1.);
+        }
+    }
+
+    #[test]
+    fn test_project_feature_effects_sourced() {
+        let mut state = State::default();
+        let project = state
+            .world
+            .projects
+            .iter()
+            .find(|p| {
+                p.effects.iter().any(|effect| {
+                    matches!(
+                        effect,
+                        Effect::OutputForFeature(..)
+                            | Effect::CO2ForFeature(..)
+                    )
+                })
+            })
+            .unwrap()
+            .clone();
+        let modifiers = |state: &State| {
+            state
+                .world
+                .processes
+                .iter()
+                .map(|p| {
+                    (
+                        p.output_modifier,
+                        p.byproduct_modifiers.co2,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let changes = || ProjectChanges {
+            source: project.id,
+            add_effects: project.effects.clone(),
+            ..Default::default()
+        };
+
+        // Re-applying the project's changes
+        // doesn't stack its feature effects.
+        state.apply_changes(changes());
+        let applied = modifiers(&state);
+        state.apply_changes(changes());
+        assert_eq!(modifiers(&state), applied);
+        assert!(state.world.processes.iter().any(|p| {
+            p.sourced_modifiers
+                .iter()
+                .any(|(source, ..)| *source == project.id)
+        }));
+    }
+
+    #[test]
+    fn test_rebuild_sourced_modifiers() {
+        let mut state = State::default();
+        let project = state
+            .world
+            .projects
+            .iter()
+            .find(|p| {
+                p.effects.iter().any(|effect| {
+                    effect.feature_modifier().is_some()
+                })
+            })
+            .unwrap()
+            .clone();
+        let modifiers = |state: &State| {
+            state
+                .world
+                .processes
+                .iter()
+                .map(|p| {
+                    (
+                        p.output_modifier,
+                        p.byproduct_modifiers.co2,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let before = modifiers(&state);
+
+        state.world.projects[&project.id].status =
+            Status::Active;
+        state.apply_changes(ProjectChanges {
+            source: project.id,
+            add_effects: project.effects.clone(),
+            ..Default::default()
+        });
+        assert_ne!(modifiers(&state), before);
+
+        // Load it as a save from before the modifiers were keyed.
+        let mut loaded: State = serde_json::from_str(
+            &serde_json::to_string(&state).unwrap(),
+        )
+        .unwrap();
+        for process in loaded.world.processes.iter_mut() {
+            process.sourced_modifiers.clear();
+        }
+        loaded.rebuild_sourced_modifiers();
+        assert_eq!(modifiers(&loaded), modifiers(&state));
+
+        loaded.stop_project(&project.id);
+        assert_eq!(modifiers(&loaded), before);
+        assert!(loaded
+            .world
+            .processes
+            .iter()
+            .all(|p| p.sourced_modifiers.is_empty()));
+    }
+
+    #[test]
+    fn test_advance_step() {
+        let (icon_id, icon) = ICON_EVENTS
+            .iter()
+            .find(|(_, ev)| ev.intensity >= 5)
+            .unwrap();
+        let main_id = Id::new_v4();
+        let event_pool = EventPool::new(
+            vec![
+                Event {
+                    id: main_id,
+                    name: "Test Event A".into(),
+                    effects: vec![Effect::PlayerVariable(
+                        PlayerVariable::PoliticalCapital,
+                        10.,
+                    )],
+                    ..Default::default()
+                },
+                Event {
+                    id: *icon_id,
+                    name: icon.name.clone(),
+                    phase: Phase::Icon,
+                    ..Default::default()
+                },
+            ]
+            .into(),
+        );
+        let mut state = State {
+            event_pool,
+            ..Default::default()
+        };
+
+        // Compare against just stepping the year.
+        let tgav = 1.5;
+        let mut control = state.clone();
+        control.step_year(tgav);
+
+        let step = state.advance_step(tgav);
+        assert!(!step.complete);
+        assert_eq!(state.world.year, control.world.year);
+        assert_eq!(
+            state.world.temperature,
+            tgav + state.world.temperature_modifier
+        );
+
+        // The event happened and was applied.
+        let occurred = |id: &Id| {
+            step.events.iter().filter(|ev| ev.id == *id).count()
+        };
+        assert_eq!(occurred(&main_id), 1);
+        assert_eq!(
+            state.political_capital,
+            control.political_capital + 10
+        );
+        let main = step
+            .events
+            .iter()
+            .find(|ev| ev.id == main_id)
+            .unwrap();
+        assert_eq!(
+            main.changes,
+            vec![EffectChange {
+                variable: "PlayerVariable:PoliticalCapital"
+                    .into(),
+                delta: 10.,
+                region_id: None,
+            }]
+        );
+
+        // As did a disaster in every region.
+        let n_regions = state.world.regions.len();
+        assert_eq!(occurred(icon_id), n_regions);
+        // Matching the game, the habitability change
+        // is the negated, scaled intensity.
+        let change = (icon.intensity as f32
+            * EVENT_INTENSITY_TO_CONTENTEDNESS)
+            .round();
+        for (region, control) in state
+            .world
+            .regions
+            .iter()
+            .zip(control.world.regions.iter())
+        {
+            assert_eq!(
+                region.base_habitability,
+                control.base_habitability + change
+            );
+        }
+
+        // Step through to the end of the turn,
+        // and check it matches advancing all at once.
+        let mut advanced = state.clone();
+        loop {
+            let step = state.advance_step(tgav);
+            if step.complete {
+                break;
+            }
+        }
+        assert!(state.is_planning_year());
+
+        let progress = advanced.advance(|_| tgav);
+        assert!(progress.complete);
+        assert_eq!(state.world.year, advanced.world.year);
+        assert_eq!(
+            state.content_hash(),
+            advanced.content_hash()
+        );
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let state = State::default();
+        let mut other = state.clone();
+        assert_eq!(state.content_hash(), other.content_hash());
+
+        other.world.regions.by_idx_mut(0).outlook += 0.001;
+        assert_ne!(state.content_hash(), other.content_hash());
+
+        // Only the world's content is hashed.
+        let mut other = state.clone();
+        other.event_seeds.push((2022, Phase::WorldMain, 42));
+        other.political_capital += 1;
+        assert_eq!(state.content_hash(), other.content_hash());
+
+        // NaNs hash the same regardless of their bits.
+        let mut a = state.clone();
+        let mut b = state.clone();
+        a.world.temperature = f32::NAN;
+        b.world.temperature = -f32::NAN;
+        assert_eq!(a.content_hash(), b.content_hash());
+        assert_ne!(a.content_hash(), state.content_hash());
+    }
+
+    #[test]
+    fn test_water_stress_outlook() {
+        let mut state = State::default();
+        state.resource_demand.base.water = 100.;
+        state.resource_demand.modifier.water = 0.;
+        state.resource_demand.factor.water = 1.;
+        for region in state.world.regions.iter_mut() {
+            region.outlook = 5.;
+        }
+        let outlook = |state: &State| {
+            state
+                .world
+                .regions
+                .iter()
+                .map(|r| r.outlook)
+                .collect::<Vec<_>>()
+        };
+        let step = |state: &mut State| {
+            state.apply_water_stress();
+            state.world.update_outlook(0., false, false);
+        };
+
+        // High water stress.
+        state.resources.available.water = 50.;
+        let start = outlook(&state);
+        for _ in 0..5 {
+            step(&mut state);
+        }
+        let stressed = outlook(&state);
+        assert_eq!(state.water_stress_years, 5);
+        assert!(stressed
+            .iter()
+            .zip(&start)
+            .all(|(s, o)| s < o));
+
+        // Relief.
+        state.resources.available.water = 400.;
+        for _ in 0..5 {
+            step(&mut state);
+        }
+        let relieved = outlook(&state);
+        assert_eq!(state.water_stress_years, 0);
+        assert!(relieved
+            .iter()
+            .zip(&stressed)
+            .all(|(r, s)| r > s));
+
+        // No loss when the feedback is turned off.
+        state.water_stress_outlook = 0.;
+        state.resources.available.water = 50.;
+        let before = outlook(&state);
+        for _ in 0..5 {
+            step(&mut state);
+        }
+        assert!(outlook(&state)
+            .iter()
+            .zip(&before)
+            .all(|(a, b)| a >= b));
+    }
+
+    #[test]
+    fn test_emissions_by_gas() {
+        let mut state = State::default();
+        state.update_demand();
+        let (co2, ch4, n2o) = state.emissions_by_gas();
+        assert!(co2 > 0. && ch4 > 0. && n2o > 0.);
+
+        let co2eq =
+            co2 + ch4 * CH4_GWP as f64 + n2o * N2O_GWP as f64;
+        let expected = state.emissions.as_gtco2eq() as f64;
+        assert!((co2eq - expected).abs() / expected < 1e-4);
+    }
+
+    #[test]
+    fn test_feature_caps() {
+        let feat = ProcessFeature::CanMeltdown;
+        let feature_output = |state: &State| -> f32 {
+            state
+                .world
+                .processes
+                .iter()
+                .filter(|p| p.features.contains(&feat))
+                .map(|p| state.produced.by_process[&p.id])
+                .sum()
+        };
+
+        let mut state = State::default();
+        state.update_demand();
+        let uncapped = feature_output(&state);
+        assert!(uncapped > 0.);
+
+        let cap = uncapped / 2.;
+        state.feature_caps.insert(feat, cap);
+        state.update_demand();
+        let capped = feature_output(&state);
+        assert!(capped <= cap * 1.001);
+        assert!(capped < uncapped);
+
+        state.feature_caps.remove(&feat);
+        state.update_demand();
+        assert_eq!(feature_output(&state), uncapped);
+    }
+
+    #[test]
+    fn test_feedstock_depletion() {
+        let mut state = State::default();
+        state.feedstocks.available = feedstocks!(
+            oil: 100.,
+            soil: 100.,
+            other: 100.
+        );
+        state.feedstocks.consumed = feedstocks!(
+            oil: 30.,
+            soil: 30.,
+            other: 30.
+        );
+
+        let cond = Condition::FeedstockYears(
+            Feedstock::Oil,
+            Comparator::LessEqual,
+            1.,
+        );
+        let mut prev = state.feedstocks.available.oil;
+        while state.feedstocks.available.oil > 0. {
+            state.deplete_feedstocks();
+            assert!(state.feedstocks.available.oil < prev);
+            prev = state.feedstocks.available.oil;
+        }
+        assert!(cond.eval(&state, None));
+
+        // Renewables aren't drawn down.
+        assert_eq!(state.feedstocks.available.soil, 100.);
+        assert_eq!(state.feedstocks.available.other, 100.);
+
+        let reserves = state.feedstock_reserves();
+        assert!(reserves
+            .iter()
+            .all(|(fs, _, _)| fs.is_finite()));
+        let (_, oil, years) = reserves
+            .iter()
+            .find(|(fs, _, _)| *fs == Feedstock::Oil)
+            .unwrap();
+        assert_eq!(*oil, 0.);
+        assert_eq!(*years, 0.);
+    }
+}
@@ -77,6 +77,14 @@ impl<T: HasId> Collection<T> {
         self.lookup.get(id).map(|idx| &mut self.values[*idx])
     }
 
+    /// Iterate over the values matching the predicate.
+    pub fn filter_by<'a>(
+        &'a self,
+        pred: impl Fn(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a T> {
+        self.values.iter().filter(move |value| pred(value))
+    }
+
     pub fn push_front(&mut self, value: T) {
         self.values.insert(0, value);
         self.reindex();
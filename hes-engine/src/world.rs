@@ -1,19 +1,21 @@
 use std::{collections::BTreeMap, sync::LazyLock};
 
 use crate::{
+    achievements::Achievement,
     events::Event,
     industries::Industry,
     kinds::{FeedstockMap, Output, OutputMap, ResourceMap},
     outputs,
     production::Process,
     projects::Project,
-    regions::{Income, Region},
+    regions::{Income, Latitude, Region},
     round_to,
     Collection,
     Id,
     OutputDemand,
 };
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
 pub static CLIMATES: LazyLock<BTreeMap<String, Vec<[f32; 4]>>> =
     LazyLock::new(|| {
@@ -53,6 +55,9 @@ pub struct World {
 
     pub feedstock_reserves: FeedstockMap,
     pub starting_resources: ResourceMap,
+
+    #[serde(default = "Achievement::defaults")]
+    pub achievements: Vec<Achievement>,
 }
 
 impl Default for World {
@@ -65,6 +70,76 @@ impl Default for World {
 }
 
 impl World {
+    /// A minimal world with `count` generated regions,
+    /// spread across latitudes with varied incomes and populations,
+    /// and no projects, processes, industries, or events.
+    /// The same seed always generates the same world,
+    /// so this is useful as a fixture for tests and simulations.
+    pub fn seed_regions(count: usize, seed: u64) -> World {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let latitudes: Vec<Latitude> =
+            Latitude::iter().collect();
+        let regions: Vec<Region> = (0..count)
+            .map(|i| {
+                let latitude = latitudes[i % latitudes.len()];
+                let mean_temp = match latitude {
+                    Latitude::Tropic => 27.,
+                    Latitude::Subtropic => 20.,
+                    Latitude::Temperate => 12.,
+                    Latitude::Frigid => -2.,
+                };
+                let precip_lo = 20. + rng.f32() * 80.;
+                Region {
+                    id: Id::from_u128(rng.u128(..)),
+                    name: format!("Region {i}"),
+                    population: 1e7 + rng.f32() * 5e8,
+                    income: rng.usize(0..4).into(),
+                    development: rng.f32(),
+                    outlook: 10.,
+                    base_habitability: 10.,
+                    temp_lo: mean_temp - 5. - rng.f32() * 5.,
+                    temp_hi: mean_temp + 5. + rng.f32() * 5.,
+                    precip_lo,
+                    precip_hi: precip_lo
+                        + 50.
+                        + rng.f32() * 100.,
+                    latitude,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        World {
+            year: 2022,
+            base_outlook: 20.,
+            temp_outlook: 0.,
+            extinction_rate: 0.,
+            temperature: 1.1,
+            sea_level_rise: 0.09,
+            temperature_modifier: 0.,
+            population_growth_modifier: 0.,
+            sea_level_rise_modifier: 0.,
+            precipitation: 0.,
+            regions: regions.into(),
+            per_capita_demand: Default::default(),
+            water_by_income: [
+                2040.4095, 4552.624, 5839.793, 11648.184,
+            ],
+            materials_by_income: [
+                2.2531416, 4.3768, 15.43, 25.9541,
+            ],
+            income_pop_coefs: [[0.; 4]; 4],
+            industries: Collection::default(),
+            projects: Collection::default(),
+            processes: Collection::default(),
+            project_lockers: BTreeMap::default(),
+            events: Collection::default(),
+            feedstock_reserves: FeedstockMap::default(),
+            starting_resources: ResourceMap::default(),
+            achievements: vec![],
+        }
+    }
+
     pub fn update_climate(&mut self, tgav: f32) -> f32 {
         let prev_temp = self.temperature;
         self.temperature = tgav + self.temperature_modifier;
@@ -96,6 +171,32 @@ impl World {
         self.base_outlook + self.regions.outlook()
     }
 
+    /// Look up a process, warning if it doesn't exist
+    /// (e.g. because of a corrupted save or bad content).
+    pub fn process(&self, id: &Id) -> Option<&Process> {
+        let process = self.processes.try_get(id);
+        if process.is_none() {
+            tracing::warn!("No process with id {id}");
+        }
+        process
+    }
+
+    /// See `World::process`.
+    pub fn process_mut(
+        &mut self,
+        id: &Id,
+    ) -> Option<&mut Process> {
+        let process = self.processes.try_get_mut(id);
+        if process.is_none() {
+            tracing::warn!("No process with id {id}");
+        }
+        process
+    }
+
+    pub fn population(&self) -> f32 {
+        self.regions.population()
+    }
+
     pub fn update_populations(&mut self) {
         for region in self.regions.iter_mut() {
             region.update_pop(
@@ -151,7 +252,7 @@ impl World {
 
     pub fn lic_population(&self) -> f32 {
         self.regions
-            .iter()
+            .remaining()
             .map(|r| {
                 r.lic_population(&self.materials_by_income)
             })
@@ -159,7 +260,7 @@ impl World {
     }
 
     pub fn region_demand(&self) -> OutputMap {
-        self.regions.iter().fold(
+        self.regions.remaining().fold(
             outputs!(),
             |mut acc, region| {
                 acc += region.demand(&self.per_capita_demand);
@@ -206,8 +307,13 @@ impl Collection<Region> {
         (up, down)
     }
 
+    /// Regions which haven't seceded.
+    pub fn remaining(&self) -> impl Iterator<Item = &Region> {
+        self.iter().filter(|r| !r.seceded)
+    }
+
     pub fn population(&self) -> f32 {
-        self.iter().map(|r| r.population).sum()
+        self.remaining().map(|r| r.population).sum()
     }
 
     fn update_outlook(
@@ -239,16 +345,24 @@ impl Collection<Region> {
         }
     }
 
-    /// Mean outlook of all regions.
+    /// Mean outlook of all regions which haven't seceded.
     pub fn outlook(&self) -> f32 {
-        self.iter().map(|r| r.outlook).sum::<f32>()
-            / self.len() as f32
+        let n = self.remaining().count();
+        if n == 0 {
+            return 0.;
+        }
+        self.remaining().map(|r| r.outlook).sum::<f32>()
+            / n as f32
     }
 
-    /// Mean habitability of all regions.
+    /// Mean habitability of all regions which haven't seceded.
     pub fn habitability(&self) -> f32 {
-        self.iter().map(|r| r.habitability()).sum::<f32>()
-            / self.len() as f32
+        let n = self.remaining().count();
+        if n == 0 {
+            return 0.;
+        }
+        self.remaining().map(|r| r.habitability()).sum::<f32>()
+            / n as f32
     }
 
     /// Mean income level of all regions.
@@ -270,3 +384,42 @@ impl Collection<Region> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_process_lookup() {
+        let mut world = World::default();
+        let id = world.processes.first().id;
+        assert_eq!(world.process(&id).map(|p| p.id), Some(id));
+        assert!(world.process_mut(&id).is_some());
+
+        let missing = Id::new_v4();
+        assert!(world.process(&missing).is_none());
+        assert!(world.process_mut(&missing).is_none());
+    }
+
+    #[test]
+    fn test_seed_regions() {
+        let world = World::seed_regions(8, 42);
+        assert_eq!(world.regions.len(), 8);
+        assert_eq!(world, World::seed_regions(8, 42));
+        assert_ne!(world, World::seed_regions(8, 43));
+
+        // Spread across all latitudes.
+        for latitude in Latitude::iter() {
+            assert_eq!(
+                world
+                    .regions
+                    .iter()
+                    .filter(|r| r.latitude == latitude)
+                    .count(),
+                2
+            );
+        }
+        assert!(world.population() > 0.);
+        assert!(world.lic_population().is_finite());
+    }
+}
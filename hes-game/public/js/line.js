@@ -0,0 +1,110 @@
+const dpr = window.devicePixelRatio || 1;
+
+// https://gist.github.com/nikolas/b0cce2261f1382159b507dd492e1ceef?permalink_comment_id=3947508#gistcomment-3947508
+const lerpColor = function(pFrom, pTo, pRatio) {
+  const ar = (pFrom & 0xFF0000) >> 16,
+        ag = (pFrom & 0x00FF00) >> 8,
+        ab = (pFrom & 0x0000FF),
+
+        br = (pTo & 0xFF0000) >> 16,
+        bg = (pTo & 0x00FF00) >> 8,
+        bb = (pTo & 0x0000FF),
+
+        rr = ar + pRatio * (br - ar),
+        rg = ag + pRatio * (bg - ag),
+        rb = ab + pRatio * (bb - ab);
+
+  return (rr << 16) + (rg << 8) + (rb | 0);
+};
+
+const PADDING = 24;
+const LEGEND_HEIGHT = 16;
+
+class LineChart {
+  constructor(stageEl) {
+    this.stage = stageEl;
+    this.canvas = document.createElement('canvas');
+    this.ctx = this.canvas.getContext('2d');
+    this.setSize();
+    this.stage.appendChild(this.canvas);
+
+    this.reset();
+  }
+
+  setSize() {
+    this.width = this.stage.clientWidth;
+    this.height = this.stage.clientHeight;
+    this.canvas.width = this.width * dpr;
+    this.canvas.height = this.height * dpr;
+    this.canvas.style.width = `${this.width}px`;
+    this.canvas.style.height = `${this.height}px`;
+    this.ctx.scale(dpr, dpr);
+  }
+
+  reset() {
+    this.ctx.clearRect(0, 0, this.width, this.height);
+
+    this.ctx.font = '12px "W95FA"';
+  }
+
+  // Data should be in the format of [[a, b, c], ...],
+  // one entry per turn, with each series stacked on the previous.
+  render(data, labels, colors) {
+    this.reset();
+    if (data.length === 0) return;
+
+    let top = LEGEND_HEIGHT + PADDING/2;
+    let bottom = this.height - PADDING;
+    let left = PADDING;
+    let right = this.width - PADDING/2;
+
+    let totals = data.map((vals) => vals.reduce((acc, v) => acc + Math.max(v, 0), 0));
+    let max = Math.max(...totals) || 1;
+    let x = (i) => left + (data.length > 1 ? i/(data.length - 1) : 0.5) * (right - left);
+    let y = (v) => bottom - (v/max) * (bottom - top);
+
+    // Axes
+    this.ctx.strokeStyle = '#000000';
+    this.ctx.beginPath();
+    this.ctx.moveTo(left, top);
+    this.ctx.lineTo(left, bottom);
+    this.ctx.lineTo(right, bottom);
+    this.ctx.stroke();
+
+    // Draw each series as a band over the previous ones
+    let baseline = data.map(() => 0);
+    labels.forEach((label, s) => {
+      let color = lerpColor(colors[0], colors[1], labels.length > 1 ? s/(labels.length - 1) : 0);
+      let upper = data.map((vals, i) => baseline[i] + Math.max(vals[s], 0));
+
+      this.ctx.beginPath();
+      upper.forEach((v, i) => {
+        if (i === 0) {
+          this.ctx.moveTo(x(i), y(v));
+        } else {
+          this.ctx.lineTo(x(i), y(v));
+        }
+      });
+      for (let i = baseline.length - 1; i >= 0; i--) {
+        this.ctx.lineTo(x(i), y(baseline[i]));
+      }
+      this.ctx.closePath();
+      this.ctx.fillStyle = `#${color.toString(16).padStart(6, '0')}`;
+      this.ctx.fill();
+
+      // Legend
+      let legendX = left + s * (right - left)/labels.length;
+      this.ctx.fillRect(legendX, 2, 10, 10);
+      this.ctx.fillStyle = '#000000';
+      this.ctx.fillText(label, legendX + 14, 11);
+
+      baseline = upper;
+    });
+
+    // Max value label
+    this.ctx.fillStyle = '#000000';
+    this.ctx.fillText(max.toFixed(1), left + 4, top + 10);
+  }
+}
+
+export { LineChart };
@@ -56,6 +56,18 @@ class PieChart {
       }
     })
 
+    this.canvas.addEventListener('click', (ev) => {
+      if (this.radius && this.labels && this.sliceClickCallback) {
+        let bounds = ev.target.getBoundingClientRect();
+        let x = ev.clientX - bounds.left;
+        let y = ev.clientY - bounds.top;
+        let label = this.labelAtPoint(x, y);
+        if (label) {
+          this.sliceClickCallback(label.label);
+        }
+      }
+    });
+
     this.ctx = this.canvas.getContext('2d');
     this.setSize();
     this.stage.appendChild(this.canvas);
@@ -161,6 +173,11 @@ class PieChart {
     this.radius = radius;
   }
 
+  // Called with the slice's label when a slice is clicked
+  onSliceClick(callback) {
+    this.sliceClickCallback = callback;
+  }
+
   labelAtPoint(x, y) {
     let center = {x: this.width/2, y: this.height/2};
     x -= center.x;
@@ -1,4 +1,4 @@
-use crate::vars::Impact;
+use crate::vars::{Impact, Var};
 use hes_engine::*;
 
 pub trait DisplayValue {
@@ -129,6 +129,29 @@ pub fn outputs(outputs: &OutputMap) -> OutputMap {
     }
 }
 
+impl Var {
+    /// Format an amount of this variable with its units.
+    /// The amount should already be in display units, i.e.
+    /// percent of available land/water, PWh of energy,
+    /// Gt CO2eq of emissions, and calorie units for calories.
+    pub fn format_amount(&self, amount: f32) -> String {
+        match self {
+            Var::Land | Var::Water => {
+                format!("{}%", percent(amount / 100., true))
+            }
+            Var::Energy | Var::Electricity | Var::Fuel => {
+                format!("{}PWh", amount.round())
+            }
+            Var::Emissions => emissions(amount),
+            Var::PlantCalories | Var::AnimalCalories => {
+                format!("{}", amount.round())
+            }
+            Var::Biodiversity => format!("{:.0}", amount),
+            Var::Contentedness => format!("{:.1}", amount),
+        }
+    }
+}
+
 pub fn land_use_percent(m2: f32, available: f32) -> f32 {
     m2 / available * 100.
 }
@@ -277,3 +300,35 @@ impl FloatExt for f32 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(Var::Land.format_amount(42.4), "42%");
+        assert_eq!(Var::Land.format_amount(0.2), "<1%");
+        assert_eq!(Var::Water.format_amount(101.), "101%");
+        assert_eq!(Var::Energy.format_amount(12.6), "13PWh");
+        assert_eq!(Var::Electricity.format_amount(4.), "4PWh");
+        assert_eq!(Var::Fuel.format_amount(7.2), "7PWh");
+        assert_eq!(
+            Var::Emissions.format_amount(51.62),
+            "51.6Gt"
+        );
+        assert_eq!(
+            Var::PlantCalories.format_amount(115.4),
+            "115"
+        );
+        assert_eq!(
+            Var::AnimalCalories.format_amount(25.5),
+            "26"
+        );
+        assert_eq!(Var::Biodiversity.format_amount(60.4), "60");
+        assert_eq!(
+            Var::Contentedness.format_amount(12.34),
+            "12.3"
+        );
+    }
+}
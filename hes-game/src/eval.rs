@@ -102,13 +102,8 @@ impl Badge {
                 state
                     .world
                     .projects
-                    .iter()
-                    .map(|proj| {
-                        proj.group == Group::Space
-                            && (proj.status == Status::Finished
-                                || proj.status
-                                    == Status::Active)
-                    })
+                    .in_group(Group::Space)
+                    .filter(|proj| proj.is_online())
                     .count()
                     >= 3
             }
@@ -342,7 +342,8 @@ impl HasIcon for Condition {
             Condition::ResourcePressure(resource, ..) => {
                 resource.icon()
             }
-            Condition::ProcessMixShareFeature(feat, ..) => {
+            Condition::ProcessMixShareFeature(feat, ..)
+            | Condition::OutputMixShareFeature(_, feat, ..) => {
                 feat.icon()
             }
             Condition::FeedstockYears(feedstock, ..) => {
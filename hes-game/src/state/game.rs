@@ -3,6 +3,7 @@ use crate::{
     consts,
     debug::get_debug_opts,
     display,
+    vars::Var,
     views::DisplayEvent,
 };
 use enum_map::EnumMap;
@@ -20,15 +21,13 @@ pub impl State {
             + (self.protected_land
                 * self.world.starting_resources.land);
         let total_land = self.world.starting_resources.land;
-        let percent = usage / total_land;
-        format!("{}%", display::percent(percent, true))
+        Var::Land.format_amount(display::land_use_percent(
+            usage, total_land,
+        ))
     }
 
-    fn water_use_percent(&self) -> String {
-        let usage = self.resource_demand.of(Resource::Water);
-        let total_water = self.resources.available.water;
-        let percent = usage / total_water;
-        format!("{}%", display::percent(percent, true))
+    fn water_use_label(&self) -> String {
+        Var::Water.format_amount(self.water_use_percent())
     }
 
     fn temp_anomaly(&self) -> String {
@@ -37,7 +36,8 @@ pub impl State {
 
     fn energy_pwh(&self) -> String {
         let energy = self.output_demand.total().energy();
-        format!("{}PWh", display::pwh(energy).round())
+        Var::Energy
+            .format_amount(display::to_energy_units(energy))
     }
 
     fn energy_twh(&self) -> String {
@@ -67,7 +67,7 @@ pub impl State {
             if self.flags.contains(&Flag::HyperResearch) {
                 discount += 1;
             }
-            if self.npcs.is_ally("The Accelerationist") {
+            if self.npcs.is_ally(NPCKind::Accelerationist) {
                 discount += 1;
             }
         }
@@ -195,18 +195,22 @@ pub impl State {
         is_free: bool,
         queued_upgrades: &mut BTreeMap<Id, bool>,
     ) -> bool {
-        let (kind, upgrade) = {
+        let (kind, cost, can_upgrade) = {
             let project = &self.world.projects[project_id];
-            (project.kind, project.next_upgrade())
+            let can_upgrade = if is_free {
+                project.next_upgrade().is_some()
+            } else {
+                project.can_upgrade(self.political_capital)
+            };
+            (
+                project.kind,
+                project.next_upgrade().map_or(0, |u| u.cost),
+                can_upgrade,
+            )
         };
-        if let Some(upgrade) = upgrade {
-            let available = self.political_capital;
-            if is_free || available >= upgrade.cost as isize {
-                if !is_free {
-                    self.change_political_capital(
-                        -(upgrade.cost as isize),
-                    );
-                }
+        if can_upgrade {
+            if !is_free {
+                self.change_political_capital(-(cost as isize));
             }
 
             match kind {
@@ -229,15 +233,17 @@ pub impl State {
         project_id: &Id,
         queued_upgrades: &mut BTreeMap<Id, bool>,
     ) {
-        let (kind, prev_upgrade) = {
+        let (kind, can_downgrade, refund) = {
             let project = &self.world.projects[project_id];
-            (project.kind, project.prev_upgrade())
+            (
+                project.kind,
+                project.prev_upgrade().is_some(),
+                project.refund_for_downgrade(),
+            )
         };
 
-        if let Some(upgrade) = prev_upgrade {
-            self.change_political_capital(
-                upgrade.cost as isize,
-            );
+        if can_downgrade {
+            self.change_political_capital(refund as isize);
             if kind == ProjectType::Policy {
                 self.downgrade_project(project_id);
             } else {
@@ -250,16 +256,31 @@ pub impl State {
         &mut self,
         phase: EventPhase,
     ) -> Vec<DisplayEvent> {
+        StateExt::roll_events_with_deferred(self, phase).0
+    }
+
+    /// Roll events for the phase, along with the names of
+    /// queued events which were pushed back to the next year.
+    fn roll_events_with_deferred(
+        &mut self,
+        phase: EventPhase,
+    ) -> (Vec<DisplayEvent>, Vec<String>) {
         if get_debug_opts().skip_events {
-            vec![]
+            (vec![], vec![])
         } else {
-            let events = self
-                .roll_events(phase)
+            let (events, deferred) = self.roll_events(phase);
+            let events = events
                 .into_iter()
                 .map(|ev| DisplayEvent::new(ev, &self))
                 .collect();
+            let deferred = deferred
+                .iter()
+                .map(|(_, id, _)| {
+                    self.event_pool.events[id].name.clone()
+                })
+                .collect();
             update_factors(&self);
-            events
+            (events, deferred)
         }
     }
 
@@ -281,6 +302,7 @@ pub impl State {
         event: &IconEvent,
         event_id: &Id,
         region_id: &Id,
+        severity: EventSeverity,
     ) {
         let effect = event.intensity as f32
             * consts::EVENT_INTENSITY_TO_CONTENTEDNESS;
@@ -289,14 +311,24 @@ pub impl State {
             -effect.round() as isize,
             region_id,
         );
-        self.apply_event(*event_id, Some(*region_id));
+        self.apply_event(*event_id, Some(*region_id), severity);
     }
 
     fn update_processes(
         &mut self,
         changes: &mut EnumMap<Output, BTreeMap<Id, isize>>,
     ) {
-        for (_output, changes) in changes.iter_mut() {
+        for (output, changes) in changes.iter_mut() {
+            if let Err(err) =
+                self.validate_process_mix(output, changes)
+            {
+                tracing::warn!(
+                    "Discarding {output} mix changes: {err}"
+                );
+                changes.clear();
+                continue;
+            }
+
             let mut rem_pts = consts::PROCESS_POINTS_PER_CYCLE;
             let mut add_pts = consts::PROCESS_POINTS_PER_CYCLE;
             let mut total = changes
@@ -60,9 +60,12 @@ fn read_save() -> Result<Option<(State, UIState)>, anyhow::Error>
             .get_item(SAVE_KEY)
             .unwrap()
             .map(|ser| {
-                Ok(serde_json::from_str::<(State, UIState)>(
-                    &ser,
-                )?)
+                let (mut game, ui) = serde_json::from_str::<(
+                    State,
+                    serde_json::Value,
+                )>(&ser)?;
+                game.rebuild_sourced_modifiers();
+                Ok((game, UIState::migrate(ui)?))
             })
             .transpose()
     } else {
@@ -11,7 +11,13 @@ use hes_engine::{
     State,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+/// The current version of the `UIState` save format.
+/// Bump this and add a step to `UIState::migrate`
+/// when making breaking changes.
+pub const UI_STATE_VERSION: u32 = 1;
 
 /// The state at the start of a 5-year cycle,
 /// for generating comparisons for the report.
@@ -29,6 +35,25 @@ pub struct CycleStart {
     pub completed_projects: Vec<Id>,
 }
 
+/// What changed over a 5-year cycle,
+/// relative to its `CycleStart`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CycleReport {
+    pub emissions: f32,
+    pub extinction_rate: f32,
+    pub contentedness: f32,
+    pub temperature: f32,
+
+    /// Regions whose income level changed,
+    /// with their new income level.
+    pub region_incomes: Vec<(String, Income)>,
+
+    /// NPC factions whose seats changed,
+    /// as `(name, seats, change)`.
+    pub seat_changes: Vec<(String, f32, f32)>,
+    pub completed_projects: Vec<Id>,
+}
+
 /// Currently staged plan changes.
 #[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlanChange {
@@ -87,6 +112,7 @@ pub enum Tutorial {
     ProcessesBack,
     Parliament,
     Dashboard,
+    DashboardBreakdown,
     Regions,
     Plan,
     Ready,
@@ -97,11 +123,29 @@ impl Tutorial {
             *self = next;
         }
     }
+
+    /// Go back to the start of the tutorial.
+    pub fn reset(&mut self) {
+        if let Some(first) = Tutorial::first() {
+            *self = first;
+        }
+    }
+
+    /// Jump to a specific step, e.g. `Tutorial::Ready`
+    /// to skip the rest of the tutorial.
+    pub fn goto(&mut self, step: Tutorial) {
+        *self = step;
+    }
 }
 
 /// Transient UI-state that is not preserved b/w sessions.
-#[derive(Default, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct UIState {
+    /// Save format version; saves from before
+    /// versioning was added are version 0.
+    #[serde(default)]
+    pub version: u32,
+
     pub phase: Phase,
     pub start_year: usize,
     pub tutorial_restarted: bool,
@@ -121,6 +165,11 @@ pub struct UIState {
     #[serde(default)]
     pub session_start_state: State,
 
+    /// Emissions for each year by gas, as
+    /// (CO2, CH4, N2O) in gigatonnes of that gas.
+    #[serde(default)]
+    pub emissions_history: Vec<(f64, f64, f64)>,
+
     // Track planned process mix changes
     pub process_mix_changes:
         EnumMap<Output, BTreeMap<Id, isize>>,
@@ -138,9 +187,67 @@ pub struct UIState {
 
     /// Viewed project and process ids,
     /// so we can keep track of which ones are new
-    pub viewed: Vec<Id>,
+    pub viewed: HashSet<Id>,
+}
+impl Default for UIState {
+    fn default() -> Self {
+        Self {
+            version: UI_STATE_VERSION,
+            phase: Phase::default(),
+            start_year: 0,
+            tutorial_restarted: false,
+            tutorial: Tutorial::default(),
+            annual_region_events: BTreeMap::default(),
+            world_events: vec![],
+            change_history: vec![],
+            process_mix_history: vec![],
+            session_start_state: State::default(),
+            emissions_history: vec![],
+            process_mix_changes: EnumMap::default(),
+            plan_changes: BTreeMap::default(),
+            queued_upgrades: BTreeMap::default(),
+            cycle_start_state: CycleStart::default(),
+            points: Points::default(),
+            viewed: HashSet::default(),
+        }
+    }
 }
 impl UIState {
+    /// Mark a project or process as viewed, so it's no longer new.
+    /// Returns false if it was already viewed.
+    pub fn mark_viewed(&mut self, id: Id) -> bool {
+        self.viewed.insert(id)
+    }
+
+    pub fn is_viewed(&self, id: &Id) -> bool {
+        self.viewed.contains(id)
+    }
+
+    /// Load a serialized `UIState`, upgrading
+    /// older save formats to the current version.
+    pub fn migrate(
+        json: Value,
+    ) -> Result<UIState, anyhow::Error> {
+        let version = json
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32;
+        if version > UI_STATE_VERSION {
+            anyhow::bail!(
+                "Save version {version} is newer than supported version {UI_STATE_VERSION}"
+            );
+        }
+
+        // Version 0 saves load as they are: the fields added
+        // since have defaults, and the engine reads projects'
+        // older single `active_outcome` itself.
+        // Migrations for later versions go here.
+
+        let mut ui: UIState = serde_json::from_value(json)?;
+        ui.version = UI_STATE_VERSION;
+        Ok(ui)
+    }
+
     pub fn cycle_start_snapshot(&mut self, state: &State) {
         self.annual_region_events.clear();
         self.world_events.clear();
@@ -164,6 +271,45 @@ impl UIState {
         self.cycle_start_state.completed_projects.clear();
     }
 
+    /// Compare the current state against
+    /// the snapshot from the start of the cycle.
+    pub fn cycle_report(&self, state: &State) -> CycleReport {
+        let start = &self.cycle_start_state;
+        let region_incomes = state
+            .world
+            .regions
+            .iter()
+            .zip(start.region_incomes.iter())
+            .filter(|(reg, inc)| reg.income != **inc)
+            .map(|(reg, _)| (reg.name.clone(), reg.income))
+            .collect();
+        let seat_changes = state
+            .npcs
+            .iter()
+            .zip(start.parliament.iter())
+            .map(|(npc, start_seats)| {
+                let change = (npc.seats - start_seats).round();
+                (npc.name.clone(), npc.seats, change)
+            })
+            .filter(|(_, _, change)| *change != 0.)
+            .collect();
+        CycleReport {
+            emissions: state.emissions.as_gtco2eq()
+                - start.emissions,
+            extinction_rate: state.world.extinction_rate
+                - start.extinction_rate,
+            contentedness: state.outlook()
+                - start.contentedness,
+            temperature: state.world.temperature
+                - start.temperature,
+            region_incomes,
+            seat_changes,
+            completed_projects: start
+                .completed_projects
+                .clone(),
+        }
+    }
+
     pub fn has_process_mix_changes(
         &self,
         output: Output,
@@ -173,6 +319,42 @@ impl UIState {
             .any(|(_, change)| *change != 0)
     }
 
+    /// Total number of mix points being moved around
+    /// for an output, counting both removals and additions.
+    pub fn total_allocated(&self, output: Output) -> isize {
+        self.process_mix_changes[output]
+            .values()
+            .map(|change| change.abs())
+            .sum()
+    }
+
+    /// Discard all staged mix changes for an output.
+    pub fn clear_output(&mut self, output: Output) {
+        self.process_mix_changes[output].clear();
+    }
+
+    /// The mix each process will have (as a fraction
+    /// of its output) once the staged changes are applied.
+    pub fn committed_mix(
+        &self,
+        state: &State,
+    ) -> EnumMap<Output, BTreeMap<Id, f32>> {
+        let mut mix: EnumMap<Output, BTreeMap<Id, f32>> =
+            EnumMap::default();
+        for process in state.world.processes.iter() {
+            let change = self.process_mix_changes
+                [process.output]
+                .get(&process.id)
+                .copied()
+                .unwrap_or(0);
+            let share =
+                (process.mix_share as isize + change).max(0);
+            mix[process.output]
+                .insert(process.id, share as f32 * 0.05);
+        }
+        mix
+    }
+
     pub fn remove_point(
         &mut self,
         points: &mut isize,
@@ -234,3 +416,206 @@ pub fn format_year_log(
     ]
     .join("\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tutorial_dashboard_breakdown() {
+        let mut tutorial = Tutorial::Dashboard;
+        tutorial.advance();
+        assert_eq!(tutorial, Tutorial::DashboardBreakdown);
+        assert!(tutorial < Tutorial::Regions);
+        tutorial.advance();
+        assert_eq!(tutorial, Tutorial::Regions);
+
+        // Skipping the tutorial passes over it.
+        let tutorial = Tutorial::Ready;
+        assert!(tutorial > Tutorial::DashboardBreakdown);
+    }
+
+    #[test]
+    fn test_cycle_report() {
+        let mut state = State::default();
+        let mut ui = UIState::default();
+        ui.cycle_start_snapshot(&state);
+
+        let report = ui.cycle_report(&state);
+        assert_eq!(report, CycleReport::default());
+
+        state.world.temperature += 0.5;
+        state.world.extinction_rate -= 10.;
+        let region = state.world.regions.by_idx_mut(0);
+        region.income = match region.income {
+            Income::High => Income::Low,
+            _ => Income::High,
+        };
+        let (name, income) =
+            (region.name.clone(), region.income);
+        let npc = state.npcs.by_idx_mut(1);
+        npc.seats += 3.2;
+        let (npc_name, seats) = (npc.name.clone(), npc.seats);
+        let project_id = state.world.projects.by_idx(0).id;
+        ui.cycle_start_state
+            .completed_projects
+            .push(project_id);
+
+        let report = ui.cycle_report(&state);
+        assert!((report.temperature - 0.5).abs() < 1e-4);
+        assert!((report.extinction_rate + 10.).abs() < 1e-4);
+        assert_eq!(report.emissions, 0.);
+        assert_eq!(report.region_incomes, vec![(name, income)]);
+        assert_eq!(
+            report.seat_changes,
+            vec![(npc_name, seats, 3.)]
+        );
+        assert_eq!(report.completed_projects, vec![project_id]);
+    }
+
+    #[test]
+    fn test_committed_mix() {
+        let state = State::default();
+        let mut ui = UIState::default();
+        let output = Output::Electricity;
+        let mut processes =
+            state.world.processes.iter().filter(|p| {
+                p.output == output && p.mix_share > 0
+            });
+        let a = processes.next().unwrap();
+        let b = processes.next().unwrap();
+
+        let mix = ui.committed_mix(&state);
+        assert_eq!(mix[output][&a.id], a.mix_percent());
+        assert!(!ui.has_process_mix_changes(output));
+
+        ui.process_mix_changes[output].insert(a.id, -1);
+        ui.process_mix_changes[output].insert(b.id, 1);
+        assert_eq!(ui.total_allocated(output), 2);
+        assert_eq!(ui.total_allocated(Output::Fuel), 0);
+
+        let mix = ui.committed_mix(&state);
+        assert_eq!(
+            mix[output][&a.id],
+            (a.mix_share - 1) as f32 * 0.05
+        );
+        assert_eq!(
+            mix[output][&b.id],
+            (b.mix_share + 1) as f32 * 0.05
+        );
+
+        // Committed shares still add up to the whole output.
+        let total: f32 = mix[output].values().sum();
+        assert!((total - 1.).abs() < 1e-4);
+
+        ui.clear_output(output);
+        assert!(!ui.has_process_mix_changes(output));
+        assert_eq!(ui.total_allocated(output), 0);
+        let mix = ui.committed_mix(&state);
+        assert_eq!(mix[output][&b.id], b.mix_percent());
+    }
+
+    #[test]
+    fn test_mark_viewed() {
+        let mut ui = UIState::default();
+        let id = Id::new_v4();
+        assert!(!ui.is_viewed(&id));
+        assert!(ui.mark_viewed(id));
+        assert!(!ui.mark_viewed(id));
+        assert!(ui.is_viewed(&id));
+        assert_eq!(ui.viewed.len(), 1);
+
+        // Older saves stored a list, which may have duplicates.
+        let viewed: HashSet<Id> =
+            serde_json::from_value(json!([id, id])).unwrap();
+        assert_eq!(viewed.len(), 1);
+    }
+
+    #[test]
+    fn test_tutorial_reset() {
+        let mut tutorial = Tutorial::Regions;
+        tutorial.reset();
+        assert_eq!(tutorial, Tutorial::Projects);
+        assert_eq!(tutorial, Tutorial::default());
+
+        // Resetting at the start stays there.
+        tutorial.reset();
+        assert_eq!(tutorial, Tutorial::Projects);
+    }
+
+    #[test]
+    fn test_tutorial_goto() {
+        let mut tutorial = Tutorial::Projects;
+        tutorial.goto(Tutorial::Parliament);
+        assert_eq!(tutorial, Tutorial::Parliament);
+        tutorial.advance();
+        assert_eq!(tutorial, Tutorial::Dashboard);
+
+        // Backwards too
+        tutorial.goto(Tutorial::ProjectsBack);
+        assert_eq!(tutorial, Tutorial::ProjectsBack);
+
+        // Every step can be jumped to
+        for step in enum_iterator::all::<Tutorial>() {
+            tutorial.goto(step);
+            assert_eq!(tutorial, step);
+        }
+
+        // Ready is the last step.
+        tutorial.goto(Tutorial::Ready);
+        tutorial.advance();
+        assert_eq!(tutorial, Tutorial::Ready);
+    }
+
+    #[test]
+    fn test_migrate_v0() {
+        // Build a v0 save from the current format, i.e.
+        // without the fields added since and with projects'
+        // single `active_outcome`.
+        let mut json =
+            serde_json::to_value(UIState::default()).unwrap();
+        let obj = json.as_object_mut().unwrap();
+        obj.remove("version");
+        obj.remove("emissions_history");
+
+        let projects = json
+            .pointer_mut("/session_start_state/world/projects")
+            .unwrap()
+            .as_array_mut()
+            .unwrap();
+        for (i, project) in projects.iter_mut().enumerate() {
+            let project = project.as_object_mut().unwrap();
+            project.remove("active_outcomes");
+            let outcome =
+                if i == 0 { json!(0) } else { Value::Null };
+            project.insert("active_outcome".into(), outcome);
+        }
+
+        let ui = UIState::migrate(json).unwrap();
+        assert_eq!(ui.version, UI_STATE_VERSION);
+        let projects: Vec<_> = ui
+            .session_start_state
+            .world
+            .projects
+            .iter()
+            .collect();
+        assert_eq!(projects[0].active_outcomes, vec![0]);
+        assert!(projects[1].active_outcomes.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_current() {
+        let ui = UIState::default();
+        let json = serde_json::to_value(&ui).unwrap();
+        assert!(UIState::migrate(json).unwrap() == ui);
+    }
+
+    #[test]
+    fn test_migrate_newer_version() {
+        let mut json =
+            serde_json::to_value(UIState::default()).unwrap();
+        json["version"] = json!(UI_STATE_VERSION + 1);
+        assert!(UIState::migrate(json).is_err());
+    }
+}
@@ -43,6 +43,10 @@ pub fn EventCard(
             .collect::<Vec<_>>();
             (arc, name, factors_list)
         });
+        let severity = with!(|event| {
+            let severity: &'static str = event.severity.into();
+            severity.to_lowercase()
+        });
         let show_effects = move || with!(|event| event.has_visible_effects());
         let effects = move || with!(|event| event.effects.clone());
         let background = move || {
@@ -60,6 +64,7 @@ pub fn EventCard(
         view! {
             <div
                 class="event--body"
+                data-severity=severity
                 style:background-image={background}
             >
                 <Help text={factor_tip.get_value()} x=0.55 y=-18.0 center=false/>
@@ -64,13 +64,12 @@ pub fn ProcessCard(
     let feedstock_estimate = move || {
         with!(|process, feedstocks| {
             let feedstock = process.feedstock.0;
-            match feedstock {
-                Feedstock::Soil | Feedstock::Other => None,
-                _ => {
-                    let estimate =
-                        feedstocks.until_exhaustion(feedstock);
-                    Some(estimate.round())
-                }
+            if feedstock.is_finite() {
+                let estimate =
+                    feedstocks.until_exhaustion(feedstock);
+                Some(estimate.round())
+            } else {
+                None
             }
         })
     };
@@ -417,7 +416,7 @@ pub fn ProcessCard(
                 Impact::Water,
                 process.output.into(),
             );
-            let percent = game.water_use_percent();
+            let percent = game.water_use_label();
             let tip = tip(icons::WATER, t!("Water: The giver of life. You're using {percent} of water resources.", percent: percent))
         .card(factors_card(Some(process.name.clone()), Var::Water, game));
             let (sig, _) = create_signal(int);
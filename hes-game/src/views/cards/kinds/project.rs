@@ -105,7 +105,8 @@ pub fn ProjectCard(
                         t!("1 planning cycle left")
                     }
                     _ => {
-                        let years = project.years_remaining();
+                        let years =
+                            project.estimate_remaining();
                         t!("{years} yrs left", years: years)
                     }
                 }
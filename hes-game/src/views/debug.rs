@@ -3,6 +3,7 @@ use crate::{
     views::{DisplayEvent, Events, Updates},
 };
 use hes_engine::{
+    EventSeverity,
     ProjectType,
     ResolvedEvent,
     State,
@@ -31,7 +32,9 @@ pub fn DebugEvents() -> impl IntoView {
                     Some(region.clone())
                 } else {
                     None
-                }
+                },
+                severity: EventSeverity::default(),
+                changes: vec![],
             };
 
             view! {
@@ -90,7 +93,7 @@ pub fn DebugEvents() -> impl IntoView {
                         };
                         update!(|game| {
                             game.world.projects[&id].status = Status::Active;
-                            game.world.projects[&id].active_outcome = Some(0);
+                            game.world.projects[&id].active_outcomes = vec![0];
                         });
                         update!(|updates| {
                             updates.push(update);
@@ -113,7 +116,7 @@ pub fn DebugEvents() -> impl IntoView {
                             };
                             update!(|game| {
                                 game.world.projects[&id].status = Status::Active;
-                                game.world.projects[&id].active_outcome = Some(i);
+                                game.world.projects[&id].active_outcomes = vec![i];
                             });
                             update!(|updates| {
                                 updates.push(update);
@@ -500,6 +500,15 @@ impl DisplayEffect {
                 );
                 (tip(icons::ALERT, text.clone()), text)
             }
+            Effect::ModifyProcessLimitPercent(id, amount) => {
+                let process = &state.world.processes[id];
+                let text = t!("{changeDir} maximum output for {process} by <strong>{amount}%</strong>.",
+                amount: display::percent(amount.abs(), true),
+                process: t!(&process.name),
+                changeDir: self.change_dir(*amount),
+                );
+                (tip(icons::ALERT, text.clone()), text)
+            }
             Effect::RegionHabitability(lat, amount) => (
                 tip! {
                     icons::HABITABILITY,
@@ -513,6 +522,22 @@ impl DisplayEffect {
                     type: t!(lat.lower()),
                 },
             ),
+            Effect::RegionHabitabilityById(id, amount) => {
+                let region = &state.world.regions[id];
+                (
+                    tip! {
+                        icons::HABITABILITY,
+                        "Lower habitability means unhappier people who may need to migrate to more hospitable locales.",
+                    },
+                    text! {
+                        "habitability",
+                        "{changeDir} habitability in {region} by {amount}.",
+                        changeDir: self.change_dir(*amount),
+                        amount: self.fmt_param(*amount),
+                        region: t!(&region.name),
+                    },
+                )
+            }
             Effect::Resource(resource, amount) => {
                 let fmtted = display::resource(
                     *amount,
@@ -1308,6 +1333,17 @@ impl DisplayEffect {
                 );
                 (tip, text)
             }
+            Effect::RemoveFlag(flag) => {
+                let tip = flag_tip(
+                    *flag,
+                    &state.output_demand.total(),
+                );
+                let text = t!(
+                    "No longer in effect: <strong>{flag}</strong>",
+                    flag: t!(&flag.to_string()),
+                );
+                (tip, text)
+            }
             Effect::ProtectLand(amount) => {
                 let before = state.protected_land;
                 let after = state.protected_land + amount;
@@ -1386,11 +1422,7 @@ impl DisplayEffect {
             }
             Effect::TerminationShock => {
                 if let Some(project) =
-                    state.world.projects.iter().find(|p| {
-                        p.name.contains(
-                            "Solar Radiation Management",
-                        )
-                    })
+                    state.world.projects.iter().find(|p| p.is_srm())
                 {
                     let effects = project.active_effects();
                     let mut temp_change = 0.;
@@ -1423,6 +1455,62 @@ impl DisplayEffect {
                     return Err(());
                 }
             }
+            Effect::NPCSeats(id, amount) => {
+                let npc = &state.npcs[id];
+                (
+                    tip! {
+                        icons::POLITICAL_CAPITAL,
+                        "{name} currently holds {seats}% of seats in parliament.",
+                        name: t!(&npc.name),
+                        seats: display::percent(npc.seats, true),
+                    },
+                    text! {
+                        "political_capital",
+                        "{changeDir} the seats held by {name} by {amount}%.",
+                        changeDir: self.change_dir(*amount),
+                        name: t!(&npc.name),
+                        amount: display::percent(amount.abs(), true),
+                    },
+                )
+            }
+            Effect::Temporary(effect, years) => {
+                let inner = DisplayEffect {
+                    effect: effect.as_ref().clone(),
+                    ..self.clone()
+                };
+                let EffectTip { tip, text } = inner.tip(state)?;
+                let text = format!(
+                    "{text} {}",
+                    t!("(Lasts {years} years.)", years: years)
+                );
+                (tip, text)
+            }
+            Effect::All(effects) => {
+                // Use the first describable effect's tip
+                // and combine all of the texts.
+                let tips = effects
+                    .iter()
+                    .filter_map(|effect| {
+                        DisplayEffect {
+                            effect: effect.clone(),
+                            ..self.clone()
+                        }
+                        .tip(state)
+                        .ok()
+                    })
+                    .collect::<Vec<_>>();
+                let text = tips
+                    .iter()
+                    .map(|tip| tip.text.clone())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let tip = tips
+                    .into_iter()
+                    .next()
+                    .map(|tip| tip.tip)
+                    .ok_or(())?;
+                (tip, text)
+            }
             Effect::AddEvent(..)
             | Effect::TriggerEvent(..)
             | Effect::RegionLeave
@@ -98,9 +98,9 @@ pub fn active_effects(project: &Project) -> Vec<DisplayEffect> {
                 .iter()
                 .map(DisplayEffect::from),
         );
-        if let Some(id) = project.active_outcome {
+        for id in &project.active_outcomes {
             effects.extend(
-                project.outcomes[id]
+                project.outcomes[*id]
                     .effects
                     .iter()
                     .map(DisplayEffect::from),
@@ -93,7 +93,8 @@ fn describe_condition(
             let name = &state.npcs[id].name;
             Some(t!("This event can occur if {name} is your {relType}.", name: t!(name), relType: t!(&rel_type.to_string())))
         }
-        Condition::ProcessMixShareFeature(feat, _, _) => {
+        Condition::ProcessMixShareFeature(feat, _, _)
+        | Condition::OutputMixShareFeature(_, feat, _, _) => {
             match feat {
                 ProcessFeature::IsCCS => Some(t!("This event is influenced by how much production involves carbon capture and storage.")),
                 ProcessFeature::CanMeltdown => Some(t!("This event is influenced by how much energy production can meltdown.")),
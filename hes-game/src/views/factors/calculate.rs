@@ -184,6 +184,89 @@ impl Factor {
             Self::Event { amount, .. } => *amount,
         }
     }
+
+    /// This factor's amount in the display units of `var`
+    /// (see `Var::format_amount`), as the different kinds
+    /// of factors don't all keep their amounts in the same units.
+    pub fn amount_in_units(
+        &self,
+        var: Var,
+        state: &State,
+    ) -> f32 {
+        let amount = self.amount();
+        let starting_land = state.world.starting_resources.land;
+        let available_water = state.resources.available.water;
+        match self {
+            // Already converted for display.
+            Self::Region { .. } => amount,
+
+            // The nature preserves (see `rank`) are in m2.
+            Self::Event { .. } if var == Var::Land => {
+                display::land_use_percent(amount, starting_land)
+            }
+
+            // Effects' amounts (see `effects_factor`).
+            Self::Project { .. } | Self::Event { .. } => {
+                match var {
+                    Var::Water => display::water_use_percent(
+                        amount,
+                        available_water,
+                    ),
+                    _ => output_units(var, amount),
+                }
+            }
+
+            // Raw production impacts (see `impact_factor`).
+            Self::Process { .. } | Self::Industry { .. } => {
+                match var {
+                    Var::Emissions => amount * 1e-15,
+                    Var::Land => display::land_use_percent(
+                        amount,
+                        starting_land,
+                    ),
+                    Var::Water => display::water_use_percent(
+                        amount,
+                        available_water,
+                    ),
+                    _ => output_units(var, amount),
+                }
+            }
+        }
+    }
+}
+
+/// Convert a raw output amount to display units,
+/// for the variables which are outputs.
+fn output_units(var: Var, amount: f32) -> f32 {
+    match var {
+        Var::Energy | Var::Electricity | Var::Fuel => {
+            display::to_energy_units(amount)
+        }
+        Var::PlantCalories | Var::AnimalCalories => {
+            display::to_calorie_units(amount)
+        }
+        _ => amount,
+    }
+}
+
+/// Each factor's signed share of the combined magnitude of
+/// all the factors' amounts, as a percent, and the net amount.
+/// The combined magnitude is used rather than the net,
+/// which can be near zero when factors offset each other.
+/// The amounts should all be in the same units
+/// (see `Factor::amount_in_units`).
+pub fn factor_shares(amounts: &[f32]) -> (Vec<f32>, f32) {
+    let net = amounts.iter().sum();
+    let gross: f32 =
+        amounts.iter().map(|amount| amount.abs()).sum();
+    if gross == 0. {
+        return (vec![0.; amounts.len()], net);
+    }
+    let shares: Vec<f32> = amounts
+        .iter()
+        .map(|amount| amount / gross * 100.)
+        .collect();
+    (shares, net)
 }
 
 fn event_factors(var: Var, state: &State) -> Vec<Factor> {
@@ -619,6 +702,57 @@ pub fn factors_card(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_factor_shares() {
+        let (shares, net) = factor_shares(&[15., -5., 30.]);
+        assert_eq!(shares, vec![30., -10., 60.]);
+        assert_eq!(net, 40.);
+
+        // No factors or only zero factors shouldn't divide by zero.
+        let (shares, net) = factor_shares(&[]);
+        assert!(shares.is_empty());
+        assert_eq!(net, 0.);
+
+        let (shares, net) = factor_shares(&[0., 0.]);
+        assert_eq!(shares, vec![0., 0.]);
+        assert_eq!(net, 0.);
+    }
+
+    #[test]
+    fn test_factor_amount_in_units() {
+        let state = State::default();
+        let factors = rank(&state);
+
+        // The nature preserves are 10% of starting land,
+        // whatever the units of their amount.
+        let preserves = factors[Var::Land]
+            .iter()
+            .find(|fac| fac.name() == "Nature Preserves")
+            .unwrap();
+        let amount =
+            preserves.amount_in_units(Var::Land, &state);
+        assert!((amount - 10.).abs() < 1e-3);
+
+        // Production emissions are in g, effects' are in Gt.
+        let industry = Factor::Industry {
+            name: "Industry".into(),
+            amount: 2e15,
+            produced: 1.,
+            intensity: 1,
+            display: "2Gt".into(),
+        };
+        let event = Factor::Event {
+            name: "Event".into(),
+            amount: 2.,
+            display: None,
+        };
+        for fac in [industry, event] {
+            let amount =
+                fac.amount_in_units(Var::Emissions, &state);
+            assert!((amount - 2.).abs() < 1e-3);
+        }
+    }
+
     #[test]
     fn test_effect_factors_emissions() {
         let state = State::default();
@@ -2,13 +2,15 @@ mod calculate;
 
 use crate::{
     consts,
+    display,
     icons::{self, HasIcon},
     state::FACTORS,
     t,
     vars::Var,
     views::{cards::FactorsCard, intensity::IntensityIcon},
 };
-pub use calculate::{rank, Factor};
+pub use calculate::{factor_shares, rank, Factor};
+use hes_engine::State;
 use leptos::*;
 
 pub use calculate::factors_card;
@@ -16,7 +18,12 @@ pub use calculate::factors_card;
 #[component]
 pub fn FactorsList(
     #[prop(into)] factors: Signal<FactorsCard>,
+
+    /// Only list the current factor, if there is one.
+    #[prop(optional)]
+    only_current: bool,
 ) -> impl IntoView {
+    let game = expect_context::<RwSignal<State>>();
     let relation = move || {
         with!(|factors| {
             let relation = match factors.kind {
@@ -69,6 +76,15 @@ pub fn FactorsList(
                         } => *produced != 0.,
                         _ => true,
                     })
+                    .filter(|user| {
+                        !only_current
+                            || factors
+                                .current
+                                .as_ref()
+                                .map_or(true, |name| {
+                                    name == user.name()
+                                })
+                    })
                     .cloned()
                     .collect::<Vec<_>>()
             } else {
@@ -84,7 +100,16 @@ pub fn FactorsList(
                 {total_label}
             </div>
             {move || {
-                relevant_factors().into_iter().map(|user| {
+                let var = with!(|factors| factors.kind);
+                let factors = relevant_factors();
+                let amounts = game.with(|state| {
+                    factors
+                        .iter()
+                        .map(|fac| fac.amount_in_units(var, state))
+                        .collect::<Vec<_>>()
+                });
+                let (shares, net) = factor_shares(&amounts);
+                let rows = factors.into_iter().zip(shares).map(|(user, share)| {
                     let highlight = cur_name() == Some(user.name().to_string());
                     let name = user.name().to_string();
                     view! {
@@ -98,10 +123,23 @@ pub fn FactorsList(
                                     relation=relation.into_signal()
                                     icon=icon.into_signal()
                                     />
+                                <div class="factors--share">
+                                    {display::signed_percent(share / 100., true)}"%"
+                                </div>
                            </div>
                       </div>
                     }
-                }).collect::<Vec<_>>()
+                }).collect::<Vec<_>>();
+                view! {
+                    {rows}
+                    <div class="factors--user factors--net">
+                        <div>{t!("Net")}</div>
+                        <div class="factors--share">
+                            {if net > 0. { "+" } else { "" }}
+                            {var.format_amount(net)}
+                        </div>
+                    </div>
+                }
             }}
         </div>
     }
@@ -156,7 +156,7 @@ pub fn Planning() -> impl IntoView {
         update!(|ui| {
             game.update_untracked(|game| {
                 if game.flags.contains(&Flag::SkipTutorial) {
-                    ui.tutorial = Tutorial::Ready;
+                    ui.tutorial.goto(Tutorial::Ready);
                     let (_, settings) = Settings::rw();
                     settings.update(|state| {
                         state.tutorial = Tutorial::Ready;
@@ -170,7 +170,7 @@ pub fn Planning() -> impl IntoView {
                     && !ui.tutorial_restarted
                 {
                     ui.tutorial_restarted = true;
-                    ui.tutorial = Tutorial::Projects;
+                    ui.tutorial.reset();
                     events.set(StateExt::roll_events(
                         game,
                         EventPhase::PlanningStart,
@@ -64,12 +64,10 @@ pub fn Processes(
 
     let has_changes =
         memo!(ui.has_process_mix_changes(output.get()));
-    let mix_changes = memo!(ui.process_mix_changes);
+    let total_allocated =
+        memo!(ui.total_allocated(output.get()));
     let changing_points = move || {
-        let total = mix_changes.get()[output.get()]
-            .values()
-            .map(|change| change.abs())
-            .sum::<isize>() as f32;
+        let total = total_allocated.get() as f32;
         (total / 2.).ceil()
     };
     let changes_time = move || {
@@ -2,8 +2,16 @@ use std::collections::BTreeMap;
 
 use enum_map::EnumMap;
 use gloo_utils::format::JsValueSerdeExt;
-use hes_engine::{Output, Resource, State};
+use hes_engine::{
+    Output,
+    Process,
+    Resource,
+    State,
+    CH4_GWP,
+    N2O_GWP,
+};
 use leptos::*;
+use leptos_use::{use_document, use_event_listener};
 use numfmt::{Formatter, Precision, Scales};
 use strum::IntoEnumIterator;
 use wasm_bindgen::prelude::*;
@@ -12,7 +20,7 @@ use crate::{
     display::{self, AsText, DisplayValue},
     icons::{self, HasIcon},
     memo,
-    state::{StateExt, UIState, FACTORS},
+    state::{StateExt, Tutorial, UIState, FACTORS},
     t,
     util::to_ws_el,
     vars::Var,
@@ -37,6 +45,52 @@ extern "C" {
         dataset: JsValue,
         colors: JsValue,
     );
+
+    #[wasm_bindgen(method, js_name = onSliceClick)]
+    fn on_slice_click(
+        this: &PieChart,
+        callback: &Closure<dyn FnMut(String)>,
+    );
+}
+
+/// Background color of the dashboard breakdown,
+/// which the pie chart is drawn against.
+pub const CHART_BACKGROUND: u32 = 0xF1E4BF;
+
+/// Minimum summed channel difference from the chart background
+/// for a color to be distinguishable from it.
+const MIN_CHART_CONTRAST: u32 = 128;
+
+fn color_distance(a: u32, b: u32) -> u32 {
+    let a = a.to_be_bytes();
+    let b = b.to_be_bytes();
+    a.iter()
+        .zip(b)
+        .skip(1) // Skip the unused alpha byte
+        .map(|(a, b)| a.abs_diff(b) as u32)
+        .sum()
+}
+
+fn darken(color: u32, factor: f32) -> u32 {
+    let [_, r, g, b] = color.to_be_bytes();
+    let scale = |c: u8| (c as f32 * factor) as u32;
+    (scale(r) << 16) | (scale(g) << 8) | scale(b)
+}
+
+#[wasm_bindgen(module = "/public/js/line.js")]
+extern "C" {
+    type LineChart;
+
+    #[wasm_bindgen(constructor)]
+    fn new(el: &web_sys::HtmlElement) -> LineChart;
+
+    #[wasm_bindgen(method)]
+    fn render(
+        this: &LineChart,
+        dataset: JsValue,
+        labels: JsValue,
+        colors: JsValue,
+    );
 }
 
 impl Var {
@@ -54,6 +108,110 @@ impl Var {
             Var::Contentedness => [0x000000, 0xFFFFFF],
         }
     }
+
+    /// If an increase in this variable is bad for the world.
+    pub fn higher_is_worse(&self) -> bool {
+        match self {
+            Var::Contentedness
+            | Var::PlantCalories
+            | Var::AnimalCalories => false,
+            _ => true,
+        }
+    }
+
+    /// The gradient for this variable, with endpoints darkened
+    /// as needed so they stand out from the chart background.
+    pub fn chart_colors(&self) -> [u32; 2] {
+        self.color().map(|mut color| {
+            while color_distance(color, CHART_BACKGROUND)
+                < MIN_CHART_CONTRAST
+            {
+                color = darken(color, 0.9);
+            }
+            color
+        })
+    }
+}
+
+/// The projected changes from the planned process mix changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ProjectedChanges {
+    extinction: f32,
+    land: f32,
+    water: f32,
+    energy: f32,
+    emissions: f32,
+}
+impl ProjectedChanges {
+    /// `changes` are the changed processes and
+    /// the change in their output.
+    fn from_changes(
+        changes: &[(Process, f32)],
+        available_land: f32,
+    ) -> Self {
+        let mut projected = Self::default();
+        for (p, mult) in changes {
+            let resources = p.adj_resources();
+            projected.extinction +=
+                p.extinction_rate(available_land) * mult;
+            projected.land += resources.land * mult;
+            projected.water += resources.water * mult;
+            projected.energy += resources.energy() * mult;
+            projected.emissions +=
+                p.adj_byproducts().gtco2eq() * mult;
+        }
+        Self {
+            extinction: projected.extinction.round(),
+            land: projected.land.round(),
+            water: projected.water.round(),
+            energy: projected.energy.round(),
+            emissions: projected.emissions.round(),
+        }
+    }
+}
+
+/// Which factor to drill into after a pie slice is clicked.
+/// `names` maps the (translated) slice labels to factor names.
+/// Clicking the selected slice again or a slice that
+/// isn't a factor (e.g. unused land) clears the selection.
+fn slice_selection(
+    label: &str,
+    names: &BTreeMap<String, String>,
+    current: Option<&str>,
+) -> Option<String> {
+    names
+        .get(label)
+        .filter(|name| current != Some(name.as_str()))
+        .cloned()
+}
+
+/// How to show a projected change:
+/// its direction and whether it's good or bad.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ChangeArrow {
+    increase: bool,
+    good: bool,
+}
+impl ChangeArrow {
+    fn new(change: f32, var: Var) -> Self {
+        let increase = change > 0.;
+        ChangeArrow {
+            increase,
+            good: increase != var.higher_is_worse(),
+        }
+    }
+
+    fn class(&self) -> String {
+        format!(
+            "dashboard--change {} {}",
+            if self.increase {
+                "increase"
+            } else {
+                "decrease"
+            },
+            if self.good { "good" } else { "bad" }
+        )
+    }
 }
 
 struct MiniCardData {
@@ -73,6 +231,37 @@ pub fn Dashboard() -> impl IntoView {
 
     let available_land =
         memo!(game.world.starting_resources.land);
+
+    // The factor drilled into from the pie chart, if any.
+    let (selected_factor, set_selected_factor) =
+        create_signal::<Option<String>>(None);
+    create_effect(move |_| {
+        // Clear the selection when the breakdown changes.
+        let _ = breakdown_factor.get();
+        set_selected_factor.set(None);
+    });
+    let factor_names = move || {
+        let mut names: BTreeMap<String, String> =
+            BTreeMap::default();
+        if let Ok(factors) = FACTORS.read() {
+            for fac in &factors[breakdown_factor.get()] {
+                names.insert(
+                    t!(&fac.name()),
+                    fac.name().to_string(),
+                );
+            }
+        }
+        names
+    };
+    let on_slice_click = move |label: String| {
+        let selected = slice_selection(
+            &label,
+            &factor_names(),
+            selected_factor.get_untracked().as_deref(),
+        );
+        set_selected_factor.set(selected);
+    };
+
     let dataset = move || {
         let mut total = 0.;
         let mut data: BTreeMap<String, f32> =
@@ -141,7 +330,9 @@ pub fn Dashboard() -> impl IntoView {
     };
 
     let processes = memo!(game.world.processes);
-    let process_mix_changes = memo!(ui.process_mix_changes);
+    let committed_mix = create_memo(move |_| {
+        with!(|ui, game| ui.committed_mix(game))
+    });
     let demand_for_outputs = create_memo(move |_| {
         let demands: EnumMap<Output, f32> =
             with!(|game| Output::iter()
@@ -153,19 +344,16 @@ pub fn Dashboard() -> impl IntoView {
         demands
     });
     let process_changes = move || {
-        with!(|processes,
-               process_mix_changes,
-               demand_for_outputs| {
+        with!(|processes, committed_mix, demand_for_outputs| {
             processes
                 .iter()
                 .filter(|p| !p.locked)
                 .filter_map(move |p| {
-                    let mix_change = (*process_mix_changes
-                        [p.output]
+                    let mix_change = committed_mix[p.output]
                         .get(&p.id)
-                        .unwrap_or(&0))
-                        as f32
-                        * 0.05;
+                        .map_or(0., |mix| {
+                            mix - p.mix_percent()
+                        });
                     if mix_change != 0. {
                         let change = mix_change
                             * demand_for_outputs[p.output];
@@ -178,15 +366,16 @@ pub fn Dashboard() -> impl IntoView {
         })
     };
 
-    let extinction_change = move || {
-        process_changes()
-            .into_iter()
-            .map(|(p, mult)| {
-                p.extinction_rate(available_land.get()) * mult
-            })
-            .sum::<f32>()
-            .round()
-    };
+    // Computed once from the process changes
+    // and shared by all the dashboard items.
+    let projected = create_memo(move |_| {
+        ProjectedChanges::from_changes(
+            &process_changes(),
+            available_land.get(),
+        )
+    });
+
+    let extinction_change = move || projected.get().extinction;
     let extinction_rate = memo!(game.world.extinction_rate);
     let current_extinction =
         move || extinction(extinction_rate.get());
@@ -195,51 +384,19 @@ pub fn Dashboard() -> impl IntoView {
             .label
     };
 
-    let land_change = move || {
-        process_changes()
-            .into_iter()
-            .map(|(p, mult)| p.adj_resources().land * mult)
-            .sum::<f32>()
-            .round()
-    };
-    let water_change = move || {
-        process_changes()
-            .into_iter()
-            .map(|(p, mult)| p.adj_resources().water * mult)
-            .sum::<f32>()
-            .round()
-    };
-    let energy_change = move || {
-        process_changes()
-            .into_iter()
-            .map(|(p, mult)| {
-                let energy = p.adj_resources().energy();
-                energy * mult
-            })
-            .sum::<f32>()
-            .round()
-    };
-    let emissions_change = move || {
-        process_changes()
-            .into_iter()
-            .map(|(p, mult)| {
-                p.adj_byproducts().gtco2eq() * mult
-            })
-            .sum::<f32>()
-            .round()
-    };
+    let land_change = move || projected.get().land;
+    let water_change = move || projected.get().water;
+    let energy_change = move || projected.get().energy;
+    let emissions_change = move || projected.get().emissions;
 
     let water_demand =
         memo!(game.resource_demand.of(Resource::Water));
-    let current_water_stress = memo!(game.water_use_percent());
+    let current_water_stress = memo!(game.water_use_label());
     let after_water_stress = move || {
-        format!(
-            "{:.0}%",
-            display::water_use_percent(
-                water_change() + water_demand.get(),
-                available_water.get()
-            )
-        )
+        Var::Water.format_amount(display::water_use_percent(
+            water_change() + water_demand.get(),
+            available_water.get(),
+        ))
     };
 
     let temp_anomaly = memo!(game.temp_anomaly());
@@ -267,7 +424,8 @@ pub fn Dashboard() -> impl IntoView {
     let emissions = memo!(game.emissions.as_gtco2eq());
     let emissions_display = memo!(game.emissions.display());
     let emissions_changed = move || {
-        display::emissions(emissions_change() + emissions.get())
+        Var::Emissions
+            .format_amount(emissions_change() + emissions.get())
     };
     let emissions_view = move || {
         view! {
@@ -278,6 +436,7 @@ pub fn Dashboard() -> impl IntoView {
                 display_changed_value=emissions_changed
                 change=emissions_change
                 icon=icons::EMISSIONS
+                var=Var::Emissions
             />
         }
     };
@@ -299,13 +458,10 @@ pub fn Dashboard() -> impl IntoView {
     let land_demand =
         memo!(game.resource_demand.of(Resource::Land));
     let land_changed = move || {
-        format!(
-            "{:.0}%",
-            display::land_use_percent(
-                land_change() + land_demand.get(),
-                available_land.get()
-            )
-        )
+        Var::Land.format_amount(display::land_use_percent(
+            land_change() + land_demand.get(),
+            available_land.get(),
+        ))
     };
     let land_view = move || {
         view! {
@@ -316,6 +472,7 @@ pub fn Dashboard() -> impl IntoView {
                 display_changed_value=land_changed
                 change=land_change
                 icon=icons::LAND
+                var=Var::Land
             />
         }
     };
@@ -337,13 +494,9 @@ pub fn Dashboard() -> impl IntoView {
     let energy_demand =
         memo!(game.output_demand.total().energy());
     let energy_changed = move || {
-        format!(
-            "{}PWh",
-            (display::pwh(
-                energy_change() + energy_demand.get()
-            ))
-            .round()
-        )
+        Var::Energy.format_amount(display::to_energy_units(
+            energy_change() + energy_demand.get(),
+        ))
     };
     let energy_view = move || {
         view! {
@@ -354,6 +507,7 @@ pub fn Dashboard() -> impl IntoView {
                 display_changed_value=energy_changed
                 change=energy_change
                 icon=icons::ENERGY
+                var=Var::Energy
             />
         }
     };
@@ -380,6 +534,7 @@ pub fn Dashboard() -> impl IntoView {
                 display_changed_value=after_water_stress
                 change=water_change
                 icon=icons::WATER
+                var=Var::Water
             />
         }
     };
@@ -406,6 +561,7 @@ pub fn Dashboard() -> impl IntoView {
                 display_changed_value=after_extinction
                 change=extinction_change
                 icon=icons::EXTINCTION_RATE
+                var=Var::Biodiversity
             />
         }
     };
@@ -486,27 +642,158 @@ pub fn Dashboard() -> impl IntoView {
         }
     };
 
+    // In CO2eq so the gases are comparable.
+    let emissions_history = create_memo(move |_| {
+        with!(|ui| ui
+            .emissions_history
+            .iter()
+            .map(|(co2, ch4, n2o)| {
+                (
+                    *co2,
+                    ch4 * CH4_GWP as f64,
+                    n2o * N2O_GWP as f64,
+                )
+            })
+            .collect::<Vec<_>>())
+    });
+
     let table_data = move || {
         with!(|game| {
-            factors_card(None, breakdown_factor.get(), game)
+            factors_card(
+                selected_factor.get(),
+                breakdown_factor.get(),
+                game,
+            )
         })
     };
     let icon = move || breakdown_factor.get().icon();
     let name = move || t!(breakdown_factor.get().title());
 
+    // Keyboard navigation for the breakdown menu:
+    // arrow keys move between options, enter selects,
+    // and escape closes the menu.
+    let (menu_index, set_menu_index) = create_signal(0);
+    let select_ref = create_node_ref::<html::Div>();
+    let menu_ref = create_node_ref::<html::Div>();
+    let open_menu = move || {
+        let current = breakdown_factor.get_untracked();
+        let idx = Var::iter()
+            .position(|var| var == current)
+            .unwrap_or(0);
+        set_menu_index.set(idx);
+        set_show_breakdown_menu.set(true);
+    };
+    // The tutorial step for the breakdown menu
+    // is finished once the player picks a breakdown.
+    let breakdown_tutorial =
+        memo!(ui.tutorial.eq(&Tutorial::DashboardBreakdown));
+    let select_breakdown = move |var: Var| {
+        set_breakdown_factor.set(var);
+        if breakdown_tutorial.get_untracked() {
+            ui.update(|ui| ui.tutorial.advance());
+        }
+    };
+    let close_menu = move || {
+        set_show_breakdown_menu.set(false);
+        if let Some(select) = select_ref.get_untracked() {
+            let _ = select.focus();
+        }
+    };
+    let _ = use_event_listener(
+        use_document(),
+        ev::keydown,
+        move |ev| {
+            if !show_breakdown_menu.get_untracked() {
+                // Open the menu from the focused select button.
+                let from_select = select_ref
+                    .get_untracked()
+                    .is_some_and(|select| {
+                        select.contains(
+                            ev.target().as_ref().and_then(
+                                |target| target.dyn_ref(),
+                            ),
+                        )
+                    });
+                if from_select
+                    && (ev.key() == "Enter" || ev.key() == " ")
+                {
+                    ev.prevent_default();
+                    open_menu();
+                }
+                return;
+            }
+            let n_vars = Var::iter().count();
+            match ev.key().as_str() {
+                "ArrowDown" => {
+                    ev.prevent_default();
+                    set_menu_index.update(|idx| {
+                        *idx = (*idx + 1) % n_vars;
+                    });
+                }
+                "ArrowUp" => {
+                    ev.prevent_default();
+                    set_menu_index.update(|idx| {
+                        *idx = (*idx + n_vars - 1) % n_vars;
+                    });
+                }
+                "Enter" => {
+                    ev.prevent_default();
+                    if let Some(var) = Var::iter()
+                        .nth(menu_index.get_untracked())
+                    {
+                        select_breakdown(var);
+                    }
+                    close_menu();
+                }
+                "Escape" => close_menu(),
+                _ => {}
+            }
+        },
+    );
+
+    // Move focus into the menu when it opens,
+    // so screen readers announce the options.
+    create_effect(move |_| {
+        if show_breakdown_menu.get() {
+            request_animation_frame(move || {
+                if let Some(menu) = menu_ref.get_untracked() {
+                    let _ = menu.focus();
+                }
+            });
+        }
+    });
+
     let menu = move || {
         view! {
             <Show when=move || show_breakdown_menu.get()>
                 <div class="dashboard-breakdown-menu-overlay">
-                    <div class="dashboard-breakdown-menu">
+                    <div
+                        class="dashboard-breakdown-menu"
+                        role="listbox"
+                        tabindex="-1"
+                        aria-activedescendant=move || {
+                            format!("breakdown-option-{}", menu_index.get())
+                        }
+                        ref=menu_ref
+                    >
                         {move || {
                             Var::iter()
-                                .map(|var| {
+                                .enumerate()
+                                .map(|(idx, var)| {
                                     view! {
-                                        <div on:click=move |_| {
-                                            set_breakdown_factor.set(var);
-                                            set_show_breakdown_menu.set(false);
-                                        }>
+                                        <div
+                                            id=format!("breakdown-option-{idx}")
+                                            role="option"
+                                            aria-selected=move || {
+                                                (menu_index.get() == idx).to_string()
+                                            }
+                                            class:active=move || menu_index.get() == idx
+                                            on:mouseenter=move |_| set_menu_index.set(idx)
+                                            on:click=move |_| {
+                                                select_breakdown(var);
+                                                close_menu();
+                                            }
+                                        >
                                             <img class="pip-icon" src=var.icon()/>
                                             {t!(var.title())}
                                         </div>
@@ -531,7 +818,12 @@ pub fn Dashboard() -> impl IntoView {
             </div> <div class="dashboard-breakdown">
                 <div
                     class="dashboard-breakdown-select btn"
-                    on:click=move |_| set_show_breakdown_menu.set(true)
+                    class:highlight=breakdown_tutorial
+                    role="button"
+                    tabindex="0"
+                    aria-haspopup="listbox"
+                    ref=select_ref
+                    on:click=move |_| open_menu()
                 >
                     <img class="pip-icon" src=icon/>
                     {name}
@@ -539,10 +831,22 @@ pub fn Dashboard() -> impl IntoView {
                 </div>
                 <PieChart
                     dataset=dataset
-                    colors=move || breakdown_factor.get().color()
+                    colors=move || breakdown_factor.get().chart_colors()
+                    on_slice_click
                 />
+                <Show when=move || {
+                    breakdown_factor.get() == Var::Emissions
+                }>
+                    <LineChart
+                        dataset=emissions_history
+                        colors=move || Var::Emissions.chart_colors()
+                    />
+                </Show>
                 <div class="dashboard--factors">
-                    <FactorsList factors=table_data/>
+                    <FactorsList
+                        factors=table_data
+                        only_current=true
+                    />
                 </div>
                 <div class="dashboard-breakdown-note">
                     {t!("Only direct impacts are shown.")}
@@ -560,8 +864,11 @@ fn DashboardItem(
     #[prop(into)] tip: MaybeSignal<Tip>,
     #[prop(into)] change: Signal<f32>,
     #[prop(into)] icon: MaybeSignal<&'static str>,
+    var: Var,
     #[prop(into, optional)] color: Option<String>,
 ) -> impl IntoView {
+    let change_class =
+        move || ChangeArrow::new(change.get(), var).class();
     let change_tip = move || {
         crate::views::tip(
             icon.get(),
@@ -575,7 +882,7 @@ fn DashboardItem(
                     <span style:color=color>{display_value}</span>
                     <Show when=move || change.get() != 0.>
                         <HasTip tip=change_tip.into_signal()>
-                            <div class="dashboard--change">
+                            <div class=change_class>
                                 <img src=icons::DOWN_ARROW_SMALL/>
                                 <span class="dashboard--change-value">
                                     {display_changed_value}
@@ -596,15 +903,39 @@ fn DashboardItem(
 fn PieChart(
     #[prop(into)] dataset: Signal<BTreeMap<String, f32>>,
     #[prop(into)] colors: Signal<[u32; 2]>,
+    #[prop(into, optional)] on_slice_click: Option<
+        Callback<String>,
+    >,
 ) -> impl IntoView {
     let stage_ref = create_node_ref::<html::Div>();
     let (_, set_chart) = create_signal(None);
 
+    // Kept alive for as long as the chart is,
+    // and dropped along with it.
+    let click_handler =
+        store_value::<Option<Closure<dyn FnMut(String)>>>(None);
+    on_cleanup(move || {
+        click_handler.try_update_value(|handler| {
+            handler.take();
+        });
+    });
+
     create_effect(move |_| {
         set_chart.update(|chart| {
             if chart.is_none() {
                 let stage = stage_ref.get().unwrap();
-                *chart = Some(PieChart::new(&to_ws_el(stage)));
+                let pie = PieChart::new(&to_ws_el(stage));
+                if let Some(callback) = on_slice_click {
+                    let closure = Closure::wrap(Box::new(
+                        move |label: String| {
+                            callback.call(label);
+                        },
+                    )
+                        as Box<dyn FnMut(String)>);
+                    pie.on_slice_click(&closure);
+                    click_handler.set_value(Some(closure));
+                }
+                *chart = Some(pie);
             }
             if let Some(chart) = chart {
                 let dataset =
@@ -619,3 +950,172 @@ fn PieChart(
 
     view! { <div class="pie-chart" ref=stage_ref></div> }
 }
+
+#[component]
+fn LineChart(
+    #[prop(into)] dataset: Signal<Vec<(f64, f64, f64)>>,
+    #[prop(into)] colors: Signal<[u32; 2]>,
+) -> impl IntoView {
+    let stage_ref = create_node_ref::<html::Div>();
+    let (_, set_chart) = create_signal(None);
+    let is_empty = move || dataset.with(|data| data.is_empty());
+
+    create_effect(move |_| {
+        set_chart.update(|chart| {
+            if chart.is_none() {
+                let stage = stage_ref.get().unwrap();
+                *chart = Some(LineChart::new(&to_ws_el(stage)));
+            }
+            if let Some(chart) = chart {
+                let dataset =
+                    JsValue::from_serde(&dataset.get())
+                        .unwrap();
+                let labels =
+                    JsValue::from_serde(&["CO2", "CH4", "N2O"])
+                        .unwrap();
+                let colors =
+                    JsValue::from_serde(&colors.get()).unwrap();
+                chart.render(dataset, labels, colors);
+            }
+        });
+    });
+
+    view! {
+        <div class="line-chart" ref=stage_ref></div>
+        <Show when=is_empty>
+            <div class="line-chart-empty">
+                {t!("No emissions history yet.")}
+            </div>
+        </Show>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_arrow() {
+        // Higher is worse
+        let arrow = ChangeArrow::new(2., Var::Emissions);
+        assert!(arrow.increase && !arrow.good);
+        let arrow = ChangeArrow::new(-2., Var::Emissions);
+        assert!(!arrow.increase && arrow.good);
+        let arrow = ChangeArrow::new(-1., Var::Biodiversity);
+        assert!(!arrow.increase && arrow.good);
+        assert_eq!(
+            ChangeArrow::new(5., Var::Land).class(),
+            "dashboard--change increase bad"
+        );
+
+        // Higher is better
+        let arrow = ChangeArrow::new(3., Var::Contentedness);
+        assert!(arrow.increase && arrow.good);
+        assert_eq!(
+            ChangeArrow::new(-3., Var::PlantCalories).class(),
+            "dashboard--change decrease bad"
+        );
+    }
+
+    #[test]
+    fn test_slice_selection() {
+        let names: BTreeMap<String, String> = [
+            (
+                "Kohlekraft".to_string(),
+                "Coal Power".to_string(),
+            ),
+            ("Solar".to_string(), "Solar PV".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        // Slice labels map back to the factor names.
+        assert_eq!(
+            slice_selection("Kohlekraft", &names, None),
+            Some("Coal Power".to_string())
+        );
+        assert_eq!(
+            slice_selection(
+                "Solar",
+                &names,
+                Some("Coal Power")
+            ),
+            Some("Solar PV".to_string())
+        );
+
+        // Clicking the selected slice again clears it.
+        assert_eq!(
+            slice_selection("Solar", &names, Some("Solar PV")),
+            None
+        );
+
+        // Unused land isn't a factor.
+        assert_eq!(
+            slice_selection("Unused", &names, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_projected_changes() {
+        let mut a = Process::default();
+        a.resources.land = 120.;
+        a.resources.water = 30.;
+        a.resources.electricity = 8.;
+        a.byproducts.co2 = 4e15;
+        a.byproducts.biodiversity = 2.;
+        let mut b = Process::default();
+        b.resources.land = 40.;
+        b.resources.fuel = 5.;
+        b.byproducts.ch4 = 1e14;
+        let changes = vec![(a, 1.5), (b, -2.)];
+        let available_land = 1000.;
+
+        // Should match summing each change separately.
+        let sum = |f: &dyn Fn(&Process) -> f32| {
+            changes
+                .iter()
+                .map(|(p, mult)| f(p) * mult)
+                .sum::<f32>()
+                .round()
+        };
+        let projected = ProjectedChanges::from_changes(
+            &changes,
+            available_land,
+        );
+        assert_eq!(
+            projected,
+            ProjectedChanges {
+                extinction: sum(
+                    &|p| p.extinction_rate(available_land)
+                ),
+                land: sum(&|p| p.adj_resources().land),
+                water: sum(&|p| p.adj_resources().water),
+                energy: sum(&|p| p.adj_resources().energy()),
+                emissions: sum(&|p| p
+                    .adj_byproducts()
+                    .gtco2eq()),
+            }
+        );
+        assert_eq!(projected.land, 100.);
+    }
+
+    #[test]
+    fn test_chart_colors_visible() {
+        for var in Var::iter() {
+            for color in var.chart_colors() {
+                assert_ne!(color, CHART_BACKGROUND);
+                assert!(
+                    color_distance(color, CHART_BACKGROUND)
+                        >= MIN_CHART_CONTRAST,
+                    "{:?} has a low contrast color: {:06X}",
+                    var,
+                    color
+                );
+            }
+        }
+
+        // Already visible colors are left as-is.
+        assert_eq!(Var::Land.chart_colors(), Var::Land.color());
+    }
+}
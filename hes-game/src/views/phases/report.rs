@@ -16,7 +16,7 @@ use crate::{
         HasTip,
     },
 };
-use hes_engine::{EventPhase, NPCRequest, State};
+use hes_engine::{EffectChange, EventPhase, NPCRequest, State};
 use leptos::*;
 
 pub struct Request {
@@ -79,19 +79,13 @@ pub fn Report() -> impl IntoView {
         )
     };
 
+    let report = create_memo(move |_| {
+        with!(|ui, game| ui.cycle_report(game))
+    });
+    let region_income_changes =
+        move || with!(|report| report.region_incomes.clone());
+
     let regions = memo!(game.world.regions);
-    let start_region_incomes =
-        memo!(ui.cycle_start_state.region_incomes);
-    let region_income_changes = move || {
-        with!(|regions, start_region_incomes| {
-            regions
-                .iter()
-                .zip(start_region_incomes.iter())
-                .filter(|(reg, inc)| reg.income != **inc)
-                .map(|(reg, _)| (reg.name.clone(), reg.income))
-                .collect::<Vec<_>>()
-        })
-    };
 
     let region_events = memo!(ui.annual_region_events);
     let region_disasters = move || {
@@ -115,30 +109,15 @@ pub fn Report() -> impl IntoView {
                     tip(
                         icons::CHANCE,
                         t!("This event occurred during this planning cycle.")
-                    ).card(ev.clone())
+                    ).card(ev.clone()),
+                    effect_changes(&ev.changes),
                 )
             }).collect::<Vec<_>>()
         })
     };
 
-    let npcs = memo!(game.npcs);
-    let start_parliament =
-        memo!(ui.cycle_start_state.parliament);
-    let seat_changes = move || {
-        with!(|start_parliament, npcs| {
-            start_parliament
-                .iter()
-                .enumerate()
-                .map(|(i, start_seats)| {
-                    let npc = &npcs.by_idx(i);
-                    let change =
-                        (npc.seats - start_seats).round();
-                    (npc.name.clone(), npc.seats, change)
-                })
-                .filter(|(_, _, change)| *change != 0.)
-                .collect::<Vec<_>>()
-        })
-    };
+    let seat_changes =
+        move || with!(|report| report.seat_changes.clone());
     let honeymoon_pc = move || {
         with!(|year, start_year| {
             if *year < *start_year + consts::HONEYMOON_YEARS {
@@ -538,14 +517,25 @@ pub fn Report() -> impl IntoView {
                                 </tr>
                                 <For
                                     each=move || world_events()
-                                    key=|(name, _)| name.clone()
-                                    children=|(name, tip)| {
+                                    key=|(name, _, _)| name.clone()
+                                    children=|(name, tip, changes)| {
                                         view! {
                                             <HasTip tip>
                                                 <tr>
                                                     <td colspan="5">{t!(& name)}</td>
                                                 </tr>
                                             </HasTip>
+                                            {changes
+                                                .into_iter()
+                                                .map(|(label, delta)| {
+                                                    view! {
+                                                        <tr class="report--effect-change">
+                                                            <td colspan="4">{label}</td>
+                                                            <td>{delta}</td>
+                                                        </tr>
+                                                    }
+                                                })
+                                                .collect::<Vec<_>>()}
                                         }
                                     }
                                 />
@@ -618,3 +608,25 @@ pub fn Report() -> impl IntoView {
         </div>
     }
 }
+
+/// The changes an event's effects made, as (label, delta),
+/// skipping any which didn't change anything.
+fn effect_changes(
+    changes: &[EffectChange],
+) -> Vec<(String, String)> {
+    changes
+        .iter()
+        .filter(|change| change.delta != 0.)
+        .map(|change| {
+            // Variables are effect fingerprints, e.g.
+            // "WorldVariable:Outlook"; the part after
+            // the kind is the more readable one.
+            let label = match change.variable.split_once(':') {
+                Some((_, var)) if !var.is_empty() => var,
+                Some((kind, _)) => kind,
+                None => &change.variable,
+            };
+            (t!(label), format!("{:+.1}", change.delta))
+        })
+        .collect()
+}
@@ -18,11 +18,13 @@ use crate::{
         events::Events,
         globe::{Globe, GlobeRef},
         hud::Hud,
+        scanner::shake_screen_for,
     },
 };
 use hes_engine::{
     Diff,
     EventPhase,
+    EventSeverity,
     IconEvent,
     Id,
     Output,
@@ -53,6 +55,17 @@ impl Toast {
             desc: t!("{disaster} in {region}", disaster: t!(&ev.name), region: t!(region_name)),
         }
     }
+
+    /// A queued event pushed back to the next year.
+    fn deferred(name: &str) -> Self {
+        let id =
+            (js_sys::Math::random() * 1e10).round() as usize;
+        Toast {
+            id,
+            icon: icons::TIME,
+            desc: t!("{event} postponed to next year", event: t!(name)),
+        }
+    }
 }
 
 fn warming_colour(mut temp: f32) -> String {
@@ -106,6 +119,7 @@ fn get_emissions(state: &State) -> HashMap<&'static str, f64> {
 struct Disaster {
     event_id: Id,
     region: Option<(Id, String)>,
+    severity: EventSeverity,
 
     /// When in the year the event occurs.
     when: f32,
@@ -116,12 +130,12 @@ fn Disasters(
     year: Memo<usize>,
     phase: RwSignal<Subphase>,
     events: RwSignal<Vec<Disaster>>,
+    toasts: RwSignal<Vec<Toast>>,
     #[prop(into)] skipping: Signal<bool>,
     #[prop(into)] on_done: Callback<()>,
 ) -> impl IntoView {
     let ui = expect_context::<RwSignal<UIState>>();
     let game = expect_context::<RwSignal<State>>();
-    let toasts = create_rw_signal::<Vec<Toast>>(vec![]);
 
     let (globe, set_globe) =
         create_signal::<Option<GlobeRef>>(None);
@@ -153,6 +167,7 @@ fn Disasters(
                 if let Disaster {
                     event_id,
                     region: Some((region_id, region_name)),
+                    severity,
                     ..
                 } = ev_meta
                 {
@@ -173,13 +188,19 @@ fn Disasters(
                             event_id,
                             region_id,
                             region_name,
+                            severity,
                         ));
                     }
                 }
             }
 
-            for (ev, event_id, region_id, region_name) in
-                occurring
+            for (
+                ev,
+                event_id,
+                region_id,
+                region_name,
+                severity,
+            ) in occurring
             {
                 ui.update_untracked(|ui| {
                     let region_events = ui
@@ -191,8 +212,12 @@ fn Disasters(
                 game.update(|game| {
                     StateExt::apply_disaster(
                         game, ev, &event_id, &region_id,
+                        severity,
                     );
                 });
+                if !skipping.get_untracked() {
+                    shake_screen_for(severity);
+                }
                 toasts.push(Toast::new(ev, &region_name));
             }
         });
@@ -252,6 +277,7 @@ pub fn WorldEvents() -> impl IntoView {
     let phase = create_rw_signal(Subphase::Events);
 
     let disasters = create_rw_signal::<Vec<Disaster>>(vec![]);
+    let toasts = create_rw_signal::<Vec<Toast>>(vec![]);
     let updates = create_rw_signal::<Vec<EngineUpdate>>(vec![]);
     let events = create_rw_signal(vec![]);
 
@@ -325,10 +351,12 @@ pub fn WorldEvents() -> impl IntoView {
                             _ => None,
                         });
 
+                    let emissions = game.emissions_by_gas();
                     ui.update_untracked(|ui| {
                         ui.cycle_start_state
                             .completed_projects
                             .extend(completed_projects);
+                        ui.emissions_history.push(emissions);
                     });
 
                     updates.set(step_updates.into());
@@ -346,10 +374,16 @@ pub fn WorldEvents() -> impl IntoView {
 
         if next == Subphase::Events {
             game.update_untracked(|game| {
-                let evs = StateExt::roll_events(
-                    game,
-                    EventPhase::WorldMain,
-                );
+                let (evs, deferred) =
+                    StateExt::roll_events_with_deferred(
+                        game,
+                        EventPhase::WorldMain,
+                    );
+                toasts.update(|toasts| {
+                    for name in &deferred {
+                        toasts.push(Toast::deferred(name));
+                    }
+                });
 
                 ui.update_untracked(|ui| {
                     for event in &evs {
@@ -424,6 +458,7 @@ pub fn WorldEvents() -> impl IntoView {
                     .map(|ev| Disaster {
                         event_id: ev.id,
                         region: ev.region.clone(),
+                        severity: ev.severity,
                         when: js_sys::Math::random() as f32,
                     })
                     .collect();
@@ -454,7 +489,7 @@ pub fn WorldEvents() -> impl IntoView {
     view! {
         <Hud/>
         <div id="event-stream">
-            <Disasters year phase skipping events=disasters on_done=move |_| {
+            <Disasters year phase skipping events=disasters toasts on_done=move |_| {
                 next_phase();
             } />
             <Show when=move || phase.get() == Subphase::Updates>
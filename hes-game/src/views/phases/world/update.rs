@@ -131,12 +131,14 @@ fn Update(
                     let proj = &projects[id];
 
                     set_can_close.set_untracked(
-                        !proj.active_outcome.is_some(),
+                        proj.active_outcomes.is_empty(),
                     );
 
                     let effects = active_effects(proj);
-                    let outcome_dialogue = proj.active_outcome.map(|id| {
-                        let (dialogue, _) = create_signal(proj.flavor.outcomes[id].clone());
+                    // Show the dialogue for the primary outcome;
+                    // the effects of all outcomes are listed below.
+                    let outcome_dialogue = proj.active_outcomes.first().map(|id| {
+                        let (dialogue, _) = create_signal(proj.flavor.outcomes[*id].clone());
                         view! {
                             <Dialogue dialogue on_start=move |_| {
                                 set_can_close.set(false);
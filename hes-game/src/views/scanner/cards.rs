@@ -164,7 +164,7 @@ pub fn ScannerCards<S: ScannerSpec>(
             let id = item.id();
             if with!(|viewed| !viewed.contains(id)) {
                 ui.update(|ui| {
-                    ui.viewed.push(*id);
+                    ui.mark_viewed(*id);
                 });
             }
         }
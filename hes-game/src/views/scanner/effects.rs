@@ -1,7 +1,10 @@
 use gloo_utils::format::JsValueSerdeExt;
+use hes_engine::EventSeverity;
 use leptos::*;
 use serde::Serialize;
+use std::cell::{Cell, RefCell};
 use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
 use web_sys::{
     Animation,
     AnimationEffect,
@@ -31,42 +34,142 @@ fn animate<F: Serialize>(
     anim
 }
 
-fn shake(elem: &Element, duration_ms: f64) {
+/// How hard to shake.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ShakeIntensity {
+    Light,
+    #[default]
+    Normal,
+    Heavy,
+}
+impl ShakeIntensity {
+    const ALL: [ShakeIntensity; 3] = [
+        ShakeIntensity::Light,
+        ShakeIntensity::Normal,
+        ShakeIntensity::Heavy,
+    ];
+
+    fn scale(&self) -> f32 {
+        match self {
+            Self::Light => 0.5,
+            Self::Normal => 1.,
+            Self::Heavy => 2.,
+        }
+    }
+
+    /// Added to the element while it shakes,
+    /// so CSS can scale the effect as well.
+    fn class(&self) -> &'static str {
+        match self {
+            Self::Light => "shake-light",
+            Self::Normal => "shake-normal",
+            Self::Heavy => "shake-heavy",
+        }
+    }
+}
+impl From<EventSeverity> for ShakeIntensity {
+    fn from(severity: EventSeverity) -> Self {
+        match severity {
+            EventSeverity::Minor => Self::Light,
+            EventSeverity::Major => Self::Normal,
+            EventSeverity::Catastrophic => Self::Heavy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShakeConfig {
+    pub intensity: ShakeIntensity,
+    pub duration_ms: f64,
+}
+impl Default for ShakeConfig {
+    fn default() -> Self {
+        Self {
+            intensity: ShakeIntensity::default(),
+            duration_ms: 350.,
+        }
+    }
+}
+impl From<EventSeverity> for ShakeConfig {
+    /// More severe events shake harder and longer.
+    fn from(severity: EventSeverity) -> Self {
+        let intensity = ShakeIntensity::from(severity);
+        Self {
+            intensity,
+            duration_ms: 350.
+                * intensity.scale().max(1.) as f64,
+        }
+    }
+}
+
+thread_local! {
+    /// Incremented for each shake, to identify the latest one.
+    static SHAKE_ID: Cell<usize> = const { Cell::new(0) };
+}
+
+fn shake(elem: &Element, config: ShakeConfig) -> Animation {
     #[derive(Serialize)]
     struct TranslateKeyframe {
-        translate: &'static str,
+        translate: String,
         offset: f32,
     }
 
-    let frames = vec![
-        TranslateKeyframe {
-            translate: "0 0",
-            offset: 0.0,
-        },
-        TranslateKeyframe {
-            translate: "-2px 1px",
-            offset: 0.25,
-        },
-        TranslateKeyframe {
-            translate: "1px 2px",
-            offset: 0.35,
-        },
-        TranslateKeyframe {
-            translate: "3px 1px",
-            offset: 0.55,
-        },
-        TranslateKeyframe {
-            translate: "-1px 2px",
-            offset: 0.75,
-        },
-        TranslateKeyframe {
-            translate: "0 0",
-            offset: 1.0,
-        },
-    ];
-    animate(&elem, &frames, duration_ms, false);
+    let scale = config.intensity.scale();
+    let frames: Vec<_> = [
+        ((0., 0.), 0.0),
+        ((-2., 1.), 0.25),
+        ((1., 2.), 0.35),
+        ((3., 1.), 0.55),
+        ((-1., 2.), 0.75),
+        ((0., 0.), 1.0),
+    ]
+    .into_iter()
+    .map(|((x, y), offset)| TranslateKeyframe {
+        translate: format!("{}px {}px", x * scale, y * scale),
+        offset,
+    })
+    .collect();
+
+    let class_list = elem.class_list();
+    for intensity in ShakeIntensity::ALL {
+        let _ = class_list.remove_1(intensity.class());
+    }
+    let class = config.intensity.class();
+    let _ = class_list.add_1(class);
+
+    // Tag the element with this shake so that if it's
+    // interrupted by another, we don't remove the new class.
+    let shake_id = SHAKE_ID.with(|id| {
+        id.set(id.get() + 1);
+        id.get().to_string()
+    });
+    let _ = elem.set_attribute("data-shake", &shake_id);
+
+    let anim =
+        animate(&elem, &frames, config.duration_ms, false);
+
+    // The `finished` promise resolves when the animation
+    // finishes and rejects if it's cancelled.
+    if let Ok(finished) = anim.finished() {
+        let elem = elem.clone();
+        spawn_local(async move {
+            let _ = JsFuture::from(finished).await;
+            if elem.get_attribute("data-shake").as_ref()
+                == Some(&shake_id)
+            {
+                let _ = elem.class_list().remove_1(class);
+                let _ = elem.remove_attribute("data-shake");
+            }
+        });
+    }
+    anim
 }
-fn pulse(elem: &Element, from: f32, to: f32, duration_ms: f64) {
+fn pulse(
+    elem: &Element,
+    from: f32,
+    to: f32,
+    duration_ms: f64,
+) -> Animation {
     #[derive(Serialize)]
     struct ScaleKeyframe {
         scale: f32,
@@ -86,7 +189,7 @@ fn pulse(elem: &Element, from: f32, to: f32, duration_ms: f64) {
             offset: 1.0,
         },
     ];
-    animate(&elem, &frames, duration_ms, false);
+    animate(&elem, &frames, duration_ms, false)
 }
 
 pub fn fill_bar(elem: &Element, duration_ms: f64) -> Animation {
@@ -108,42 +211,119 @@ pub fn fill_bar(elem: &Element, duration_ms: f64) -> Animation {
     animate(&elem, &frames, duration_ms, true)
 }
 
+const IMPACT_SOUND: &str = "/assets/sounds/impact.mp3";
+
+thread_local! {
+    /// When the impact sound last played,
+    /// so overlapping shakes don't restart it.
+    static LAST_IMPACT: Cell<f64> = const { Cell::new(f64::MIN) };
+}
+
 pub fn shake_screen() {
+    shake_screen_with(ShakeConfig::default(), IMPACT_SOUND);
+}
+
+/// Shake the screen harder and longer for more severe events.
+pub fn shake_screen_for(severity: EventSeverity) {
+    shake_screen_with(severity.into(), IMPACT_SOUND);
+}
+
+/// Shake the screen, playing the given sound
+/// unless it's already playing for a recent shake.
+/// If sound is muted the audio manager skips playback.
+pub fn shake_screen_with(config: ShakeConfig, sound: &str) {
     document().body().map(|body| {
-        audio::play_one_shot("/assets/sounds/impact.mp3");
-        shake(&body.into(), 350.0);
+        let now = js_sys::Date::now();
+        let should_play = LAST_IMPACT.with(|last| {
+            let play = now - last.get() >= config.duration_ms;
+            if play {
+                last.set(now);
+            }
+            play
+        });
+        if should_play {
+            audio::play_one_shot(sound);
+        }
+        shake(&body.into(), config);
     });
 }
 
+thread_local! {
+    /// The current progress shake, so rapid repeated
+    /// rejections restart the shake rather than stacking.
+    static PROGRESS_SHAKE: RefCell<Option<Animation>> =
+        const { RefCell::new(None) };
+}
+
 pub fn shake_progress(elem: web_sys::HtmlElement) {
-    if let Some(elem) = elem.parent_element() {
-        shake(&elem, 350.0);
-    }
+    shake_progress_with(elem, ShakeConfig::default());
 }
 
-pub fn pulse_card() {
-    if let Some(elem) =
-        document().query_selector(".draggable.active").unwrap()
-    {
+pub fn shake_progress_with(
+    elem: web_sys::HtmlElement,
+    config: ShakeConfig,
+) {
+    PROGRESS_SHAKE.with(|current| {
+        if let Some(prev) = current.take() {
+            prev.cancel();
+        }
+
+        // The progress bar may have been removed already.
+        if let Some(elem) = elem
+            .parent_element()
+            .filter(|elem| elem.is_connected())
+        {
+            let anim = shake(&elem, config);
+            *current.borrow_mut() = Some(anim);
+        }
+    });
+}
+
+/// The active card or an element within it, if there is one.
+/// The card may be gone by the time an effect fires
+/// (e.g. if it was just removed), in which case this is `None`.
+/// Animations on elements which are removed mid-animation
+/// are simply cancelled by the browser.
+fn active_card(selector: &str) -> Option<Element> {
+    document()
+        .query_selector(&format!(".draggable.active{selector}"))
+        .ok()
+        .flatten()
+}
+
+/// Pulse the active card's scale by the given multiplier and back.
+/// Does nothing if there's no active card.
+fn pulse_active_card(multiplier: f32) {
+    if let Some(elem) = active_card("") {
         let from = card_scale();
-        pulse(&elem, from, from * 1.05, 100.);
+        pulse(&elem, from, from * multiplier, 100.);
     }
 }
 
+pub fn pulse_card() {
+    pulse_active_card(1.05);
+}
+
+/// Signals a rejected scan.
 pub fn shrink_pulse_card() {
-    if let Some(elem) =
-        document().query_selector(".draggable.active").unwrap()
-    {
-        let from = card_scale();
-        pulse(&elem, from, from * 0.95, 100.);
-    }
+    pulse_active_card(0.95);
+}
+
+thread_local! {
+    /// The current level pulse, so it can be cancelled
+    /// (which resets the scale) if interrupted by another.
+    static LEVEL_PULSE: RefCell<Option<Animation>> =
+        const { RefCell::new(None) };
 }
 
 pub fn pulse_level() {
-    if let Some(elem) = document()
-        .query_selector(".draggable.active .project-cost")
-        .unwrap()
-    {
-        pulse(&elem, 1.0, 1.2, 200.);
+    if let Some(elem) = active_card(" .project-cost") {
+        LEVEL_PULSE.with(|current| {
+            if let Some(prev) = current.take() {
+                prev.cancel();
+            }
+            let anim = pulse(&elem, 1.0, 1.2, 200.);
+            *current.borrow_mut() = Some(anim);
+        });
     }
 }
@@ -14,6 +14,7 @@ use crate::{util::to_ws_el, views::create_sentinel};
 use draggable::{DragRect, Draggable};
 
 pub use cards::ScannerCards;
+pub use effects::shake_screen_for;
 pub use process::ProcessScanner;
 pub use project::ProjectScanner;
 
@@ -135,17 +135,23 @@ impl ScannerSpec for ProjectScanner {
                                             &mut ui.queued_upgrades,
                                         ) {
                                             changed = true;
-                                        }
-                                        controls.pulse_level();
+                                            controls.pulse_level();
 
-                                        if p.next_upgrade()
-                                            .is_some()
-                                        {
-                                            keep_scanning = true;
-                                        }
+                                            if p.next_upgrade()
+                                                .is_some()
+                                            {
+                                                keep_scanning = true;
+                                            }
 
-                                        // Refundable upgrade
-                                        changes.upgrades += 1;
+                                            // Refundable upgrade
+                                            changes.upgrades += 1;
+                                        } else {
+                                            // Not enough PC
+                                            if free {
+                                                changes.downgrades += 1;
+                                            }
+                                            controls.reject_scan();
+                                        }
 
                                     // Adding points to Research/Infrastructure
                                     } else if p.kind != ProjectType::Policy
@@ -16,6 +16,7 @@ enum WorldStatus {
     Default,
     Custom(String, World),
     FailedToParse,
+    Invalid(String),
 }
 impl WorldStatus {
     fn is_custom(&self) -> bool {
@@ -143,7 +144,10 @@ pub fn Start(
                                                         if let Some(text) = result.as_string() {
                                                             let w = serde_json::from_str::<World>(&text);
                                                             if let Ok(w) = w {
-                                                                world.set(WorldStatus::Custom(name.clone(), w));
+                                                                match State::new(w.clone()).validate_content() {
+                                                                    Ok(()) => world.set(WorldStatus::Custom(name.clone(), w)),
+                                                                    Err(err) => world.set(WorldStatus::Invalid(err.to_string())),
+                                                                }
                                                             } else {
                                                                 world.set(WorldStatus::FailedToParse);
                                                             }
@@ -164,6 +168,7 @@ pub fn Start(
                                                              WorldStatus::Default => t!("Default World"),
                                                              WorldStatus::Custom(name, _world) => format!("{}: {name}", t!("Custom")),
                                                              WorldStatus::FailedToParse => t!("Failed to parse provided world."),
+                                                             WorldStatus::Invalid(err) => format!("{}: {err}", t!("Invalid world")),
                                                          }
                                                      })
                                                  }}
@@ -1,6 +1,6 @@
 use hes_engine::World;
 use rgb::ComponentBytes;
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
 include!("../assets/scaling_patterns/out/scale_patterns.in");
 include!("../assets/biome_lookup/out/biome_lookup.in");
@@ -37,6 +37,27 @@ const COLORS: [Color; 11] = [
     (26, 176, 59),   // Tropical rain forest
 ];
 
+/// A palette which doesn't have
+/// exactly one color for each biome label.
+#[derive(Debug, PartialEq)]
+pub struct PaletteSizeError {
+    pub expected: usize,
+    pub actual: usize,
+}
+impl Display for PaletteSizeError {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "Palette must have {} colors, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+impl std::error::Error for PaletteSizeError {}
+
 #[derive(Clone)]
 pub struct EarthSurface {
     width: usize,
@@ -45,6 +66,7 @@ pub struct EarthSurface {
     biomes: Vec<BiomeLabel>,
     biome_lookup: Vec<BiomeLabel>,
     intensities: Vec<(BigColor, usize)>,
+    palette: Vec<Color>,
     pub pixels: Vec<u8>,
 }
 
@@ -56,7 +78,9 @@ impl EarthSurface {
         scale: usize,
         lookup: Vec<BiomeLabel>,
     ) -> EarthSurface {
-        let mut pixels: Vec<u8> = biomes_to_pixels(&biomes);
+        let palette = COLORS.to_vec();
+        let mut pixels: Vec<u8> =
+            biomes_to_pixels(&biomes, &palette);
         pixels = nearest_neighbor_scale(
             &pixels, width, height, scale,
         );
@@ -75,6 +99,7 @@ impl EarthSurface {
             pixels,
             scale,
             intensities,
+            palette,
             width: w,
             height: h,
             biome_lookup: lookup,
@@ -89,6 +114,68 @@ impl EarthSurface {
         self.height
     }
 
+    /// Number of (unscaled) biome pixels for each biome label.
+    pub fn biome_histogram(&self) -> Vec<usize> {
+        let mut counts = vec![0; COLORS.len()];
+        for label in &self.biomes {
+            counts[*label as usize] += 1;
+        }
+        counts
+    }
+
+    /// Fraction of the surface covered by the given biome label.
+    pub fn land_cover_fraction(
+        &self,
+        label: BiomeLabel,
+    ) -> f32 {
+        if self.biomes.is_empty() {
+            return 0.;
+        }
+        let count =
+            self.biomes.iter().filter(|l| **l == label).count();
+        count as f32 / self.biomes.len() as f32
+    }
+
+    /// Bytes per pixel in `pixels` (r, g, b).
+    pub fn stride(&self) -> usize {
+        STRIDE
+    }
+
+    /// Length of `pixels` in bytes, i.e. `width * height * stride`.
+    pub fn surface_len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// Swap in a different biome color palette, e.g. a
+    /// colorblind-friendly one. This resets the pixels to the
+    /// current biomes in the new colors, so you'll need to run
+    /// `update_surface()` afterwards.
+    pub fn set_palette(
+        &mut self,
+        colors: Vec<Color>,
+    ) -> Result<(), PaletteSizeError> {
+        // One color for each biome label
+        if colors.len() != COLORS.len() {
+            return Err(PaletteSizeError {
+                expected: COLORS.len(),
+                actual: colors.len(),
+            });
+        }
+        self.palette = colors;
+
+        let pixels =
+            biomes_to_pixels(&self.biomes, &self.palette);
+        self.pixels = nearest_neighbor_scale(
+            &pixels,
+            self.width / self.scale,
+            self.height / self.scale,
+            self.scale,
+        );
+        self.intensities =
+            compute_intensities(&self.pixels).collect();
+        Ok(())
+    }
+
     pub fn update_surface(&mut self) {
         oil_paint_effect(
             &mut self.pixels,
@@ -98,6 +185,70 @@ impl EarthSurface {
         );
     }
 
+    /// Re-apply the oil paint effect only to the pixels
+    /// affected by a change to the biome tile at `(x, y)`,
+    /// i.e. those whose `RADIUS` neighborhood overlaps the tile.
+    pub fn update_surface_region(
+        &mut self,
+        x: usize,
+        y: usize,
+    ) {
+        let x_start = (x * self.scale).saturating_sub(RADIUS);
+        let x_end =
+            ((x + 1) * self.scale + RADIUS).min(self.width);
+        let y_start = (y * self.scale).saturating_sub(RADIUS);
+        let y_end =
+            ((y + 1) * self.scale + RADIUS).min(self.height);
+
+        let mut counts = intensity_counts();
+        for py in y_start..y_end {
+            for px in x_start..x_end {
+                let idx = py * self.width + px;
+                let (r, g, b) = oil_paint_pixel(
+                    idx,
+                    &self.intensities,
+                    self.width,
+                    self.height,
+                    &mut counts,
+                );
+                let i = idx * STRIDE;
+                self.pixels[i..i + STRIDE]
+                    .copy_from_slice(&[r, g, b]);
+            }
+        }
+    }
+
+    /// Apply climate-driven biome transitions for one tick.
+    /// Each biome changes at most once per tick, by the first
+    /// rule that applies to it. Water and cropland never change.
+    pub fn step_biomes(
+        &mut self,
+        rules: &TransitionRules,
+        temp_anomaly: f32,
+        precipitation: f32,
+    ) {
+        let width = self.width / self.scale;
+        for idx in 0..self.biomes.len() {
+            let biome = self.biomes[idx];
+            if biome == 0 || biome == 1 {
+                continue;
+            }
+            let rule = rules.rules.iter().find(|rule| {
+                rule.applies(biome, temp_anomaly, precipitation)
+            });
+            if let Some(rule) = rule {
+                self.biomes[idx] = rule.to;
+                set_block_color(
+                    &mut self.intensities,
+                    idx,
+                    width,
+                    self.scale,
+                    color_for_biome(rule.to, &self.palette),
+                );
+            }
+        }
+    }
+
     pub fn update_biomes(&mut self, tgav: f32) {
         // Above we assert that TEMP_PATTERN_W, TEMP_PATTERN_B, and tgav are all the same size,
         // so no scaling necessary.
@@ -128,27 +279,91 @@ impl EarthSurface {
             );
             if *biome != label {
                 *biome = label;
-                let color = color_for_biome(label);
-                let r = color.0 as usize;
-                let g = color.1 as usize;
-                let b = color.2 as usize;
 
                 // Update intensities
                 // Then you can run `update_surface()` to update the surface pixels
-                let intensity = compute_intensity(r, g, b);
-                for i in scaled_px_indices(
+                set_block_color(
+                    &mut self.intensities,
                     idx,
                     self.width / self.scale,
                     self.scale,
-                ) {
-                    self.intensities[i..i + self.scale]
-                        .fill(((r, g, b), intensity));
-                }
+                    color_for_biome(label, &self.palette),
+                );
             }
         }
     }
 }
 
+/// Set the intensities for the `scale x scale` block of
+/// (scaled) pixels covering the (unscaled) biome at `idx`.
+fn set_block_color(
+    intensities: &mut [(BigColor, usize)],
+    idx: usize,
+    width: usize,
+    scale: usize,
+    color: Color,
+) {
+    let r = color.0 as usize;
+    let g = color.1 as usize;
+    let b = color.2 as usize;
+    let intensity = compute_intensity(r, g, b);
+    for i in scaled_px_indices(idx, width, scale) {
+        intensities[i..i + scale].fill(((r, g, b), intensity));
+    }
+}
+
+/// A biome changes from `from` to `to` when the temperature
+/// anomaly (C) is at least `min_temp_anomaly` and the
+/// precipitation change (cm/year) is at most `max_precip_change`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionRule {
+    pub from: BiomeLabel,
+    pub to: BiomeLabel,
+    pub min_temp_anomaly: f32,
+    pub max_precip_change: f32,
+}
+impl TransitionRule {
+    fn applies(
+        &self,
+        biome: BiomeLabel,
+        temp_anomaly: f32,
+        precipitation: f32,
+    ) -> bool {
+        biome == self.from
+            && temp_anomaly >= self.min_temp_anomaly
+            && precipitation <= self.max_precip_change
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionRules {
+    pub rules: Vec<TransitionRule>,
+}
+impl Default for TransitionRules {
+    // Biomes shifting under warming, see `COLORS` for the labels
+    fn default() -> Self {
+        let rule =
+            |from, to, min_temp_anomaly, max_precip_change| {
+                TransitionRule {
+                    from,
+                    to,
+                    min_temp_anomaly,
+                    max_precip_change,
+                }
+            };
+        TransitionRules {
+            rules: vec![
+                rule(2, 6, 1., f32::INFINITY), // Tundra -> Boreal forest
+                rule(6, 7, 1.5, f32::INFINITY), // Boreal -> Temperate seasonal forest
+                rule(7, 8, 2., 0.), // Temperate seasonal forest -> Woodland/shrubland
+                rule(10, 5, 2., 0.), // Tropical rain forest -> Tropical seasonal forest/savanna
+                rule(9, 7, 2.5, 0.), // Temperate rain forest -> Temperate seasonal forest
+                rule(3, 4, 3., 0.), // Temperate grassland/desert -> Subtropical desert
+            ],
+        }
+    }
+}
+
 // The biome changing logic
 fn biome_for_temp(
     biome: &mut BiomeLabel,
@@ -193,16 +408,19 @@ fn scaled_px_indices(
     (0..scale).map(move |i| scaled_idx + (i * width * scale))
 }
 
-fn color_for_biome(label: u8) -> Color {
-    COLORS[label as usize]
+fn color_for_biome(label: u8, palette: &[Color]) -> Color {
+    palette[label as usize]
 }
 
 // Convert biome labels to RGB
-fn biomes_to_pixels(biomes: &[u8]) -> Vec<u8> {
+fn biomes_to_pixels(
+    biomes: &[u8],
+    palette: &[Color],
+) -> Vec<u8> {
     let mut pixels: Vec<u8> =
         Vec::with_capacity(biomes.len() * STRIDE);
     for label in biomes {
-        let (r, g, b) = color_for_biome(*label);
+        let (r, g, b) = color_for_biome(*label, palette);
         pixels.push(r);
         pixels.push(g);
         pixels.push(b);
@@ -210,6 +428,30 @@ fn biomes_to_pixels(biomes: &[u8]) -> Vec<u8> {
     pixels
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    NearestNeighbor,
+    Bilinear,
+}
+
+// Upscale an RGB image by an integer factor
+pub fn scale_image(
+    img: &[u8],
+    width: usize,
+    height: usize,
+    scale: usize,
+    mode: ScaleMode,
+) -> Vec<u8> {
+    match mode {
+        ScaleMode::NearestNeighbor => {
+            nearest_neighbor_scale(img, width, height, scale)
+        }
+        ScaleMode::Bilinear => {
+            bilinear_scale(img, width, height, scale)
+        }
+    }
+}
+
 fn nearest_neighbor_scale(
     img: &[u8],
     width: usize,
@@ -234,6 +476,48 @@ fn nearest_neighbor_scale(
     result
 }
 
+// Interpolates between the centers of the source pixels,
+// clamping at the edges.
+fn bilinear_scale(
+    img: &[u8],
+    width: usize,
+    height: usize,
+    scale: usize,
+) -> Vec<u8> {
+    let new_width = width * scale;
+    let new_height = height * scale;
+    let mut result: Vec<u8> =
+        Vec::with_capacity(new_width * new_height * STRIDE);
+
+    // Source position and weight of the next pixel along
+    let source = |i: usize, len: usize| {
+        let pos = ((i as f32 + 0.5) / scale as f32 - 0.5)
+            .clamp(0., (len - 1) as f32);
+        let lo = pos.floor() as usize;
+        let hi = (lo + 1).min(len - 1);
+        (lo, hi, pos - lo as f32)
+    };
+
+    for i in 0..new_height {
+        let (y0, y1, fy) = source(i, height);
+        for j in 0..new_width {
+            let (x0, x1, fx) = source(j, width);
+            for c in 0..STRIDE {
+                let px = |x: usize, y: usize| {
+                    img[(y * width + x) * STRIDE + c] as f32
+                };
+                let top =
+                    px(x0, y0) * (1. - fx) + px(x1, y0) * fx;
+                let bot =
+                    px(x0, y1) * (1. - fx) + px(x1, y1) * fx;
+                let val = top * (1. - fy) + bot * fy;
+                result.push(val.round() as u8);
+            }
+        }
+    }
+    result
+}
+
 // Compute pixel intensities, for applying the oil paint effect
 pub fn compute_intensities<'a>(
     img: &'a [u8],
@@ -251,63 +535,133 @@ fn compute_intensity(r: usize, g: usize, b: usize) -> usize {
     ((avg * INTENSITY) / 255.).round() as usize
 }
 
+type IntensityCounts = Vec<(usize, BigColor)>;
+
+fn intensity_counts() -> IntensityCounts {
+    vec![(0, (0, 0, 0)); INTENSITY as usize + 1]
+}
+
 // Ported from <https://codepen.io/loktar00/pen/Fhzot>
+// Each pixel is computed independently from the intensities,
+// so this runs in parallel when the `parallel` feature is enabled.
+#[cfg(feature = "parallel")]
 pub fn oil_paint_effect(
     pixels: &mut [u8],
     intensities: &[(BigColor, usize)],
     width: usize,
     height: usize,
 ) {
-    // For each pixel, get the most common intensity value of the neighbors in radius
-    let mut pixel_intensity_count: Vec<(usize, BigColor)> =
-        vec![(0, (0, 0, 0)); INTENSITY as usize + 1];
-    for idx in 0..intensities.len() {
-        pixel_intensity_count.fill((0, (0, 0, 0)));
-
-        // Find intensities of nearest pixels within radius.
-        let x = idx % width;
-        let y = idx / width;
-        let up_span = y.min(RADIUS); // rows to traverse up from idx
-        let down_span = (height - y - 1).min(RADIUS); // rows to traverse down from idx
-        let left_span = x.min(RADIUS); // rows to traverse left from idx
-        let right_span = (width - x - 1).min(RADIUS); // rows to traverse right from idx
-        let y_span = up_span + down_span + 1; // rows to traverse up and down, including idx
-        let start_idx = idx - (up_span * width);
-
-        for i in 0..y_span {
-            let midpoint = start_idx + i * width;
-            for (rgb, intensity_val) in &intensities
-                [midpoint - left_span..midpoint + right_span]
-            {
-                let count =
-                    &mut pixel_intensity_count[*intensity_val];
-
-                count.0 += 1;
-                count.1 .0 += rgb.0;
-                count.1 .1 += rgb.1;
-                count.1 .2 += rgb.2;
-            }
-        }
+    use rayon::prelude::*;
 
-        // Max intensity value
-        let top = pixel_intensity_count.iter().fold(
-            (0, (0, 0, 0)),
-            |acc, count| {
-                if count.0 > acc.0 {
-                    *count
-                } else {
-                    acc
-                }
+    // Each worker gets its own intensity counts to reuse.
+    pixels
+        .par_chunks_exact_mut(STRIDE)
+        .enumerate()
+        .for_each_init(
+            intensity_counts,
+            |counts, (idx, px)| {
+                let (r, g, b) = oil_paint_pixel(
+                    idx,
+                    intensities,
+                    width,
+                    height,
+                    counts,
+                );
+                px[0] = r;
+                px[1] = g;
+                px[2] = b;
             },
         );
+}
 
-        let i = idx * STRIDE;
-        pixels[i] = !!(top.1 .0 / top.0) as u8; // r
-        pixels[i + 1] = !!(top.1 .1 / top.0) as u8; // g
-        pixels[i + 2] = !!(top.1 .2 / top.0) as u8; // b
+#[cfg(not(feature = "parallel"))]
+pub fn oil_paint_effect(
+    pixels: &mut [u8],
+    intensities: &[(BigColor, usize)],
+    width: usize,
+    height: usize,
+) {
+    oil_paint_effect_serial(pixels, intensities, width, height);
+}
+
+pub fn oil_paint_effect_serial(
+    pixels: &mut [u8],
+    intensities: &[(BigColor, usize)],
+    width: usize,
+    height: usize,
+) {
+    let mut counts = intensity_counts();
+    for (idx, px) in pixels.chunks_exact_mut(STRIDE).enumerate()
+    {
+        let (r, g, b) = oil_paint_pixel(
+            idx,
+            intensities,
+            width,
+            height,
+            &mut counts,
+        );
+        px[0] = r;
+        px[1] = g;
+        px[2] = b;
     }
 }
 
+// For a pixel, get the most common intensity value
+// of the neighbors in radius and average their colors.
+fn oil_paint_pixel(
+    idx: usize,
+    intensities: &[(BigColor, usize)],
+    width: usize,
+    height: usize,
+    pixel_intensity_count: &mut IntensityCounts,
+) -> Color {
+    pixel_intensity_count.fill((0, (0, 0, 0)));
+
+    // Find intensities of nearest pixels within radius.
+    let x = idx % width;
+    let y = idx / width;
+    let up_span = y.min(RADIUS); // rows to traverse up from idx
+    let down_span = (height - y - 1).min(RADIUS); // rows to traverse down from idx
+    let left_span = x.min(RADIUS); // rows to traverse left from idx
+    let right_span = (width - x - 1).min(RADIUS); // rows to traverse right from idx
+    let y_span = up_span + down_span + 1; // rows to traverse up and down, including idx
+    let start_idx = idx - (up_span * width);
+
+    for i in 0..y_span {
+        let midpoint = start_idx + i * width;
+        for (rgb, intensity_val) in &intensities
+            [midpoint - left_span..midpoint + right_span]
+        {
+            let count =
+                &mut pixel_intensity_count[*intensity_val];
+
+            count.0 += 1;
+            count.1 .0 += rgb.0;
+            count.1 .1 += rgb.1;
+            count.1 .2 += rgb.2;
+        }
+    }
+
+    // Max intensity value
+    let top = pixel_intensity_count.iter().fold(
+        (0, (0, 0, 0)),
+        |acc, count| {
+            if count.0 > acc.0 {
+                *count
+            } else {
+                acc
+            }
+        },
+    );
+
+    // Averages of u8 values, so these always fit in a u8
+    (
+        (top.1 .0 / top.0) as u8, // r
+        (top.1 .1 / top.0) as u8, // g
+        (top.1 .2 / top.0) as u8, // b
+    )
+}
+
 /*
 Applies tgav from Hector over a scaling pattern,
 to spatialize temperatures to a grid.
@@ -486,6 +840,20 @@ fn main() {
 mod test {
     use super::*;
     use float_cmp::approx_eq;
+    use std::time::Instant;
+
+    // Random-ish but repeatable image.
+    fn noise_image(width: usize, height: usize) -> Vec<u8> {
+        let mut seed: u32 = 7;
+        (0..width * height * STRIDE)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(1103515245)
+                    .wrapping_add(12345);
+                (seed >> 16) as u8
+            })
+            .collect()
+    }
 
     #[test]
     fn test_apply_pscl() {
@@ -529,6 +897,48 @@ mod test {
             .all(|(x1, x2)| *x1 == x2));
     }
 
+    #[test]
+    fn test_scale_image_modes() {
+        // 2x2 grayscale, expanded to rgb
+        let gray = |vals: &[u8]| -> Vec<u8> {
+            vals.iter().flat_map(|v| [*v; STRIDE]).collect()
+        };
+        let img = gray(&[0, 100, 200, 200]);
+
+        let nearest = scale_image(
+            &img,
+            2,
+            2,
+            2,
+            ScaleMode::NearestNeighbor,
+        );
+        assert_eq!(
+            nearest,
+            gray(&[
+                0, 0, 100, 100, //
+                0, 0, 100, 100, //
+                200, 200, 200, 200, //
+                200, 200, 200, 200,
+            ])
+        );
+        assert_eq!(
+            nearest,
+            nearest_neighbor_scale(&img, 2, 2, 2)
+        );
+
+        let bilinear =
+            scale_image(&img, 2, 2, 2, ScaleMode::Bilinear);
+        assert_eq!(
+            bilinear,
+            gray(&[
+                0, 25, 75, 100, //
+                50, 69, 106, 125, //
+                150, 156, 169, 175, //
+                200, 200, 200, 200,
+            ])
+        );
+    }
+
     #[test]
     fn test_scale_idx() {
         let mut scale = 2;
@@ -548,6 +958,49 @@ mod test {
         assert!(scale_idx(5, width, scale) == 56);
     }
 
+    #[test]
+    fn test_set_block_color() {
+        let width = 3;
+        let height = 2;
+        let scale = 2;
+        let mut intensities =
+            vec![
+                ((0, 0, 0), 0);
+                width * height * scale * scale
+            ];
+
+        // The biome at (1, 1) covers scaled rows 2-3, cols 2-3.
+        let color = COLORS[2];
+        set_block_color(
+            &mut intensities,
+            4,
+            width,
+            scale,
+            color,
+        );
+
+        let scaled_width = width * scale;
+        let block = [
+            2 * scaled_width + 2,
+            2 * scaled_width + 3,
+            3 * scaled_width + 2,
+            3 * scaled_width + 3,
+        ];
+        for (i, (rgb, _)) in intensities.iter().enumerate() {
+            if block.contains(&i) {
+                assert!(
+                    *rgb == (
+                        color.0 as usize,
+                        color.1 as usize,
+                        color.2 as usize
+                    )
+                );
+            } else {
+                assert!(*rgb == (0, 0, 0));
+            }
+        }
+    }
+
     #[test]
     fn test_scaled_indices() {
         let scale = 3;
@@ -573,4 +1026,259 @@ mod test {
         ];
         assert!(expected_image == scaled_image);
     }
+
+    #[test]
+    fn test_oil_paint_parallel_matches_serial() {
+        let (width, height) = (37, 23);
+        let img = noise_image(width, height);
+        let intensities: Vec<_> =
+            compute_intensities(&img).collect();
+
+        let mut parallel = img.clone();
+        oil_paint_effect(
+            &mut parallel,
+            &intensities,
+            width,
+            height,
+        );
+
+        let mut serial = img.clone();
+        oil_paint_effect_serial(
+            &mut serial,
+            &intensities,
+            width,
+            height,
+        );
+        assert_eq!(parallel, serial);
+        assert_ne!(serial, img);
+    }
+
+    #[test]
+    fn test_oil_paint_pixel_average() {
+        // The most common intensity (2) is shared by
+        // the two bright pixels, which should be averaged.
+        let intensities = vec![
+            ((255, 255, 255), 2),
+            ((245, 235, 225), 2),
+            ((10, 10, 10), 0),
+            ((0, 0, 0), 1),
+        ];
+        let mut counts = intensity_counts();
+        let color =
+            oil_paint_pixel(0, &intensities, 4, 1, &mut counts);
+        assert_eq!(color, (250, 245, 240));
+
+        // Averages of maximum values stay in range.
+        let intensities = vec![((255, 255, 255), 25); 4];
+        let color =
+            oil_paint_pixel(0, &intensities, 4, 1, &mut counts);
+        assert_eq!(color, (255, 255, 255));
+    }
+
+    // Build a surface directly, as `EarthSurface::new`
+    // expects biomes matching the scaling patterns.
+    fn test_surface(
+        biomes: Vec<BiomeLabel>,
+        width: usize,
+        height: usize,
+        scale: usize,
+    ) -> EarthSurface {
+        let pixels = nearest_neighbor_scale(
+            &biomes_to_pixels(&biomes, &COLORS),
+            width,
+            height,
+            scale,
+        );
+        let intensities =
+            compute_intensities(&pixels).collect();
+        EarthSurface {
+            width: width * scale,
+            height: height * scale,
+            scale,
+            biomes,
+            biome_lookup: vec![],
+            intensities,
+            palette: COLORS.to_vec(),
+            pixels,
+        }
+    }
+
+    #[test]
+    fn test_update_surface_region() {
+        let (width, height, scale) = (6, 4, 3);
+        let biomes: Vec<BiomeLabel> = (0..width * height)
+            .map(|i| (i % COLORS.len()) as BiomeLabel)
+            .collect();
+        let mut surface =
+            test_surface(biomes, width, height, scale);
+        surface.update_surface();
+
+        // Change a single tile.
+        let (x, y) = (2, 1);
+        set_block_color(
+            &mut surface.intensities,
+            y * width + x,
+            width,
+            scale,
+            COLORS[0],
+        );
+        let mut full = surface.clone();
+        full.update_surface();
+        surface.update_surface_region(x, y);
+        assert!(full.pixels == surface.pixels);
+        assert_eq!(
+            surface.surface_len(),
+            surface.width()
+                * surface.height()
+                * surface.stride()
+        );
+    }
+
+    // Run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_oil_paint_effect() {
+        let (width, height) = (2000, 1000);
+        let img = noise_image(width, height);
+        let intensities: Vec<_> =
+            compute_intensities(&img).collect();
+
+        let mut pixels = img.clone();
+        let start = Instant::now();
+        oil_paint_effect_serial(
+            &mut pixels,
+            &intensities,
+            width,
+            height,
+        );
+        println!("Serial: {:?}", start.elapsed());
+
+        let mut pixels = img.clone();
+        let start = Instant::now();
+        oil_paint_effect(
+            &mut pixels,
+            &intensities,
+            width,
+            height,
+        );
+        println!("Default: {:?}", start.elapsed());
+    }
+
+    #[test]
+    fn test_set_palette() {
+        let (width, height, scale) = (6, 4, 3);
+        let biomes: Vec<BiomeLabel> = (0..width * height)
+            .map(|i| (i % COLORS.len()) as BiomeLabel)
+            .collect();
+        let mut surface =
+            test_surface(biomes, width, height, scale);
+        surface.update_surface();
+        let original = surface.pixels.clone();
+
+        let mut palette = COLORS.to_vec();
+        palette.reverse();
+        surface.set_palette(palette.clone()).unwrap();
+
+        // The first tile's block is the new color for its biome.
+        let label = surface.biomes[0] as usize;
+        let (r, g, b) = palette[label];
+        assert_eq!(&surface.pixels[..STRIDE], &[r, g, b]);
+
+        surface.update_surface();
+        assert!(surface.pixels != original);
+
+        // Swapping to the same palette gives the same output.
+        let mut other = surface.clone();
+        other.set_palette(palette).unwrap();
+        other.update_surface();
+        assert!(other.pixels == surface.pixels);
+
+        // And swapping back restores the original.
+        surface.set_palette(COLORS.to_vec()).unwrap();
+        surface.update_surface();
+        assert!(surface.pixels == original);
+    }
+
+    #[test]
+    fn test_set_palette_wrong_size() {
+        let mut surface = test_surface(vec![0; 4], 2, 2, 2);
+        let pixels = surface.pixels.clone();
+        assert_eq!(
+            surface.set_palette(vec![(0, 0, 0)]),
+            Err(PaletteSizeError {
+                expected: COLORS.len(),
+                actual: 1,
+            })
+        );
+
+        // The surface is left as it was.
+        assert!(surface.palette == COLORS.to_vec());
+        assert!(surface.pixels == pixels);
+    }
+
+    #[test]
+    fn test_biome_histogram() {
+        let biomes = vec![
+            0, 0, 0, 1, //
+            0, 1, 6, 6,
+        ];
+        let surface = test_surface(biomes, 4, 2, 2);
+        let hist = surface.biome_histogram();
+        assert_eq!(hist.len(), COLORS.len());
+        assert_eq!(hist[0], 4);
+        assert_eq!(hist[1], 2);
+        assert_eq!(hist[6], 2);
+        assert_eq!(hist.iter().sum::<usize>(), 8);
+
+        assert!(approx_eq!(
+            f32,
+            surface.land_cover_fraction(0),
+            0.5
+        ));
+        assert!(approx_eq!(
+            f32,
+            surface.land_cover_fraction(6),
+            0.25
+        ));
+        assert!(approx_eq!(
+            f32,
+            surface.land_cover_fraction(10),
+            0.
+        ));
+    }
+
+    #[test]
+    fn test_step_biomes() {
+        // Water, cropland, tundra, boreal forest
+        let biomes = vec![0, 1, 2, 6];
+        let mut surface = test_surface(biomes, 4, 1, 2);
+        let rules = TransitionRules::default();
+
+        // No warming, no change
+        surface.step_biomes(&rules, 0., 0.);
+        assert_eq!(surface.biomes, vec![0, 1, 2, 6]);
+
+        // Each biome only moves one step per tick
+        surface.step_biomes(&rules, 2., 0.);
+        assert_eq!(surface.biomes, vec![0, 1, 6, 7]);
+
+        // Intensities follow the new biomes
+        let (r, g, b) = COLORS[6];
+        assert_eq!(
+            surface.intensities[2 * 2].0,
+            (r as usize, g as usize, b as usize)
+        );
+
+        // Water never changes, even with a matching rule
+        let rules = TransitionRules {
+            rules: vec![TransitionRule {
+                from: 0,
+                to: 4,
+                min_temp_anomaly: 0.,
+                max_precip_change: f32::INFINITY,
+            }],
+        };
+        surface.step_biomes(&rules, 5., 0.);
+        assert_eq!(surface.biomes[0], 0);
+    }
 }